@@ -0,0 +1,7 @@
+--
+-- Comment editing and soft deletion
+--
+
+ALTER TABLE public.post_comments
+    ADD COLUMN edited_at timestamp without time zone,
+    ADD COLUMN deleted_at timestamp without time zone;
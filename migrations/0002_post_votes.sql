@@ -0,0 +1,25 @@
+--
+-- Post voting
+--
+
+CREATE TABLE public.post_votes (
+    user_id integer NOT NULL,
+    post_id integer NOT NULL,
+    value smallint NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    CONSTRAINT post_votes_value_check CHECK ((value = ANY (ARRAY['-1'::integer, 1])))
+);
+
+
+ALTER TABLE public.post_votes OWNER TO forum;
+
+ALTER TABLE ONLY public.post_votes
+    ADD CONSTRAINT post_votes_pkey PRIMARY KEY (user_id, post_id);
+
+CREATE INDEX post_votes_post_id_index ON public.post_votes USING btree (post_id);
+
+ALTER TABLE ONLY public.post_votes
+    ADD CONSTRAINT post_votes_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.post_votes
+    ADD CONSTRAINT post_votes_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
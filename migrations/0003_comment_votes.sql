@@ -0,0 +1,25 @@
+--
+-- Comment voting
+--
+
+CREATE TABLE public.comment_votes (
+    user_id integer NOT NULL,
+    comment_id integer NOT NULL,
+    value smallint NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    CONSTRAINT comment_votes_value_check CHECK ((value = ANY (ARRAY['-1'::integer, 1])))
+);
+
+
+ALTER TABLE public.comment_votes OWNER TO forum;
+
+ALTER TABLE ONLY public.comment_votes
+    ADD CONSTRAINT comment_votes_pkey PRIMARY KEY (user_id, comment_id);
+
+CREATE INDEX comment_votes_comment_id_index ON public.comment_votes USING btree (comment_id);
+
+ALTER TABLE ONLY public.comment_votes
+    ADD CONSTRAINT comment_votes_comment_id_fkey FOREIGN KEY (comment_id) REFERENCES public.post_comments(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.comment_votes
+    ADD CONSTRAINT comment_votes_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
@@ -0,0 +1,7 @@
+--
+-- Indexes for keyset pagination of posts and comments
+--
+
+CREATE INDEX posts_created_at_id_index ON public.posts USING btree (created_at, id);
+
+CREATE INDEX post_comments_post_id_created_at_id_index ON public.post_comments USING btree (post_id, created_at, id);
@@ -0,0 +1,13 @@
+--
+-- Full-text search over posts and comments
+--
+
+ALTER TABLE public.posts
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS ((setweight(to_tsvector('english'::regconfig, (title)::text), 'A'::"char") || setweight(to_tsvector('english'::regconfig, (body)::text), 'B'::"char"))) STORED;
+
+ALTER TABLE public.post_comments
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (to_tsvector('english'::regconfig, (body)::text)) STORED;
+
+CREATE INDEX posts_search_vector_index ON public.posts USING gin (search_vector);
+
+CREATE INDEX post_comments_search_vector_index ON public.post_comments USING gin (search_vector);
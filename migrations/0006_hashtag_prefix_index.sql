@@ -0,0 +1,5 @@
+--
+-- Prefix index for hashtag suggestions
+--
+
+CREATE INDEX hashtags_name_pattern_index ON public.hashtags USING btree (name varchar_pattern_ops);
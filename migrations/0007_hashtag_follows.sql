@@ -0,0 +1,23 @@
+--
+-- Hashtag follows
+--
+
+CREATE TABLE public.hashtag_follows (
+    user_id integer NOT NULL,
+    hashtag_id integer NOT NULL,
+    created_at timestamp without time zone NOT NULL
+);
+
+
+ALTER TABLE public.hashtag_follows OWNER TO forum;
+
+ALTER TABLE ONLY public.hashtag_follows
+    ADD CONSTRAINT hashtag_follows_pkey PRIMARY KEY (user_id, hashtag_id);
+
+CREATE INDEX hashtag_follows_hashtag_id_index ON public.hashtag_follows USING btree (hashtag_id);
+
+ALTER TABLE ONLY public.hashtag_follows
+    ADD CONSTRAINT hashtag_follows_hashtag_id_fkey FOREIGN KEY (hashtag_id) REFERENCES public.hashtags(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.hashtag_follows
+    ADD CONSTRAINT hashtag_follows_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
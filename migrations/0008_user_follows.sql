@@ -0,0 +1,24 @@
+--
+-- User follows
+--
+
+CREATE TABLE public.user_follows (
+    follower_id integer NOT NULL,
+    following_id integer NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    CONSTRAINT user_follows_check CHECK ((follower_id <> following_id))
+);
+
+
+ALTER TABLE public.user_follows OWNER TO forum;
+
+ALTER TABLE ONLY public.user_follows
+    ADD CONSTRAINT user_follows_pkey PRIMARY KEY (follower_id, following_id);
+
+CREATE INDEX user_follows_following_id_index ON public.user_follows USING btree (following_id);
+
+ALTER TABLE ONLY public.user_follows
+    ADD CONSTRAINT user_follows_follower_id_fkey FOREIGN KEY (follower_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.user_follows
+    ADD CONSTRAINT user_follows_following_id_fkey FOREIGN KEY (following_id) REFERENCES public.users(id) ON DELETE CASCADE;
@@ -0,0 +1,10 @@
+--
+-- Editable user profiles
+--
+
+ALTER TABLE public.users
+    ADD COLUMN display_name character varying(50),
+    ADD COLUMN bio character varying(500),
+    ADD COLUMN website character varying(200),
+    ADD COLUMN location character varying(100),
+    ADD COLUMN avatar_url character varying(500);
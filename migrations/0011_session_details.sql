@@ -0,0 +1,13 @@
+--
+-- Session details for listing and revoking sessions
+--
+
+ALTER TABLE public.sessions
+    ADD COLUMN last_seen_at timestamp without time zone,
+    ADD COLUMN user_agent character varying(500),
+    ADD COLUMN ip character varying(45);
+
+UPDATE public.sessions SET last_seen_at = created_at;
+
+ALTER TABLE public.sessions
+    ALTER COLUMN last_seen_at SET NOT NULL;
@@ -0,0 +1,50 @@
+--
+-- Email verification and password reset
+--
+
+ALTER TABLE public.users
+    ADD COLUMN email character varying(254),
+    ADD COLUMN email_verified_at timestamp without time zone;
+
+CREATE UNIQUE INDEX email_lower_unique_index ON public.users USING btree (lower((email)::text));
+
+CREATE TABLE public.email_tokens (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    purpose character varying(20) NOT NULL,
+    email character varying(254) NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone,
+    CONSTRAINT email_tokens_purpose_check CHECK (((purpose)::text = ANY ((ARRAY['verify_email'::character varying, 'reset_password'::character varying])::text[])))
+);
+
+
+ALTER TABLE public.email_tokens OWNER TO forum;
+
+CREATE SEQUENCE public.email_tokens_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.email_tokens_id_seq OWNER TO forum;
+
+ALTER SEQUENCE public.email_tokens_id_seq OWNED BY public.email_tokens.id;
+
+ALTER TABLE ONLY public.email_tokens ALTER COLUMN id SET DEFAULT nextval('public.email_tokens_id_seq'::regclass);
+
+ALTER TABLE ONLY public.email_tokens
+    ADD CONSTRAINT email_tokens_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.email_tokens
+    ADD CONSTRAINT email_tokens_token_hash_key UNIQUE (token_hash);
+
+CREATE INDEX email_tokens_user_id_index ON public.email_tokens USING btree (user_id);
+
+ALTER TABLE ONLY public.email_tokens
+    ADD CONSTRAINT email_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
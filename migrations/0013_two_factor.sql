@@ -0,0 +1,93 @@
+--
+-- Two-factor authentication
+--
+
+CREATE TABLE public.login_challenges (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    attempts integer DEFAULT 0 NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
+);
+
+
+ALTER TABLE public.login_challenges OWNER TO forum;
+
+CREATE SEQUENCE public.login_challenges_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.login_challenges_id_seq OWNER TO forum;
+
+ALTER SEQUENCE public.login_challenges_id_seq OWNED BY public.login_challenges.id;
+
+CREATE TABLE public.totp_recovery_codes (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    code_hash character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
+);
+
+
+ALTER TABLE public.totp_recovery_codes OWNER TO forum;
+
+CREATE SEQUENCE public.totp_recovery_codes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.totp_recovery_codes_id_seq OWNER TO forum;
+
+ALTER SEQUENCE public.totp_recovery_codes_id_seq OWNED BY public.totp_recovery_codes.id;
+
+CREATE TABLE public.user_totp (
+    user_id integer NOT NULL,
+    secret character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    confirmed_at timestamp without time zone,
+    last_used_step bigint
+);
+
+
+ALTER TABLE public.user_totp OWNER TO forum;
+
+ALTER TABLE ONLY public.login_challenges ALTER COLUMN id SET DEFAULT nextval('public.login_challenges_id_seq'::regclass);
+
+ALTER TABLE ONLY public.totp_recovery_codes ALTER COLUMN id SET DEFAULT nextval('public.totp_recovery_codes_id_seq'::regclass);
+
+ALTER TABLE ONLY public.login_challenges
+    ADD CONSTRAINT login_challenges_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.login_challenges
+    ADD CONSTRAINT login_challenges_token_hash_key UNIQUE (token_hash);
+
+ALTER TABLE ONLY public.totp_recovery_codes
+    ADD CONSTRAINT totp_recovery_codes_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.user_totp
+    ADD CONSTRAINT user_totp_pkey PRIMARY KEY (user_id);
+
+CREATE INDEX login_challenges_user_id_index ON public.login_challenges USING btree (user_id);
+
+CREATE INDEX totp_recovery_codes_user_id_index ON public.totp_recovery_codes USING btree (user_id);
+
+ALTER TABLE ONLY public.login_challenges
+    ADD CONSTRAINT login_challenges_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.totp_recovery_codes
+    ADD CONSTRAINT totp_recovery_codes_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.user_totp
+    ADD CONSTRAINT user_totp_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
@@ -0,0 +1,81 @@
+--
+-- OpenID Connect sign-in
+--
+
+CREATE TABLE public.oidc_states (
+    id integer NOT NULL,
+    state_hash character varying(64) NOT NULL,
+    provider character varying(50) NOT NULL,
+    pkce_verifier character varying(128) NOT NULL,
+    nonce character varying(128) NOT NULL,
+    user_id integer,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
+);
+
+
+ALTER TABLE public.oidc_states OWNER TO forum;
+
+CREATE SEQUENCE public.oidc_states_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.oidc_states_id_seq OWNER TO forum;
+
+ALTER SEQUENCE public.oidc_states_id_seq OWNED BY public.oidc_states.id;
+
+CREATE TABLE public.user_identities (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    provider character varying(50) NOT NULL,
+    subject character varying(255) NOT NULL,
+    email character varying(254),
+    created_at timestamp without time zone NOT NULL,
+    last_used_at timestamp without time zone NOT NULL
+);
+
+
+ALTER TABLE public.user_identities OWNER TO forum;
+
+CREATE SEQUENCE public.user_identities_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.user_identities_id_seq OWNER TO forum;
+
+ALTER SEQUENCE public.user_identities_id_seq OWNED BY public.user_identities.id;
+
+ALTER TABLE ONLY public.oidc_states ALTER COLUMN id SET DEFAULT nextval('public.oidc_states_id_seq'::regclass);
+
+ALTER TABLE ONLY public.user_identities ALTER COLUMN id SET DEFAULT nextval('public.user_identities_id_seq'::regclass);
+
+ALTER TABLE ONLY public.oidc_states
+    ADD CONSTRAINT oidc_states_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.oidc_states
+    ADD CONSTRAINT oidc_states_state_hash_key UNIQUE (state_hash);
+
+ALTER TABLE ONLY public.user_identities
+    ADD CONSTRAINT user_identities_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.user_identities
+    ADD CONSTRAINT user_identities_provider_subject_key UNIQUE (provider, subject);
+
+CREATE INDEX user_identities_user_id_index ON public.user_identities USING btree (user_id);
+
+ALTER TABLE ONLY public.oidc_states
+    ADD CONSTRAINT oidc_states_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+ALTER TABLE ONLY public.user_identities
+    ADD CONSTRAINT user_identities_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
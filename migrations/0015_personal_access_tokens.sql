@@ -0,0 +1,54 @@
+--
+-- Personal access tokens
+--
+
+CREATE TYPE public.token_scope AS ENUM (
+    'read',
+    'post',
+    'comment',
+    'moderate'
+);
+
+
+ALTER TYPE public.token_scope OWNER TO forum;
+
+CREATE TABLE public.personal_access_tokens (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    name character varying(100) NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    scopes public.token_scope[] NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone,
+    last_used_at timestamp without time zone,
+    revoked_at timestamp without time zone
+);
+
+
+ALTER TABLE public.personal_access_tokens OWNER TO forum;
+
+CREATE SEQUENCE public.personal_access_tokens_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.personal_access_tokens_id_seq OWNER TO forum;
+
+ALTER SEQUENCE public.personal_access_tokens_id_seq OWNED BY public.personal_access_tokens.id;
+
+ALTER TABLE ONLY public.personal_access_tokens ALTER COLUMN id SET DEFAULT nextval('public.personal_access_tokens_id_seq'::regclass);
+
+ALTER TABLE ONLY public.personal_access_tokens
+    ADD CONSTRAINT personal_access_tokens_pkey PRIMARY KEY (id);
+
+ALTER TABLE ONLY public.personal_access_tokens
+    ADD CONSTRAINT personal_access_tokens_token_hash_key UNIQUE (token_hash);
+
+CREATE INDEX personal_access_tokens_user_id_index ON public.personal_access_tokens USING btree (user_id);
+
+ALTER TABLE ONLY public.personal_access_tokens
+    ADD CONSTRAINT personal_access_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
@@ -0,0 +1,16 @@
+--
+-- Sign-in and sign-up throttling
+--
+
+CREATE TABLE public.auth_attempts (
+    key character varying(255) NOT NULL,
+    count integer NOT NULL,
+    last_attempt_at timestamp without time zone NOT NULL,
+    previous_attempt_at timestamp without time zone NOT NULL
+);
+
+
+ALTER TABLE public.auth_attempts OWNER TO forum;
+
+ALTER TABLE ONLY public.auth_attempts
+    ADD CONSTRAINT auth_attempts_pkey PRIMARY KEY (key);
@@ -31,6 +31,20 @@ CREATE TYPE public.color AS ENUM (
 
 ALTER TYPE public.color OWNER TO forum;
 
+--
+-- Name: token_scope; Type: TYPE; Schema: public; Owner: forum
+--
+
+CREATE TYPE public.token_scope AS ENUM (
+    'read',
+    'post',
+    'comment',
+    'moderate'
+);
+
+
+ALTER TYPE public.token_scope OWNER TO forum;
+
 SET default_tablespace = '';
 
 SET default_table_access_method = heap;
@@ -50,26 +64,58 @@ CREATE TABLE public.hashtags (
 ALTER TABLE public.hashtags OWNER TO forum;
 
 --
--- Name: post_comments; Type: TABLE; Schema: public; Owner: forum
+-- Name: auth_attempts; Type: TABLE; Schema: public; Owner: forum
 --
 
-CREATE TABLE public.post_comments (
+CREATE TABLE public.auth_attempts (
+    key character varying(255) NOT NULL,
+    count integer NOT NULL,
+    last_attempt_at timestamp without time zone NOT NULL,
+    previous_attempt_at timestamp without time zone NOT NULL
+);
+
+
+ALTER TABLE public.auth_attempts OWNER TO forum;
+
+--
+-- Name: comment_votes; Type: TABLE; Schema: public; Owner: forum
+--
+
+CREATE TABLE public.comment_votes (
+    user_id integer NOT NULL,
+    comment_id integer NOT NULL,
+    value smallint NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    CONSTRAINT comment_votes_value_check CHECK ((value = ANY (ARRAY['-1'::integer, 1])))
+);
+
+
+ALTER TABLE public.comment_votes OWNER TO forum;
+
+--
+-- Name: email_tokens; Type: TABLE; Schema: public; Owner: forum
+--
+
+CREATE TABLE public.email_tokens (
     id integer NOT NULL,
-    body character varying(500) NOT NULL,
-    post_id integer NOT NULL,
     user_id integer NOT NULL,
-    comment_id integer,
-    created_at timestamp without time zone NOT NULL
+    purpose character varying(20) NOT NULL,
+    email character varying(254) NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone,
+    CONSTRAINT email_tokens_purpose_check CHECK (((purpose)::text = ANY ((ARRAY['verify_email'::character varying, 'reset_password'::character varying])::text[])))
 );
 
 
-ALTER TABLE public.post_comments OWNER TO forum;
+ALTER TABLE public.email_tokens OWNER TO forum;
 
 --
--- Name: post_comments_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+-- Name: email_tokens_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-CREATE SEQUENCE public.post_comments_id_seq
+CREATE SEQUENCE public.email_tokens_id_seq
     AS integer
     START WITH 1
     INCREMENT BY 1
@@ -78,47 +124,50 @@ CREATE SEQUENCE public.post_comments_id_seq
     CACHE 1;
 
 
-ALTER SEQUENCE public.post_comments_id_seq OWNER TO forum;
+ALTER SEQUENCE public.email_tokens_id_seq OWNER TO forum;
 
 --
--- Name: post_comments_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+-- Name: email_tokens_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER SEQUENCE public.post_comments_id_seq OWNED BY public.post_comments.id;
+ALTER SEQUENCE public.email_tokens_id_seq OWNED BY public.email_tokens.id;
 
 
 --
--- Name: posts; Type: TABLE; Schema: public; Owner: forum
+-- Name: hashtag_follows; Type: TABLE; Schema: public; Owner: forum
 --
 
-CREATE TABLE public.posts (
-    id integer NOT NULL,
-    title character varying(100) NOT NULL,
-    body character varying(5000) NOT NULL,
+CREATE TABLE public.hashtag_follows (
     user_id integer NOT NULL,
+    hashtag_id integer NOT NULL,
     created_at timestamp without time zone NOT NULL
 );
 
 
-ALTER TABLE public.posts OWNER TO forum;
+ALTER TABLE public.hashtag_follows OWNER TO forum;
 
 --
--- Name: posts_hashtags_relationship; Type: TABLE; Schema: public; Owner: forum
+-- Name: login_challenges; Type: TABLE; Schema: public; Owner: forum
 --
 
-CREATE TABLE public.posts_hashtags_relationship (
-    post_id integer NOT NULL,
-    hashtag_id integer NOT NULL
+CREATE TABLE public.login_challenges (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    attempts integer DEFAULT 0 NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
 );
 
 
-ALTER TABLE public.posts_hashtags_relationship OWNER TO forum;
+ALTER TABLE public.login_challenges OWNER TO forum;
 
 --
--- Name: posts_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+-- Name: login_challenges_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-CREATE SEQUENCE public.posts_id_seq
+CREATE SEQUENCE public.login_challenges_id_seq
     AS integer
     START WITH 1
     INCREMENT BY 1
@@ -127,32 +176,80 @@ CREATE SEQUENCE public.posts_id_seq
     CACHE 1;
 
 
-ALTER SEQUENCE public.posts_id_seq OWNER TO forum;
+ALTER SEQUENCE public.login_challenges_id_seq OWNER TO forum;
 
 --
--- Name: posts_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+-- Name: login_challenges_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER SEQUENCE public.posts_id_seq OWNED BY public.posts.id;
+ALTER SEQUENCE public.login_challenges_id_seq OWNED BY public.login_challenges.id;
 
 
 --
--- Name: saved_posts; Type: TABLE; Schema: public; Owner: forum
+-- Name: oidc_states; Type: TABLE; Schema: public; Owner: forum
 --
 
-CREATE TABLE public.saved_posts (
+CREATE TABLE public.oidc_states (
+    id integer NOT NULL,
+    state_hash character varying(64) NOT NULL,
+    provider character varying(50) NOT NULL,
+    pkce_verifier character varying(128) NOT NULL,
+    nonce character varying(128) NOT NULL,
+    user_id integer,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
+);
+
+
+ALTER TABLE public.oidc_states OWNER TO forum;
+
+--
+-- Name: oidc_states_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+--
+
+CREATE SEQUENCE public.oidc_states_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.oidc_states_id_seq OWNER TO forum;
+
+--
+-- Name: oidc_states_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+--
+
+ALTER SEQUENCE public.oidc_states_id_seq OWNED BY public.oidc_states.id;
+
+
+--
+-- Name: personal_access_tokens; Type: TABLE; Schema: public; Owner: forum
+--
+
+CREATE TABLE public.personal_access_tokens (
+    id integer NOT NULL,
     user_id integer NOT NULL,
-    post_id integer NOT NULL
+    name character varying(100) NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    scopes public.token_scope[] NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone,
+    last_used_at timestamp without time zone,
+    revoked_at timestamp without time zone
 );
 
 
-ALTER TABLE public.saved_posts OWNER TO forum;
+ALTER TABLE public.personal_access_tokens OWNER TO forum;
 
 --
--- Name: topics_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+-- Name: personal_access_tokens_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-CREATE SEQUENCE public.topics_id_seq
+CREATE SEQUENCE public.personal_access_tokens_id_seq
     AS integer
     START WITH 1
     INCREMENT BY 1
@@ -161,34 +258,39 @@ CREATE SEQUENCE public.topics_id_seq
     CACHE 1;
 
 
-ALTER SEQUENCE public.topics_id_seq OWNER TO forum;
+ALTER SEQUENCE public.personal_access_tokens_id_seq OWNER TO forum;
 
 --
--- Name: topics_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+-- Name: personal_access_tokens_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER SEQUENCE public.topics_id_seq OWNED BY public.hashtags.id;
+ALTER SEQUENCE public.personal_access_tokens_id_seq OWNED BY public.personal_access_tokens.id;
 
 
 --
--- Name: users; Type: TABLE; Schema: public; Owner: forum
+-- Name: post_comments; Type: TABLE; Schema: public; Owner: forum
 --
 
-CREATE TABLE public.users (
+CREATE TABLE public.post_comments (
     id integer NOT NULL,
-    username character varying(50) NOT NULL,
-    password_hash character varying(200) NOT NULL,
-    created_at timestamp without time zone NOT NULL
+    body character varying(500) NOT NULL,
+    post_id integer NOT NULL,
+    user_id integer NOT NULL,
+    comment_id integer,
+    created_at timestamp without time zone NOT NULL,
+    edited_at timestamp without time zone,
+    deleted_at timestamp without time zone,
+    search_vector tsvector GENERATED ALWAYS AS (to_tsvector('english'::regconfig, (body)::text)) STORED
 );
 
 
-ALTER TABLE public.users OWNER TO forum;
+ALTER TABLE public.post_comments OWNER TO forum;
 
 --
--- Name: users_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+-- Name: post_comments_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-CREATE SEQUENCE public.users_id_seq
+CREATE SEQUENCE public.post_comments_id_seq
     AS integer
     START WITH 1
     INCREMENT BY 1
@@ -197,160 +299,955 @@ CREATE SEQUENCE public.users_id_seq
     CACHE 1;
 
 
-ALTER SEQUENCE public.users_id_seq OWNER TO forum;
+ALTER SEQUENCE public.post_comments_id_seq OWNER TO forum;
 
 --
--- Name: users_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+-- Name: post_comments_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER SEQUENCE public.users_id_seq OWNED BY public.users.id;
+ALTER SEQUENCE public.post_comments_id_seq OWNED BY public.post_comments.id;
 
 
 --
--- Name: hashtags id; Type: DEFAULT; Schema: public; Owner: forum
+-- Name: posts; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.hashtags ALTER COLUMN id SET DEFAULT nextval('public.topics_id_seq'::regclass);
+CREATE TABLE public.posts (
+    id integer NOT NULL,
+    title character varying(100) NOT NULL,
+    body character varying(5000) NOT NULL,
+    user_id integer NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    search_vector tsvector GENERATED ALWAYS AS ((setweight(to_tsvector('english'::regconfig, (title)::text), 'A'::"char") || setweight(to_tsvector('english'::regconfig, (body)::text), 'B'::"char"))) STORED
+);
+
 
+ALTER TABLE public.posts OWNER TO forum;
 
 --
--- Name: post_comments id; Type: DEFAULT; Schema: public; Owner: forum
+-- Name: post_votes; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.post_comments ALTER COLUMN id SET DEFAULT nextval('public.post_comments_id_seq'::regclass);
+CREATE TABLE public.post_votes (
+    user_id integer NOT NULL,
+    post_id integer NOT NULL,
+    value smallint NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    CONSTRAINT post_votes_value_check CHECK ((value = ANY (ARRAY['-1'::integer, 1])))
+);
 
 
+ALTER TABLE public.post_votes OWNER TO forum;
+
 --
--- Name: posts id; Type: DEFAULT; Schema: public; Owner: forum
+-- Name: posts_hashtags_relationship; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.posts ALTER COLUMN id SET DEFAULT nextval('public.posts_id_seq'::regclass);
+CREATE TABLE public.posts_hashtags_relationship (
+    post_id integer NOT NULL,
+    hashtag_id integer NOT NULL
+);
 
 
+ALTER TABLE public.posts_hashtags_relationship OWNER TO forum;
+
 --
--- Name: users id; Type: DEFAULT; Schema: public; Owner: forum
+-- Name: posts_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.users ALTER COLUMN id SET DEFAULT nextval('public.users_id_seq'::regclass);
+CREATE SEQUENCE public.posts_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
+ALTER SEQUENCE public.posts_id_seq OWNER TO forum;
+
 --
--- Name: post_comments post_comments_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: posts_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.post_comments
-    ADD CONSTRAINT post_comments_pkey PRIMARY KEY (id);
+ALTER SEQUENCE public.posts_id_seq OWNED BY public.posts.id;
 
 
 --
--- Name: posts posts_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: refresh_tokens; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.posts
-    ADD CONSTRAINT posts_pkey PRIMARY KEY (id);
+CREATE TABLE public.refresh_tokens (
+    id integer NOT NULL,
+    session_id integer NOT NULL,
+    token_hash character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    expires_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
+);
 
 
+ALTER TABLE public.refresh_tokens OWNER TO forum;
+
 --
--- Name: posts_hashtags_relationship posts_topics_relationship_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: refresh_tokens_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.posts_hashtags_relationship
-    ADD CONSTRAINT posts_topics_relationship_pkey PRIMARY KEY (post_id, hashtag_id);
+CREATE SEQUENCE public.refresh_tokens_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
+ALTER SEQUENCE public.refresh_tokens_id_seq OWNER TO forum;
+
 --
--- Name: saved_posts saved_posts_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: refresh_tokens_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.saved_posts
-    ADD CONSTRAINT saved_posts_pkey PRIMARY KEY (user_id, post_id);
+ALTER SEQUENCE public.refresh_tokens_id_seq OWNED BY public.refresh_tokens.id;
 
 
 --
--- Name: hashtags topics_name_key; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: saved_posts; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.hashtags
-    ADD CONSTRAINT topics_name_key UNIQUE (name);
+CREATE TABLE public.saved_posts (
+    user_id integer NOT NULL,
+    post_id integer NOT NULL
+);
 
 
+ALTER TABLE public.saved_posts OWNER TO forum;
+
 --
--- Name: hashtags topics_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: sessions; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.hashtags
-    ADD CONSTRAINT topics_pkey PRIMARY KEY (id);
+CREATE TABLE public.sessions (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    revoked_at timestamp without time zone,
+    last_seen_at timestamp without time zone NOT NULL,
+    user_agent character varying(500),
+    ip character varying(45)
+);
 
 
+ALTER TABLE public.sessions OWNER TO forum;
+
 --
--- Name: users users_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+-- Name: sessions_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.users
-    ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+CREATE SEQUENCE public.sessions_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
+ALTER SEQUENCE public.sessions_id_seq OWNER TO forum;
+
 --
--- Name: username_lower_unique_index; Type: INDEX; Schema: public; Owner: forum
+-- Name: sessions_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-CREATE UNIQUE INDEX username_lower_unique_index ON public.users USING btree (lower((username)::text));
+ALTER SEQUENCE public.sessions_id_seq OWNED BY public.sessions.id;
 
 
 --
--- Name: post_comments post_comments_comment_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: topics_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.post_comments
-    ADD CONSTRAINT post_comments_comment_id_fkey FOREIGN KEY (comment_id) REFERENCES public.post_comments(id) ON DELETE CASCADE;
+CREATE SEQUENCE public.topics_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
+ALTER SEQUENCE public.topics_id_seq OWNER TO forum;
+
 --
--- Name: post_comments post_comments_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: topics_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.post_comments
-    ADD CONSTRAINT post_comments_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+ALTER SEQUENCE public.topics_id_seq OWNED BY public.hashtags.id;
 
 
 --
--- Name: posts_hashtags_relationship posts_topics_relationship_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: totp_recovery_codes; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.posts_hashtags_relationship
-    ADD CONSTRAINT posts_topics_relationship_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+CREATE TABLE public.totp_recovery_codes (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    code_hash character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    used_at timestamp without time zone
+);
+
 
+ALTER TABLE public.totp_recovery_codes OWNER TO forum;
 
 --
--- Name: posts_hashtags_relationship posts_topics_relationship_topic_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: totp_recovery_codes_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.posts_hashtags_relationship
-    ADD CONSTRAINT posts_topics_relationship_topic_id_fkey FOREIGN KEY (hashtag_id) REFERENCES public.hashtags(id) ON DELETE CASCADE;
+CREATE SEQUENCE public.totp_recovery_codes_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
 
+ALTER SEQUENCE public.totp_recovery_codes_id_seq OWNER TO forum;
 
 --
--- Name: posts posts_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: totp_recovery_codes_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.posts
-    ADD CONSTRAINT posts_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+ALTER SEQUENCE public.totp_recovery_codes_id_seq OWNED BY public.totp_recovery_codes.id;
 
 
 --
--- Name: saved_posts saved_posts_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: user_follows; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.saved_posts
-    ADD CONSTRAINT saved_posts_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+CREATE TABLE public.user_follows (
+    follower_id integer NOT NULL,
+    following_id integer NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    CONSTRAINT user_follows_check CHECK ((follower_id <> following_id))
+);
+
 
+ALTER TABLE public.user_follows OWNER TO forum;
 
 --
--- Name: saved_posts saved_posts_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+-- Name: user_identities; Type: TABLE; Schema: public; Owner: forum
 --
 
-ALTER TABLE ONLY public.saved_posts
-    ADD CONSTRAINT saved_posts_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+CREATE TABLE public.user_identities (
+    id integer NOT NULL,
+    user_id integer NOT NULL,
+    provider character varying(50) NOT NULL,
+    subject character varying(255) NOT NULL,
+    email character varying(254),
+    created_at timestamp without time zone NOT NULL,
+    last_used_at timestamp without time zone NOT NULL
+);
+
+
+ALTER TABLE public.user_identities OWNER TO forum;
+
+--
+-- Name: user_identities_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+--
+
+CREATE SEQUENCE public.user_identities_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.user_identities_id_seq OWNER TO forum;
+
+--
+-- Name: user_identities_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+--
+
+ALTER SEQUENCE public.user_identities_id_seq OWNED BY public.user_identities.id;
+
+
+--
+-- Name: user_totp; Type: TABLE; Schema: public; Owner: forum
+--
+
+CREATE TABLE public.user_totp (
+    user_id integer NOT NULL,
+    secret character varying(64) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    confirmed_at timestamp without time zone,
+    last_used_step bigint
+);
+
+
+ALTER TABLE public.user_totp OWNER TO forum;
+
+--
+-- Name: users; Type: TABLE; Schema: public; Owner: forum
+--
+
+CREATE TABLE public.users (
+    id integer NOT NULL,
+    username character varying(50) NOT NULL,
+    password_hash character varying(200) NOT NULL,
+    created_at timestamp without time zone NOT NULL,
+    display_name character varying(50),
+    bio character varying(500),
+    website character varying(200),
+    location character varying(100),
+    avatar_url character varying(500),
+    email character varying(254),
+    email_verified_at timestamp without time zone
+);
+
+
+ALTER TABLE public.users OWNER TO forum;
+
+--
+-- Name: users_id_seq; Type: SEQUENCE; Schema: public; Owner: forum
+--
+
+CREATE SEQUENCE public.users_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER SEQUENCE public.users_id_seq OWNER TO forum;
+
+--
+-- Name: users_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: forum
+--
+
+ALTER SEQUENCE public.users_id_seq OWNED BY public.users.id;
+
+
+--
+-- Name: email_tokens id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.email_tokens ALTER COLUMN id SET DEFAULT nextval('public.email_tokens_id_seq'::regclass);
+
+
+--
+-- Name: hashtags id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.hashtags ALTER COLUMN id SET DEFAULT nextval('public.topics_id_seq'::regclass);
+
+
+--
+-- Name: login_challenges id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.login_challenges ALTER COLUMN id SET DEFAULT nextval('public.login_challenges_id_seq'::regclass);
+
+
+--
+-- Name: oidc_states id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.oidc_states ALTER COLUMN id SET DEFAULT nextval('public.oidc_states_id_seq'::regclass);
+
+
+--
+-- Name: personal_access_tokens id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.personal_access_tokens ALTER COLUMN id SET DEFAULT nextval('public.personal_access_tokens_id_seq'::regclass);
+
+
+--
+-- Name: post_comments id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_comments ALTER COLUMN id SET DEFAULT nextval('public.post_comments_id_seq'::regclass);
+
+
+--
+-- Name: posts id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.posts ALTER COLUMN id SET DEFAULT nextval('public.posts_id_seq'::regclass);
+
+
+--
+-- Name: refresh_tokens id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.refresh_tokens ALTER COLUMN id SET DEFAULT nextval('public.refresh_tokens_id_seq'::regclass);
+
+
+--
+-- Name: sessions id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.sessions ALTER COLUMN id SET DEFAULT nextval('public.sessions_id_seq'::regclass);
+
+
+--
+-- Name: totp_recovery_codes id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.totp_recovery_codes ALTER COLUMN id SET DEFAULT nextval('public.totp_recovery_codes_id_seq'::regclass);
+
+
+--
+-- Name: user_identities id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_identities ALTER COLUMN id SET DEFAULT nextval('public.user_identities_id_seq'::regclass);
+
+
+--
+-- Name: users id; Type: DEFAULT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.users ALTER COLUMN id SET DEFAULT nextval('public.users_id_seq'::regclass);
+
+
+--
+-- Name: auth_attempts auth_attempts_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.auth_attempts
+    ADD CONSTRAINT auth_attempts_pkey PRIMARY KEY (key);
+
+
+--
+-- Name: comment_votes comment_votes_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.comment_votes
+    ADD CONSTRAINT comment_votes_pkey PRIMARY KEY (user_id, comment_id);
+
+
+--
+-- Name: email_tokens email_tokens_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.email_tokens
+    ADD CONSTRAINT email_tokens_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: email_tokens email_tokens_token_hash_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.email_tokens
+    ADD CONSTRAINT email_tokens_token_hash_key UNIQUE (token_hash);
+
+
+--
+-- Name: login_challenges login_challenges_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.login_challenges
+    ADD CONSTRAINT login_challenges_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: login_challenges login_challenges_token_hash_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.login_challenges
+    ADD CONSTRAINT login_challenges_token_hash_key UNIQUE (token_hash);
+
+
+--
+-- Name: oidc_states oidc_states_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.oidc_states
+    ADD CONSTRAINT oidc_states_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: oidc_states oidc_states_state_hash_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.oidc_states
+    ADD CONSTRAINT oidc_states_state_hash_key UNIQUE (state_hash);
+
+
+--
+-- Name: personal_access_tokens personal_access_tokens_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.personal_access_tokens
+    ADD CONSTRAINT personal_access_tokens_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: personal_access_tokens personal_access_tokens_token_hash_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.personal_access_tokens
+    ADD CONSTRAINT personal_access_tokens_token_hash_key UNIQUE (token_hash);
+
+
+--
+-- Name: post_comments post_comments_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_comments
+    ADD CONSTRAINT post_comments_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: posts posts_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.posts
+    ADD CONSTRAINT posts_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: post_votes post_votes_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_votes
+    ADD CONSTRAINT post_votes_pkey PRIMARY KEY (user_id, post_id);
+
+
+--
+-- Name: hashtag_follows hashtag_follows_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.hashtag_follows
+    ADD CONSTRAINT hashtag_follows_pkey PRIMARY KEY (user_id, hashtag_id);
+
+
+--
+-- Name: posts_hashtags_relationship posts_topics_relationship_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.posts_hashtags_relationship
+    ADD CONSTRAINT posts_topics_relationship_pkey PRIMARY KEY (post_id, hashtag_id);
+
+
+--
+-- Name: refresh_tokens refresh_tokens_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.refresh_tokens
+    ADD CONSTRAINT refresh_tokens_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: refresh_tokens refresh_tokens_token_hash_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.refresh_tokens
+    ADD CONSTRAINT refresh_tokens_token_hash_key UNIQUE (token_hash);
+
+
+--
+-- Name: saved_posts saved_posts_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.saved_posts
+    ADD CONSTRAINT saved_posts_pkey PRIMARY KEY (user_id, post_id);
+
+
+--
+-- Name: sessions sessions_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.sessions
+    ADD CONSTRAINT sessions_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: hashtags topics_name_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.hashtags
+    ADD CONSTRAINT topics_name_key UNIQUE (name);
+
+
+--
+-- Name: hashtags topics_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.hashtags
+    ADD CONSTRAINT topics_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: totp_recovery_codes totp_recovery_codes_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.totp_recovery_codes
+    ADD CONSTRAINT totp_recovery_codes_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: user_follows user_follows_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_follows
+    ADD CONSTRAINT user_follows_pkey PRIMARY KEY (follower_id, following_id);
+
+
+--
+-- Name: user_identities user_identities_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_identities
+    ADD CONSTRAINT user_identities_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: user_identities user_identities_provider_subject_key; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_identities
+    ADD CONSTRAINT user_identities_provider_subject_key UNIQUE (provider, subject);
+
+
+--
+-- Name: user_totp user_totp_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_totp
+    ADD CONSTRAINT user_totp_pkey PRIMARY KEY (user_id);
+
+
+--
+-- Name: users users_pkey; Type: CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.users
+    ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: username_lower_unique_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE UNIQUE INDEX username_lower_unique_index ON public.users USING btree (lower((username)::text));
+
+
+--
+-- Name: email_lower_unique_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE UNIQUE INDEX email_lower_unique_index ON public.users USING btree (lower((email)::text));
+
+
+--
+-- Name: email_tokens_user_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX email_tokens_user_id_index ON public.email_tokens USING btree (user_id);
+
+
+--
+-- Name: comment_votes_comment_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX comment_votes_comment_id_index ON public.comment_votes USING btree (comment_id);
+
+
+--
+-- Name: post_votes_post_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX post_votes_post_id_index ON public.post_votes USING btree (post_id);
+
+
+--
+-- Name: posts_search_vector_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX posts_search_vector_index ON public.posts USING gin (search_vector);
+
+
+--
+-- Name: post_comments_search_vector_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX post_comments_search_vector_index ON public.post_comments USING gin (search_vector);
+
+
+--
+-- Name: hashtag_follows_hashtag_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX hashtag_follows_hashtag_id_index ON public.hashtag_follows USING btree (hashtag_id);
+
+
+--
+-- Name: hashtags_name_pattern_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX hashtags_name_pattern_index ON public.hashtags USING btree (name varchar_pattern_ops);
+
+
+--
+-- Name: user_follows_following_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX user_follows_following_id_index ON public.user_follows USING btree (following_id);
+
+
+--
+-- Name: refresh_tokens_session_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX refresh_tokens_session_id_index ON public.refresh_tokens USING btree (session_id);
+
+
+--
+-- Name: sessions_user_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX sessions_user_id_index ON public.sessions USING btree (user_id);
+
+
+--
+-- Name: login_challenges_user_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX login_challenges_user_id_index ON public.login_challenges USING btree (user_id);
+
+
+--
+-- Name: totp_recovery_codes_user_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX totp_recovery_codes_user_id_index ON public.totp_recovery_codes USING btree (user_id);
+
+
+--
+-- Name: personal_access_tokens_user_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX personal_access_tokens_user_id_index ON public.personal_access_tokens USING btree (user_id);
+
+
+--
+-- Name: user_identities_user_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX user_identities_user_id_index ON public.user_identities USING btree (user_id);
+
+
+--
+-- Name: posts_created_at_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX posts_created_at_id_index ON public.posts USING btree (created_at, id);
+
+
+--
+-- Name: post_comments_post_id_created_at_id_index; Type: INDEX; Schema: public; Owner: forum
+--
+
+CREATE INDEX post_comments_post_id_created_at_id_index ON public.post_comments USING btree (post_id, created_at, id);
+
+
+--
+-- Name: comment_votes comment_votes_comment_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.comment_votes
+    ADD CONSTRAINT comment_votes_comment_id_fkey FOREIGN KEY (comment_id) REFERENCES public.post_comments(id) ON DELETE CASCADE;
+
+
+--
+-- Name: comment_votes comment_votes_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.comment_votes
+    ADD CONSTRAINT comment_votes_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: email_tokens email_tokens_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.email_tokens
+    ADD CONSTRAINT email_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: hashtag_follows hashtag_follows_hashtag_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.hashtag_follows
+    ADD CONSTRAINT hashtag_follows_hashtag_id_fkey FOREIGN KEY (hashtag_id) REFERENCES public.hashtags(id) ON DELETE CASCADE;
+
+
+--
+-- Name: hashtag_follows hashtag_follows_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.hashtag_follows
+    ADD CONSTRAINT hashtag_follows_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: login_challenges login_challenges_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.login_challenges
+    ADD CONSTRAINT login_challenges_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: oidc_states oidc_states_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.oidc_states
+    ADD CONSTRAINT oidc_states_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: personal_access_tokens personal_access_tokens_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.personal_access_tokens
+    ADD CONSTRAINT personal_access_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: post_comments post_comments_comment_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_comments
+    ADD CONSTRAINT post_comments_comment_id_fkey FOREIGN KEY (comment_id) REFERENCES public.post_comments(id) ON DELETE CASCADE;
+
+
+--
+-- Name: post_comments post_comments_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_comments
+    ADD CONSTRAINT post_comments_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+
+
+--
+-- Name: post_votes post_votes_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_votes
+    ADD CONSTRAINT post_votes_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+
+
+--
+-- Name: post_votes post_votes_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.post_votes
+    ADD CONSTRAINT post_votes_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: posts_hashtags_relationship posts_topics_relationship_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.posts_hashtags_relationship
+    ADD CONSTRAINT posts_topics_relationship_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+
+
+--
+-- Name: posts_hashtags_relationship posts_topics_relationship_topic_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.posts_hashtags_relationship
+    ADD CONSTRAINT posts_topics_relationship_topic_id_fkey FOREIGN KEY (hashtag_id) REFERENCES public.hashtags(id) ON DELETE CASCADE;
+
+
+--
+-- Name: posts posts_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.posts
+    ADD CONSTRAINT posts_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: saved_posts saved_posts_post_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.saved_posts
+    ADD CONSTRAINT saved_posts_post_id_fkey FOREIGN KEY (post_id) REFERENCES public.posts(id) ON DELETE CASCADE;
+
+
+--
+-- Name: saved_posts saved_posts_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.saved_posts
+    ADD CONSTRAINT saved_posts_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: refresh_tokens refresh_tokens_session_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.refresh_tokens
+    ADD CONSTRAINT refresh_tokens_session_id_fkey FOREIGN KEY (session_id) REFERENCES public.sessions(id) ON DELETE CASCADE;
+
+
+--
+-- Name: sessions sessions_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.sessions
+    ADD CONSTRAINT sessions_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: totp_recovery_codes totp_recovery_codes_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.totp_recovery_codes
+    ADD CONSTRAINT totp_recovery_codes_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: user_follows user_follows_follower_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_follows
+    ADD CONSTRAINT user_follows_follower_id_fkey FOREIGN KEY (follower_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: user_follows user_follows_following_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_follows
+    ADD CONSTRAINT user_follows_following_id_fkey FOREIGN KEY (following_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: user_identities user_identities_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_identities
+    ADD CONSTRAINT user_identities_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: user_totp user_totp_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: forum
+--
+
+ALTER TABLE ONLY public.user_totp
+    ADD CONSTRAINT user_totp_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
 
 
 --
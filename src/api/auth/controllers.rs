@@ -1,11 +1,18 @@
 use actix_web::{
-  web::{Data, Json},
+  http::header,
+  web::{Data, Json, Path},
   HttpResponse,
 };
 use deadpool_postgres::Pool;
 use serde_json::json;
 
 use super::models::{self, UserAuth};
+use super::two_factor::models as models_2fa;
+use crate::{
+  mailer::Mailer,
+  password::Passwords,
+  throttle::{self, Throttle},
+};
 
 pub async fn verify(user_detail: UserAuth) -> HttpResponse {
   user_detail.details.map_or(
@@ -27,8 +34,29 @@ pub async fn verify(user_detail: UserAuth) -> HttpResponse {
 
 pub async fn create_account(
   body: Json<models::CreateAccountDetails>,
+  client_info: models::ClientInfo,
   db_pool: Data<Pool>,
+  mailer: Data<dyn Mailer>,
+  passwords: Data<Passwords>,
+  throttle: Data<Throttle>,
 ) -> HttpResponse {
+  let ip = throttle.client_ip(client_info.ip.as_deref(), client_info.peer_ip.as_deref());
+  let keys = [(throttle::IP_SIGN_UP, throttle::IP_SIGN_UP.key(ip))];
+
+  if let Err((s, v)) = throttle.hit(&keys).await {
+    let mut res = HttpResponse::Ok();
+
+    if let Some(retry_after) = v["retry_after"].as_i64() {
+      res.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+    }
+
+    return res.status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
   let db_client_res = db_pool.get().await;
 
   if let Err(e) = db_client_res {
@@ -40,9 +68,11 @@ pub async fn create_account(
 
   let db_client = db_client_res.unwrap();
 
+  let body = body.into_inner();
+  let has_email = body.has_email();
+
   let res = body
-    .into_inner()
-    .add_db_client(&db_client)
+    .add_db_client(&db_client, &passwords)
     .insert_to_db()
     .await;
 
@@ -56,16 +86,79 @@ pub async fn create_account(
 
   let res = res.unwrap();
 
-  HttpResponse::Ok().json(json!({
-      "success": true,
-      "data": {
-        "id": res.id,
-        "username": res.username,
-        "access_token": res.to_jwt()
-  }}))
+  if has_email {
+    let sent = models::SendVerificationEmail {
+      db_client: &db_client,
+      mailer: &**mailer,
+      user_id: res.id,
+    }
+    .exec()
+    .await;
+
+    if let Err((_, v)) = sent {
+      eprintln!("Could not send verification email: {}", v["message"]);
+    }
+  }
+
+  let tokens = models::IssueTokens {
+    db_client: &db_client,
+    user_details: &res,
+    client_info: &client_info,
+  }
+  .exec()
+  .await;
+
+  match tokens {
+    Ok(tokens) => HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+          "id": res.id,
+          "username": res.username,
+          "access_token": tokens.access_token,
+          "refresh_token": tokens.refresh_token,
+          "expires_at": tokens.expires_at
+    }})),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
 }
 
-pub async fn login(body: Json<models::LoginDetails>, db_pool: Data<Pool>) -> HttpResponse {
+pub async fn login(
+  body: Json<models::LoginDetails>,
+  client_info: models::ClientInfo,
+  db_pool: Data<Pool>,
+  passwords: Data<Passwords>,
+  throttle: Data<Throttle>,
+) -> HttpResponse {
+  let body = body.into_inner();
+  let ip = throttle.client_ip(client_info.ip.as_deref(), client_info.peer_ip.as_deref());
+  let mut keys = vec![(throttle::IP_LOGIN, throttle::IP_LOGIN.key(ip))];
+
+  if let Some(username) = body.username() {
+    keys.push((
+      throttle::ACCOUNT_LOGIN,
+      throttle::ACCOUNT_LOGIN.key(username),
+    ));
+  }
+
+  if let Err((s, v)) = throttle.hit(&keys).await {
+    let mut res = HttpResponse::Ok();
+
+    if let Some(retry_after) = v["retry_after"].as_i64() {
+      res.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+    }
+
+    return res.status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
   let db_client_res = db_pool.get().await;
 
   if let Err(e) = db_client_res {
@@ -77,7 +170,7 @@ pub async fn login(body: Json<models::LoginDetails>, db_pool: Data<Pool>) -> Htt
 
   let db_client = db_client_res.unwrap();
 
-  let res = body.into_inner().add_db_client(&db_client).validate().await;
+  let res = body.add_db_client(&db_client, &passwords).validate().await;
 
   if let Err(err) = res {
     return HttpResponse::BadRequest().json(json!({
@@ -89,11 +182,684 @@ pub async fn login(body: Json<models::LoginDetails>, db_pool: Data<Pool>) -> Htt
 
   let res = res.unwrap();
 
-  HttpResponse::Ok().json(json!({
+  let challenge = models_2fa::LoginChallenge {
+    db_client: &db_client,
+    user_details: &res,
+  }
+  .create()
+  .await;
+
+  match challenge {
+    Ok(Some(challenge)) => {
+      return HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+          "id": res.id,
+          "username": res.username,
+          "two_factor_required": true,
+          "challenge": challenge
+        }
+      }))
+    }
+
+    Ok(None) => (),
+
+    Err((s, v)) => {
+      return HttpResponse::Ok().status(s).json(json!({
+        "success": false,
+        "message": v["message"],
+        "error": v
+      }))
+    }
+  }
+
+  if let Err((s, v)) = throttle.release(&keys[0].1).await {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  if let Some((_, key)) = keys.get(1) {
+    if let Err((s, v)) = throttle.clear(key).await {
+      return HttpResponse::Ok().status(s).json(json!({
+        "success": false,
+        "message": v["message"],
+        "error": v
+      }));
+    }
+  }
+
+  let tokens = models::IssueTokens {
+    db_client: &db_client,
+    user_details: &res,
+    client_info: &client_info,
+  }
+  .exec()
+  .await;
+
+  match tokens {
+    Ok(tokens) => HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+          "id": res.id,
+          "username": res.username,
+          "access_token": tokens.access_token,
+          "refresh_token": tokens.refresh_token,
+          "expires_at": tokens.expires_at
+    }})),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn login_two_factor(
+  body: Json<models_2fa::TwoFactorLoginDetails>,
+  client_info: models::ClientInfo,
+  db_pool: Data<Pool>,
+  throttle: Data<Throttle>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let body = body.into_inner().add_db_client(&db_client);
+
+  let username = match body.username().await {
+    Ok(username) => username,
+    Err((s, v)) => {
+      return HttpResponse::Ok().status(s).json(json!({
+        "success": false,
+        "message": v["message"],
+        "error": v
+      }))
+    }
+  };
+
+  let ip = throttle.client_ip(client_info.ip.as_deref(), client_info.peer_ip.as_deref());
+  let mut keys = vec![(throttle::IP_LOGIN, throttle::IP_LOGIN.key(ip))];
+
+  if let Some(username) = username.as_deref() {
+    keys.push((
+      throttle::ACCOUNT_LOGIN,
+      throttle::ACCOUNT_LOGIN.key(username),
+    ));
+  }
+
+  if let Err((s, v)) = throttle.hit(&keys).await {
+    let mut res = HttpResponse::Ok();
+
+    if let Some(retry_after) = v["retry_after"].as_i64() {
+      res.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+    }
+
+    return res.status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let res = body.exec().await;
+
+  if let Err((s, v)) = res {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let res = res.unwrap();
+
+  if let Err((s, v)) = throttle.release(&keys[0].1).await {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  if let Some((_, key)) = keys.get(1) {
+    if let Err((s, v)) = throttle.clear(key).await {
+      return HttpResponse::Ok().status(s).json(json!({
+        "success": false,
+        "message": v["message"],
+        "error": v
+      }));
+    }
+  }
+
+  let tokens = models::IssueTokens {
+    db_client: &db_client,
+    user_details: &res,
+    client_info: &client_info,
+  }
+  .exec()
+  .await;
+
+  match tokens {
+    Ok(tokens) => HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+          "id": res.id,
+          "username": res.username,
+          "access_token": tokens.access_token,
+          "refresh_token": tokens.refresh_token,
+          "expires_at": tokens.expires_at
+    }})),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn refresh(body: Json<models::RefreshTokenDetails>, db_pool: Data<Pool>) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body.into_inner().add_db_client(&db_client).rotate().await;
+
+  match res {
+    Ok(tokens) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": tokens
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn sign_out(
+  body: Json<models::RefreshTokenDetails>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body.into_inner().add_db_client(&db_client).revoke().await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn sign_out_all(user_details: UserAuth, db_pool: Data<Pool>) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::SignOutAll {
+    db_client: &db_client,
+    user_details,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn fetch_sessions(user_details: UserAuth, db_pool: Data<Pool>) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::FetchSessions {
+    db_client: &db_client,
+    user_details,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
       "success": true,
-      "data": {
-        "id": res.id,
-        "username": res.username,
-        "access_token": res.to_jwt()
-  }}))
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn revoke_session(
+  user_details: UserAuth,
+  id: Path<i32>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::RevokeSession {
+    db_client: &db_client,
+    user_details,
+    id: id.into_inner(),
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn change_password(
+  user_details: UserAuth,
+  body: Json<models::ChangePasswordDetails>,
+  db_pool: Data<Pool>,
+  passwords: Data<Passwords>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, &passwords, user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn update_email(
+  user_details: UserAuth,
+  body: Json<models::UpdateEmailDetails>,
+  db_pool: Data<Pool>,
+  mailer: Data<dyn Mailer>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, &**mailer, user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn send_verification_email(
+  user_details: UserAuth,
+  db_pool: Data<Pool>,
+  mailer: Data<dyn Mailer>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::SendVerificationEmail {
+    db_client: &db_client,
+    mailer: &**mailer,
+    user_id: user_details.id,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn verify_email(
+  body: Json<models::VerifyEmailDetails>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body.into_inner().add_db_client(&db_client).exec().await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn forgot_password(
+  body: Json<models::ForgotPasswordDetails>,
+  db_pool: Data<Pool>,
+  mailer: Data<dyn Mailer>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, &**mailer)
+    .exec()
+    .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn reset_password(
+  body: Json<models::ResetPasswordDetails>,
+  db_pool: Data<Pool>,
+  passwords: Data<Passwords>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_db_client(&db_client, &passwords)
+    .exec()
+    .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
 }
@@ -1,9 +1,42 @@
 use actix_web::web::{self, ServiceConfig};
 mod controllers;
 pub mod models;
+mod oidc;
+mod tokens;
+mod two_factor;
 
 pub fn view(cfg: &mut ServiceConfig) {
   cfg.route("", web::get().to(controllers::verify));
   cfg.route("/sign-in", web::post().to(controllers::login));
+  cfg.route(
+    "/sign-in/2fa",
+    web::post().to(controllers::login_two_factor),
+  );
   cfg.route("/sign-up", web::post().to(controllers::create_account));
+  cfg.route("/refresh", web::post().to(controllers::refresh));
+  cfg.route("/sign-out", web::post().to(controllers::sign_out));
+  cfg.route("/sign-out-all", web::post().to(controllers::sign_out_all));
+  cfg.route("/password", web::post().to(controllers::change_password));
+  cfg.route("/email", web::post().to(controllers::update_email));
+  cfg.route("/verify-email", web::post().to(controllers::verify_email));
+  cfg.route(
+    "/verify-email/send",
+    web::post().to(controllers::send_verification_email),
+  );
+  cfg.route(
+    "/forgot-password",
+    web::post().to(controllers::forgot_password),
+  );
+  cfg.route(
+    "/reset-password",
+    web::post().to(controllers::reset_password),
+  );
+  cfg.route("/sessions", web::get().to(controllers::fetch_sessions));
+  cfg.route(
+    "/sessions/{id}",
+    web::delete().to(controllers::revoke_session),
+  );
+  cfg.service(web::scope("/2fa").configure(two_factor::view));
+  cfg.service(web::scope("/oidc").configure(oidc::view));
+  cfg.service(web::scope("/tokens").configure(tokens::view));
 }
@@ -3,29 +3,48 @@ use std::{
   future::{ready, Ready},
 };
 
-use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use actix_web::{
+  http::{header, Method, StatusCode},
+  Error, FromRequest, HttpMessage, HttpRequest,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, NaiveDateTime, Utc};
 use deadpool_postgres::Client;
 use hmac::{Hmac, Mac};
 use jwt::{SignWithKey, VerifyWithKey};
+use postgres_types::{FromSql, ToSql};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use tokio_postgres::Statement;
 
+use crate::{
+  mailer::{Mail, Mailer},
+  password::Passwords,
+};
+
+pub(super) const ACCESS_TOKEN_MINUTES: i64 = 15;
+const REFRESH_TOKEN_DAYS: i64 = 30;
+const VERIFY_EMAIL_TOKEN_HOURS: i64 = 24;
+pub(super) const PERSONAL_ACCESS_TOKEN_PREFIX: &str = "fpat_";
+const RESET_PASSWORD_TOKEN_MINUTES: i64 = 60;
+
 #[derive(Serialize, Deserialize)]
 pub struct CreateAccountDetails {
   username: Option<String>,
   password: Option<String>,
   confirm_password: Option<String>,
+  email: Option<String>,
 }
 
-#[derive(Debug)]
 pub struct CreateAccountDetailsWithDBClient<'a> {
   username: Option<String>,
   password: Option<String>,
   confirm_password: Option<String>,
+  email: Option<String>,
   db_client: &'a Client,
+  passwords: &'a Passwords,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -38,6 +57,7 @@ pub struct LoginDetailsWithDBClient<'a> {
   username: Option<String>,
   password: Option<String>,
   db_client: &'a Client,
+  passwords: &'a Passwords,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,19 +65,172 @@ pub struct UserAuthDetails {
   pub id: i32,
   pub username: String,
   pub expires_at: NaiveDateTime,
+  pub session_id: Option<i32>,
+  #[serde(skip)]
+  pub scopes: Option<Vec<TokenScope>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSql, FromSql)]
+#[postgres(name = "token_scope")]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+  #[postgres(name = "read")]
+  Read,
+  #[postgres(name = "post")]
+  Post,
+  #[postgres(name = "comment")]
+  Comment,
+  #[postgres(name = "moderate")]
+  Moderate,
 }
 
 pub struct UserAuth {
   pub details: Option<UserAuthDetails>,
+  token_details: Option<UserAuthDetails>,
+}
+
+pub struct ClientInfo {
+  pub user_agent: Option<String>,
+  pub ip: Option<String>,
+  pub peer_ip: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AuthTokens {
+  pub access_token: String,
+  pub refresh_token: String,
+  pub expires_at: NaiveDateTime,
+}
+
+pub struct IssueTokens<'a> {
+  pub db_client: &'a Client,
+  pub user_details: &'a UserAuthDetails,
+  pub client_info: &'a ClientInfo,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RefreshTokenDetails {
+  refresh_token: Option<String>,
+}
+
+pub struct RefreshTokenDetailsWithDBClient<'a> {
+  refresh_token: Option<String>,
+  db_client: &'a Client,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangePasswordDetails {
+  current_password: Option<String>,
+  password: Option<String>,
+  confirm_password: Option<String>,
+}
+
+pub struct ChangePasswordDetailsWithDBClient<'a> {
+  current_password: Option<String>,
+  password: Option<String>,
+  confirm_password: Option<String>,
+  db_client: &'a Client,
+  passwords: &'a Passwords,
+  user_details: UserAuthDetails,
+}
+
+pub struct SendVerificationEmail<'a> {
+  pub db_client: &'a Client,
+  pub mailer: &'a dyn Mailer,
+  pub user_id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateEmailDetails {
+  email: Option<String>,
+}
+
+pub struct UpdateEmailDetailsWithDBClient<'a> {
+  email: Option<String>,
+  db_client: &'a Client,
+  mailer: &'a dyn Mailer,
+  user_details: UserAuthDetails,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VerifyEmailDetails {
+  token: Option<String>,
+}
+
+pub struct VerifyEmailDetailsWithDBClient<'a> {
+  token: Option<String>,
+  db_client: &'a Client,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ForgotPasswordDetails {
+  email: Option<String>,
+}
+
+pub struct ForgotPasswordDetailsWithDBClient<'a> {
+  email: Option<String>,
+  db_client: &'a Client,
+  mailer: &'a dyn Mailer,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ResetPasswordDetails {
+  token: Option<String>,
+  password: Option<String>,
+  confirm_password: Option<String>,
+}
+
+pub struct ResetPasswordDetailsWithDBClient<'a> {
+  token: Option<String>,
+  password: Option<String>,
+  confirm_password: Option<String>,
+  db_client: &'a Client,
+  passwords: &'a Passwords,
+}
+
+pub struct SignOutAll<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+}
+
+#[derive(Serialize)]
+pub struct Session {
+  id: i32,
+  created_at: NaiveDateTime,
+  last_seen_at: NaiveDateTime,
+  user_agent: Option<String>,
+  ip: Option<String>,
+  current: bool,
+}
+
+pub struct FetchSessions<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+}
+
+pub struct RevokeSession<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+  pub id: i32,
 }
 
 impl CreateAccountDetails {
-  pub fn add_db_client(self, db_client: &Client) -> CreateAccountDetailsWithDBClient {
+  pub fn has_email(&self) -> bool {
+    self.email.as_deref().is_some_and(|e| !e.trim().is_empty())
+  }
+
+  pub fn add_db_client<'a>(
+    self,
+    db_client: &'a Client,
+    passwords: &'a Passwords,
+  ) -> CreateAccountDetailsWithDBClient<'a> {
     CreateAccountDetailsWithDBClient {
       username: self.username,
       password: self.password,
       confirm_password: self.confirm_password,
-      db_client: db_client,
+      email: self.email,
+      db_client,
+      passwords,
     }
   }
 }
@@ -69,17 +242,18 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
       .await
       .map_err(|e| json!({ "message": format!("Postgres statement error {}", e.to_string()) }))?;
 
-    let (username, _) = self.validate_details().await?;
+    let (username, _, email) = self.validate_details().await?;
+    let password_hash = self.hash_password().await?;
 
     self
       .db_client
       .query(
         &stmt,
-        &[&username, &self.hash_password()?, &Utc::now().naive_utc()],
+        &[&username, &password_hash, &Utc::now().naive_utc(), &email],
       )
       .await
       .map_err(|e| json!({ "message": format!("e {}", e.to_string()) }))?
-      .get(0)
+      .first()
       .ok_or("No id returned".to_owned())
       .map_err(|e| json!({ "message": e }))?
       .try_get("id")
@@ -87,19 +261,21 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
       .map(|id| UserAuthDetails {
         id,
         username,
-        expires_at: Utc::now().naive_utc() + Duration::weeks(2),
+        expires_at: Utc::now().naive_utc() + Duration::minutes(ACCESS_TOKEN_MINUTES),
+        session_id: None,
+        scopes: None,
       })
   }
 
   async fn get_insert_statement(&self) -> Result<Statement, tokio_postgres::Error> {
-    let stmt = "INSERT INTO users (username, password_hash, created_at)
-                      VALUES ($1, $2, $3)
+    let stmt = "INSERT INTO users (username, password_hash, created_at, email)
+                      VALUES ($1, $2, $3, $4)
                       RETURNING id";
 
     self.db_client.prepare(stmt).await
   }
 
-  async fn validate_details(&self) -> Result<(String, String), Value> {
+  async fn validate_details(&self) -> Result<(String, String, Option<String>), Value> {
     if self.username.is_none() {
       return Err(json!({
           "name": "username",
@@ -124,7 +300,7 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
     let username = self.username.as_ref().unwrap().trim();
     let password = self.password.as_ref().unwrap();
 
-    if username.len() == 0 {
+    if username.is_empty() {
       return Err(json!({
           "name": "username",
           "message": "Username is required"
@@ -138,12 +314,7 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
       }));
     }
 
-    if password.len() < 4 || password.len() > 50 {
-      return Err(json!({
-        "name": "password",
-        "message": "Password should greater than 3 but not more than 50 characters"
-      }));
-    }
+    self.passwords.validate(password)?;
 
     let is_username_taken = self.is_username_taken().await.map_err(|e| {
       json!({
@@ -159,7 +330,25 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
       }));
     };
 
-    Ok((username.to_owned(), password.to_owned()))
+    let email = match self.email.as_deref().map(str::trim) {
+      Some(e) if !e.is_empty() => Some(validate_email(e)?),
+      _ => None,
+    };
+
+    if let Some(email) = &email {
+      let is_email_taken = is_email_taken(self.db_client, email, None)
+        .await
+        .map_err(|e| json!({"name": "email", "message": e}))?;
+
+      if is_email_taken {
+        return Err(json!({
+          "name": "email",
+          "message": "Email is already taken"
+        }));
+      }
+    }
+
+    Ok((username.to_owned(), password.to_owned(), email))
   }
 
   async fn is_username_taken(&self) -> Result<bool, String> {
@@ -179,7 +368,7 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
       .query(&stmt, &[&username])
       .await
       .map_err(|_| "Cannot verify uniqueness of username".to_owned())?
-      .get(0)
+      .first()
       .ok_or("Cannot verify uniqueness of username".to_owned())?
       .try_get("exists")
       .map_err(|_| "Cannot verify uniqueness of username".to_owned())
@@ -191,7 +380,7 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
     self.db_client.prepare(stmt).await
   }
 
-  fn hash_password(&self) -> Result<String, Value> {
+  async fn hash_password(&self) -> Result<String, Value> {
     if self.password.is_none() {
       return Err(json!({
           "name": "password",
@@ -199,22 +388,25 @@ impl<'a> CreateAccountDetailsWithDBClient<'a> {
       }));
     }
 
-    bcrypt::hash(self.password.as_ref().unwrap(), 6).map_err(|e| {
-      json!({
-        "name": "password",
-        "message": format!("Error hashing password\n{}", e.to_string())
-
-      })
-    })
+    hash_password(self.passwords, self.password.as_ref().unwrap()).await
   }
 }
 
 impl LoginDetails {
-  pub fn add_db_client(self, db_client: &Client) -> LoginDetailsWithDBClient {
+  pub fn username(&self) -> Option<&str> {
+    self.username.as_deref()
+  }
+
+  pub fn add_db_client<'a>(
+    self,
+    db_client: &'a Client,
+    passwords: &'a Passwords,
+  ) -> LoginDetailsWithDBClient<'a> {
     LoginDetailsWithDBClient {
       username: self.username,
       password: self.password,
       db_client,
+      passwords,
     }
   }
 }
@@ -256,10 +448,14 @@ impl<'a> LoginDetailsWithDBClient<'a> {
         })
       })?;
 
-    let row = vec_row.get(0).ok_or(json!({
-      "name":"username",
-      "message":"Username does not exists"
-    }))?;
+    let Some(row) = vec_row.first() else {
+      self
+        .passwords
+        .verify_dummy(self.password.as_ref().unwrap())
+        .await;
+
+      return Err(invalid_credentials());
+    };
 
     let id = row.try_get::<&str, i32>("id");
     let username = row.try_get::<&str, String>("username");
@@ -271,24 +467,38 @@ impl<'a> LoginDetailsWithDBClient<'a> {
       }));
     }
 
-    let wrong_password = !bcrypt::verify(self.password.as_ref().unwrap(), password_hash.unwrap())
+    let password = self.password.as_ref().unwrap();
+    let password_hash = password_hash.unwrap();
+
+    let wrong_password = !self
+      .passwords
+      .verify(password, password_hash)
+      .await
       .map_err(|_| {
-      json!({
-        "message": "Error verifying password"
-      })
-    })?;
+        json!({
+          "message": "Error verifying password"
+        })
+      })?;
 
     if wrong_password {
-      return Err(json!({
-        "name": "password",
-        "message": "Wrong password"
-      }));
+      return Err(invalid_credentials());
+    }
+
+    if self.passwords.needs_rehash(password_hash) {
+      if let Err(e) = self
+        .rehash_password(*id.as_ref().unwrap(), password, password_hash)
+        .await
+      {
+        eprintln!("Could not rehash password: {}", e["message"]);
+      }
     }
 
     Ok(UserAuthDetails {
       id: id.unwrap(),
       username: username.unwrap(),
-      expires_at: Utc::now().naive_utc() + Duration::weeks(2),
+      expires_at: Utc::now().naive_utc() + Duration::minutes(ACCESS_TOKEN_MINUTES),
+      session_id: None,
+      scopes: None,
     })
   }
 
@@ -297,6 +507,25 @@ impl<'a> LoginDetailsWithDBClient<'a> {
 
     self.db_client.prepare(stmt).await
   }
+
+  async fn rehash_password(&self, id: i32, password: &str, old_hash: &str) -> Result<(), Value> {
+    let password_hash = hash_password(self.passwords, password).await?;
+
+    let stmt = "UPDATE users SET password_hash = $2 WHERE id = $1 AND password_hash = $3";
+
+    let stmt = self
+      .db_client
+      .prepare(stmt)
+      .await
+      .map_err(|e| json!({ "message": e.to_string() }))?;
+
+    self
+      .db_client
+      .execute(&stmt, &[&id, &password_hash, &old_hash])
+      .await
+      .map_err(|e| json!({ "message": e.to_string() }))
+      .map(|_| ())
+  }
 }
 
 impl UserAuthDetails {
@@ -317,13 +546,1118 @@ impl UserAuthDetails {
       })
   }
 
-  pub fn to_jwt(self) -> String {
+  pub fn to_jwt(&self) -> String {
     let jwt_secret = env::var("JWT_SECRET").unwrap_or("ItsPublic".to_owned());
 
     let key: Hmac<Sha256> = Hmac::new_from_slice(jwt_secret.as_bytes()).unwrap();
 
     self.sign_with_key(&key).unwrap_or(String::new())
   }
+
+  pub async fn verify_session(&self, db_client: &Client) -> bool {
+    let Some(session_id) = self.session_id else {
+      return false;
+    };
+
+    let stmt = "WITH s AS (SELECT id, last_seen_at FROM sessions
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL),
+      u AS (UPDATE sessions SET last_seen_at = $3 FROM s
+        WHERE sessions.id = s.id AND s.last_seen_at < $3::TIMESTAMP - INTERVAL '1 minute')
+      SELECT id FROM s";
+
+    let Ok(stmt) = db_client.prepare(stmt).await else {
+      return false;
+    };
+
+    db_client
+      .query(&stmt, &[&session_id, &self.id, &Utc::now().naive_utc()])
+      .await
+      .map(|r| !r.is_empty())
+      .unwrap_or(false)
+  }
+}
+
+impl UserAuthDetails {
+  pub async fn from_personal_access_token(
+    token: &str,
+    db_client: &Client,
+  ) -> Option<UserAuthDetails> {
+    if !token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+      return None;
+    }
+
+    let stmt = "WITH t AS (SELECT t.id, t.user_id, u.username, t.scopes, t.expires_at, t.last_used_at
+        FROM personal_access_tokens t INNER JOIN users u ON u.id = t.user_id
+        WHERE t.token_hash = $1 AND t.revoked_at IS NULL AND (t.expires_at IS NULL OR t.expires_at > $2)),
+      u AS (UPDATE personal_access_tokens SET last_used_at = $2 FROM t
+        WHERE personal_access_tokens.id = t.id
+        AND (t.last_used_at IS NULL OR t.last_used_at < $2::TIMESTAMP - INTERVAL '1 minute'))
+      SELECT user_id, username, scopes, expires_at FROM t";
+
+    let stmt = db_client.prepare(stmt).await.ok()?;
+
+    let now = Utc::now().naive_utc();
+
+    let rows = db_client
+      .query(&stmt, &[&hash_token(token), &now])
+      .await
+      .ok()?;
+
+    let row = rows.first()?;
+
+    Some(UserAuthDetails {
+      id: row.try_get("user_id").ok()?,
+      username: row.try_get("username").ok()?,
+      expires_at: row
+        .try_get::<&str, Option<NaiveDateTime>>("expires_at")
+        .ok()?
+        .unwrap_or(now + Duration::minutes(ACCESS_TOKEN_MINUTES)),
+      session_id: None,
+      scopes: Some(row.try_get("scopes").ok()?),
+    })
+  }
+
+  pub fn has_scope(&self, scope: TokenScope) -> bool {
+    self.scopes.as_ref().is_none_or(|s| s.contains(&scope))
+  }
+}
+
+impl UserAuth {
+  pub fn require_scope(self, scope: TokenScope) -> Result<UserAuth, Value> {
+    match self.token_details {
+      Some(details) if details.has_scope(scope) => Ok(UserAuth {
+        details: Some(details),
+        token_details: None,
+      }),
+      Some(_) => Err(json!({
+        "name": "scope",
+        "message": format!("Access token is missing the {} scope", scope.as_str())
+      })),
+      None => Ok(self),
+    }
+  }
+
+  pub fn require_session(self) -> Result<UserAuth, Value> {
+    let is_access_token =
+      self.token_details.is_some() || self.details.as_ref().is_some_and(|d| d.scopes.is_some());
+
+    if is_access_token {
+      return Err(json!({
+        "name": "scope",
+        "message": "Account settings need a regular sign-in, not an access token"
+      }));
+    }
+
+    Ok(self)
+  }
+}
+
+impl TokenScope {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      TokenScope::Read => "read",
+      TokenScope::Post => "post",
+      TokenScope::Comment => "comment",
+      TokenScope::Moderate => "moderate",
+    }
+  }
+}
+
+impl<'a> IssueTokens<'a> {
+  pub async fn exec(&self) -> Result<AuthTokens, (StatusCode, Value)> {
+    let refresh_token = generate_token();
+    let now = Utc::now().naive_utc();
+
+    let session_id: i32 = self
+      .db_client
+      .query(
+        &self.get_insert_statement().await?,
+        &[
+          &self.user_details.id,
+          &now,
+          &hash_token(&refresh_token),
+          &(now + Duration::days(REFRESH_TOKEN_DAYS)),
+          &self.client_info.user_agent,
+          &self.client_info.ip,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": "No session id returned"}),
+      ))?
+      .try_get("session_id")
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let user_details = UserAuthDetails {
+      session_id: Some(session_id),
+      scopes: None,
+      ..self.user_details.clone()
+    };
+
+    Ok(AuthTokens {
+      access_token: user_details.to_jwt(),
+      refresh_token,
+      expires_at: user_details.expires_at,
+    })
+  }
+
+  async fn get_insert_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "WITH s AS (INSERT INTO sessions (user_id, created_at, last_seen_at, user_agent, ip)
+      VALUES ($1, $2, $2, $5, $6) RETURNING id)
+      INSERT INTO refresh_tokens (session_id, token_hash, created_at, expires_at)
+      SELECT s.id, $3, $2, $4 FROM s RETURNING session_id";
+
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+}
+
+impl RefreshTokenDetails {
+  pub fn add_db_client(self, db_client: &Client) -> RefreshTokenDetailsWithDBClient<'_> {
+    RefreshTokenDetailsWithDBClient {
+      refresh_token: self.refresh_token,
+      db_client,
+    }
+  }
+}
+
+impl<'a> RefreshTokenDetailsWithDBClient<'a> {
+  pub async fn rotate(&self) -> Result<AuthTokens, (StatusCode, Value)> {
+    let token_hash = hash_token(self.get_refresh_token()?);
+    let refresh_token = generate_token();
+    let now = Utc::now().naive_utc();
+
+    let rows = self
+      .db_client
+      .query(
+        &self.get_rotate_statement().await?,
+        &[
+          &token_hash,
+          &now,
+          &hash_token(&refresh_token),
+          &(now + Duration::days(REFRESH_TOKEN_DAYS)),
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let Some(row) = rows.first() else {
+      return Err(self.handle_rejected_token(&token_hash, now).await?);
+    };
+
+    let session_id = row.try_get::<&str, i32>("session_id");
+    let id = row.try_get::<&str, i32>("user_id");
+    let username = row.try_get::<&str, String>("username");
+
+    let user_details = match (session_id, id, username) {
+      (Ok(session_id), Ok(id), Ok(username)) => UserAuthDetails {
+        id,
+        username,
+        expires_at: now + Duration::minutes(ACCESS_TOKEN_MINUTES),
+        session_id: Some(session_id),
+        scopes: None,
+      },
+      _ => {
+        return Err((
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message":"Error converting postgres types" }),
+        ))
+      }
+    };
+
+    Ok(AuthTokens {
+      access_token: user_details.to_jwt(),
+      refresh_token,
+      expires_at: user_details.expires_at,
+    })
+  }
+
+  pub async fn revoke(&self) -> Result<(), (StatusCode, Value)> {
+    let token_hash = hash_token(self.get_refresh_token()?);
+
+    self
+      .db_client
+      .execute(
+        &self.get_revoke_statement().await?,
+        &[&token_hash, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  async fn handle_rejected_token(
+    &self,
+    token_hash: &str,
+    now: NaiveDateTime,
+  ) -> Result<(StatusCode, Value), (StatusCode, Value)> {
+    let never_used = self
+      .db_client
+      .query(&self.get_reuse_statement().await?, &[&token_hash])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .is_empty();
+
+    if never_used {
+      return Ok((
+        StatusCode::UNAUTHORIZED,
+        json!({"name": "re-auth", "message": "Invalid or expired refresh token"}),
+      ));
+    }
+
+    self
+      .db_client
+      .execute(&self.get_revoke_statement().await?, &[&token_hash, &now])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    Ok((
+      StatusCode::UNAUTHORIZED,
+      json!({"name": "re-auth", "message": "Refresh token has already been used. Sign in again"}),
+    ))
+  }
+
+  fn get_refresh_token(&self) -> Result<&str, (StatusCode, Value)> {
+    self
+      .refresh_token
+      .as_deref()
+      .map(str::trim)
+      .filter(|t| !t.is_empty())
+      .ok_or((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "refresh_token", "message": "Refresh token is required"}),
+      ))
+  }
+
+  async fn get_rotate_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "WITH used AS (UPDATE refresh_tokens rt SET used_at = $2 FROM sessions s, users u
+      WHERE rt.token_hash = $1 AND rt.used_at IS NULL AND rt.expires_at > $2
+      AND s.id = rt.session_id AND s.revoked_at IS NULL AND u.id = s.user_id
+      RETURNING rt.session_id, s.user_id, u.username),
+    issued AS (INSERT INTO refresh_tokens (session_id, token_hash, created_at, expires_at)
+      SELECT session_id, $3, $2, $4 FROM used)
+    SELECT session_id, user_id, username FROM used";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_reuse_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT 1 FROM refresh_tokens WHERE token_hash = $1 AND used_at IS NOT NULL";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_revoke_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "UPDATE sessions s SET revoked_at = $2 FROM refresh_tokens rt
+    WHERE rt.token_hash = $1 AND s.id = rt.session_id AND s.revoked_at IS NULL";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({ "message": e.to_string() }),
+      )
+    })
+  }
+}
+
+impl ChangePasswordDetails {
+  pub fn add_details<'a>(
+    self,
+    db_client: &'a Client,
+    passwords: &'a Passwords,
+    user_details: UserAuthDetails,
+  ) -> ChangePasswordDetailsWithDBClient<'a> {
+    ChangePasswordDetailsWithDBClient {
+      current_password: self.current_password,
+      password: self.password,
+      confirm_password: self.confirm_password,
+      db_client,
+      passwords,
+      user_details,
+    }
+  }
+}
+
+impl<'a> ChangePasswordDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let (current_password, password) = self.validate_details()?;
+
+    let password_hash: String = self
+      .db_client
+      .query(
+        &self.get_select_statement().await?,
+        &[&self.user_details.id],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::NOT_FOUND,
+        json!({"message": "User does not exists"}),
+      ))?
+      .try_get("password_hash")
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let wrong_password = !self
+      .passwords
+      .verify(current_password, &password_hash)
+      .await
+      .map_err(|_| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": "Error verifying password"}),
+        )
+      })?;
+
+    if wrong_password {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "current_password", "message": "Wrong password"}),
+      ));
+    }
+
+    let password_hash = hash_password(self.passwords, password)
+      .await
+      .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    self
+      .db_client
+      .execute(
+        &self.get_update_statement().await?,
+        &[
+          &self.user_details.id,
+          &password_hash,
+          &Utc::now().naive_utc(),
+          &self.user_details.session_id,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  fn validate_details(&self) -> Result<(&str, &str), (StatusCode, Value)> {
+    let current_password = self.current_password.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "current_password", "message": "Current password is required"}),
+    ))?;
+
+    let password = self.password.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "password", "message": "Password is required"}),
+    ))?;
+
+    if self.password != self.confirm_password {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "confirm_password", "message": "Passwords does not match"}),
+      ));
+    }
+
+    self
+      .passwords
+      .validate(password)
+      .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    Ok((current_password, password))
+  }
+
+  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT password_hash FROM users WHERE id = $1";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_update_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "WITH u AS (UPDATE users SET password_hash = $2 WHERE id = $1),
+      t AS (UPDATE personal_access_tokens SET revoked_at = $3
+        WHERE user_id = $1 AND revoked_at IS NULL)
+      UPDATE sessions SET revoked_at = $3
+      WHERE user_id = $1 AND revoked_at IS NULL AND id IS DISTINCT FROM $4";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({ "message": e.to_string() }),
+      )
+    })
+  }
+}
+
+impl<'a> SendVerificationEmail<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let stmt = "SELECT username, email FROM users
+      WHERE id = $1 AND email IS NOT NULL AND email_verified_at IS NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(&stmt, &[&self.user_id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let row = rows.first().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "email", "message": "No unverified email on this account"}),
+    ))?;
+
+    let username = row.try_get::<&str, String>("username");
+    let email = row.try_get::<&str, String>("email");
+
+    let (username, email) = match (username, email) {
+      (Ok(username), Ok(email)) => (username, email),
+      _ => {
+        return Err((
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message":"Error converting postgres types" }),
+        ))
+      }
+    };
+
+    let token = create_email_token(
+      self.db_client,
+      self.user_id,
+      "verify_email",
+      &email,
+      Duration::hours(VERIFY_EMAIL_TOKEN_HOURS),
+    )
+    .await?;
+
+    self
+      .mailer
+      .send(Mail {
+        to: email,
+        subject: "Verify your email address".to_owned(),
+        body: format!(
+          "Hi {username},\n\nUse the link below to verify your email address. It expires in {VERIFY_EMAIL_TOKEN_HOURS} hours.\n\n{}/verify-email?token={token}\n\nIf you did not add this email to a forum account, you can ignore this message.",
+          get_app_url()
+        ),
+      })
+      .await
+      .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, json!({ "message": e })))
+  }
+}
+
+impl UpdateEmailDetails {
+  pub fn add_details<'a>(
+    self,
+    db_client: &'a Client,
+    mailer: &'a dyn Mailer,
+    user_details: UserAuthDetails,
+  ) -> UpdateEmailDetailsWithDBClient<'a> {
+    UpdateEmailDetailsWithDBClient {
+      email: self.email,
+      db_client,
+      mailer,
+      user_details,
+    }
+  }
+}
+
+impl<'a> UpdateEmailDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let email = self.email.as_deref().map(str::trim).unwrap_or_default();
+
+    if email.is_empty() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "email", "message": "Email is required"}),
+      ));
+    }
+
+    let email = validate_email(email).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let is_email_taken = is_email_taken(self.db_client, &email, Some(self.user_details.id))
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"name": "email", "message": e}),
+        )
+      })?;
+
+    if is_email_taken {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "email", "message": "Email is already taken"}),
+      ));
+    }
+
+    let stmt = "UPDATE users SET email = $2, email_verified_at = NULL
+      WHERE id = $1 AND LOWER(COALESCE(email, '')) != LOWER($2)";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let updated = self
+      .db_client
+      .execute(&stmt, &[&self.user_details.id, &email])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if updated == 0 {
+      return Ok(());
+    }
+
+    SendVerificationEmail {
+      db_client: self.db_client,
+      mailer: self.mailer,
+      user_id: self.user_details.id,
+    }
+    .exec()
+    .await
+  }
+}
+
+impl VerifyEmailDetails {
+  pub fn add_db_client(self, db_client: &Client) -> VerifyEmailDetailsWithDBClient<'_> {
+    VerifyEmailDetailsWithDBClient {
+      token: self.token,
+      db_client,
+    }
+  }
+}
+
+impl<'a> VerifyEmailDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let token = get_email_token(&self.token)?;
+
+    let stmt = "WITH t AS (UPDATE email_tokens SET used_at = $2
+        WHERE token_hash = $1 AND purpose = 'verify_email' AND used_at IS NULL AND expires_at > $2
+        RETURNING user_id, email)
+      UPDATE users u SET email_verified_at = $2 FROM t
+      WHERE u.id = t.user_id AND LOWER(u.email) = LOWER(t.email)
+      RETURNING u.id";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let verified = self
+      .db_client
+      .query(&stmt, &[&hash_token(token), &Utc::now().naive_utc()])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if verified.is_empty() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "token", "message": "Invalid or expired token"}),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+impl ForgotPasswordDetails {
+  pub fn add_details<'a>(
+    self,
+    db_client: &'a Client,
+    mailer: &'a dyn Mailer,
+  ) -> ForgotPasswordDetailsWithDBClient<'a> {
+    ForgotPasswordDetailsWithDBClient {
+      email: self.email,
+      db_client,
+      mailer,
+    }
+  }
+}
+
+impl<'a> ForgotPasswordDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let email = self.email.as_deref().map(str::trim).unwrap_or_default();
+
+    if email.is_empty() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "email", "message": "Email is required"}),
+      ));
+    }
+
+    let stmt = "SELECT id, username, email FROM users
+      WHERE LOWER(email) = LOWER($1) AND email_verified_at IS NOT NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self.db_client.query(&stmt, &[&email]).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let Some(row) = rows.first() else {
+      return Ok(());
+    };
+
+    let id = row.try_get::<&str, i32>("id");
+    let username = row.try_get::<&str, String>("username");
+    let email = row.try_get::<&str, String>("email");
+
+    let (id, username, email) = match (id, username, email) {
+      (Ok(id), Ok(username), Ok(email)) => (id, username, email),
+      _ => {
+        return Err((
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message":"Error converting postgres types" }),
+        ))
+      }
+    };
+
+    let token = create_email_token(
+      self.db_client,
+      id,
+      "reset_password",
+      &email,
+      Duration::minutes(RESET_PASSWORD_TOKEN_MINUTES),
+    )
+    .await?;
+
+    self
+      .mailer
+      .send(Mail {
+        to: email,
+        subject: "Reset your password".to_owned(),
+        body: format!(
+          "Hi {username},\n\nUse the link below to choose a new password. It expires in {RESET_PASSWORD_TOKEN_MINUTES} minutes and can only be used once.\n\n{}/reset-password?token={token}\n\nIf you did not ask for a password reset, you can ignore this message.",
+          get_app_url()
+        ),
+      })
+      .await
+      .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, json!({ "message": e })))
+  }
+}
+
+impl ResetPasswordDetails {
+  pub fn add_db_client<'a>(
+    self,
+    db_client: &'a Client,
+    passwords: &'a Passwords,
+  ) -> ResetPasswordDetailsWithDBClient<'a> {
+    ResetPasswordDetailsWithDBClient {
+      token: self.token,
+      password: self.password,
+      confirm_password: self.confirm_password,
+      db_client,
+      passwords,
+    }
+  }
+}
+
+impl<'a> ResetPasswordDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let token = get_email_token(&self.token)?;
+
+    let password = self.password.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "password", "message": "Password is required"}),
+    ))?;
+
+    if self.password != self.confirm_password {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "confirm_password", "message": "Passwords does not match"}),
+      ));
+    }
+
+    self
+      .passwords
+      .validate(password)
+      .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let password_hash = hash_password(self.passwords, password)
+      .await
+      .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    let stmt = "WITH t AS (UPDATE email_tokens SET used_at = $3
+        WHERE token_hash = $1 AND purpose = 'reset_password' AND used_at IS NULL AND expires_at > $3
+        RETURNING user_id),
+      u AS (UPDATE users SET password_hash = $2 FROM t WHERE users.id = t.user_id RETURNING users.id),
+      s AS (UPDATE sessions SET revoked_at = $3 FROM u
+        WHERE sessions.user_id = u.id AND sessions.revoked_at IS NULL),
+      p AS (UPDATE personal_access_tokens SET revoked_at = $3 FROM u
+        WHERE personal_access_tokens.user_id = u.id AND personal_access_tokens.revoked_at IS NULL)
+      SELECT id FROM u";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let reset = self
+      .db_client
+      .query(
+        &stmt,
+        &[&hash_token(token), &password_hash, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if reset.is_empty() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "token", "message": "Invalid or expired token"}),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+impl<'a> SignOutAll<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let stmt = "WITH t AS (UPDATE personal_access_tokens SET revoked_at = $2
+        WHERE user_id = $1 AND revoked_at IS NULL)
+      UPDATE sessions SET revoked_at = $2 WHERE user_id = $1 AND revoked_at IS NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .execute(&stmt, &[&self.user_details.id, &Utc::now().naive_utc()])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+}
+
+fn validate_email(email: &str) -> Result<String, Value> {
+  let email = email.trim();
+
+  let err = json!({
+    "name": "email",
+    "message": "Email address is not valid"
+  });
+
+  if email.len() > 254 || email.contains(char::is_whitespace) {
+    return Err(err);
+  }
+
+  match email.split_once('@') {
+    Some((local, domain))
+      if !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.') =>
+    {
+      Ok(email.to_owned())
+    }
+    _ => Err(err),
+  }
+}
+
+async fn is_email_taken(
+  db_client: &Client,
+  email: &str,
+  except_user_id: Option<i32>,
+) -> Result<bool, String> {
+  let stmt = "SELECT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($1) AND id IS DISTINCT FROM $2) as exists";
+
+  let stmt = db_client
+    .prepare(stmt)
+    .await
+    .map_err(|_| "Cannot verify uniqueness of email".to_owned())?;
+
+  db_client
+    .query(&stmt, &[&email, &except_user_id])
+    .await
+    .map_err(|_| "Cannot verify uniqueness of email".to_owned())?
+    .first()
+    .ok_or("Cannot verify uniqueness of email".to_owned())?
+    .try_get("exists")
+    .map_err(|_| "Cannot verify uniqueness of email".to_owned())
+}
+
+async fn create_email_token(
+  db_client: &Client,
+  user_id: i32,
+  purpose: &str,
+  email: &str,
+  valid_for: Duration,
+) -> Result<String, (StatusCode, Value)> {
+  let token = generate_token();
+  let now = Utc::now().naive_utc();
+
+  let stmt = "WITH o AS (UPDATE email_tokens SET used_at = $5
+      WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL)
+    INSERT INTO email_tokens (user_id, purpose, email, token_hash, created_at, expires_at)
+    VALUES ($1, $2, $3, $4, $5, $6)";
+
+  let stmt = db_client.prepare(stmt).await.map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
+
+  db_client
+    .execute(
+      &stmt,
+      &[
+        &user_id,
+        &purpose,
+        &email,
+        &hash_token(&token),
+        &now,
+        &(now + valid_for),
+      ],
+    )
+    .await
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+  Ok(token)
+}
+
+fn get_email_token(token: &Option<String>) -> Result<&str, (StatusCode, Value)> {
+  token
+    .as_deref()
+    .map(str::trim)
+    .filter(|t| !t.is_empty())
+    .ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "token", "message": "Token is required"}),
+    ))
+}
+
+fn get_app_url() -> String {
+  env::var("CORS_ORIGIN").unwrap_or("http://localhost:5173".to_owned())
+}
+
+pub(super) async fn hash_password(passwords: &Passwords, password: &str) -> Result<String, Value> {
+  passwords.hash(password).await.map_err(|e| {
+    json!({
+      "name": "password",
+      "message": format!("Error hashing password\n{}", e)
+    })
+  })
+}
+
+fn invalid_credentials() -> Value {
+  json!({
+    "name": "credentials",
+    "message": "Invalid username or password"
+  })
+}
+
+pub(super) fn generate_token() -> String {
+  URL_SAFE_NO_PAD.encode(rand::thread_rng().gen::<[u8; 32]>())
+}
+
+pub(super) fn hash_token(token: &str) -> String {
+  format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+impl<'a> FetchSessions<'a> {
+  pub async fn exec(&self) -> Result<Vec<Session>, (StatusCode, Value)> {
+    let stmt = "SELECT s.id, s.created_at, s.last_seen_at, s.user_agent, s.ip FROM sessions s
+      WHERE s.user_id = $1 AND s.revoked_at IS NULL
+      AND EXISTS (SELECT 1 FROM refresh_tokens rt
+        WHERE rt.session_id = s.id AND rt.used_at IS NULL AND rt.expires_at > $2)
+      ORDER BY s.last_seen_at DESC";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .query(&stmt, &[&self.user_details.id, &Utc::now().naive_utc()])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .iter()
+      .map(|r| Session::from_row(r, self.user_details.session_id))
+      .collect()
+  }
+}
+
+impl Session {
+  pub fn from_row(
+    row: &tokio_postgres::Row,
+    current_session_id: Option<i32>,
+  ) -> Result<Session, (StatusCode, Value)> {
+    let id = row.try_get::<&str, i32>("id");
+    let created_at = row.try_get::<&str, NaiveDateTime>("created_at");
+    let last_seen_at = row.try_get::<&str, NaiveDateTime>("last_seen_at");
+    let user_agent = row.try_get::<&str, Option<String>>("user_agent");
+    let ip = row.try_get::<&str, Option<String>>("ip");
+
+    match (id, created_at, last_seen_at, user_agent, ip) {
+      (Ok(id), Ok(created_at), Ok(last_seen_at), Ok(user_agent), Ok(ip)) => Ok(Session {
+        id,
+        created_at,
+        last_seen_at,
+        user_agent,
+        ip,
+        current: current_session_id == Some(id),
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+impl<'a> RevokeSession<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let stmt = "UPDATE sessions SET revoked_at = $3
+      WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let revoked = self
+      .db_client
+      .execute(
+        &stmt,
+        &[&self.id, &self.user_details.id, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if revoked == 0 {
+      return Err((
+        StatusCode::NOT_FOUND,
+        json!({"message": "No active session found with such id"}),
+      ));
+    }
+
+    Ok(())
+  }
 }
 
 impl FromRequest for UserAuth {
@@ -331,8 +1665,41 @@ impl FromRequest for UserAuth {
   type Future = Ready<Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
-    ready(Ok(UserAuth {
-      details: req.extensions().get::<UserAuthDetails>().cloned(),
+    let details = req.extensions().get::<UserAuthDetails>().cloned();
+
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD);
+
+    match details {
+      Some(d) if d.scopes.is_some() && !(is_read && d.has_scope(TokenScope::Read)) => {
+        ready(Ok(UserAuth {
+          details: None,
+          token_details: Some(d),
+        }))
+      }
+      details => ready(Ok(UserAuth {
+        details,
+        token_details: None,
+      })),
+    }
+  }
+}
+
+impl FromRequest for ClientInfo {
+  type Error = Error;
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+    ready(Ok(ClientInfo {
+      user_agent: req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.chars().take(500).collect()),
+      ip: req
+        .connection_info()
+        .realip_remote_addr()
+        .map(|s| s.chars().take(45).collect()),
+      peer_ip: req.peer_addr().map(|a| a.ip().to_string()),
     }))
   }
 }
@@ -0,0 +1,224 @@
+use actix_web::{
+  web::{Data, Json, Path},
+  HttpResponse,
+};
+use deadpool_postgres::Pool;
+use serde_json::json;
+
+use crate::{api::UserAuth, oidc::OidcProviders, password::Passwords};
+
+use super::super::models::{ClientInfo, IssueTokens};
+use super::super::two_factor::models::LoginChallenge;
+use super::models;
+
+pub async fn fetch_providers(providers: Data<OidcProviders>) -> HttpResponse {
+  HttpResponse::Ok().json(json!({
+    "success": true,
+    "data": models::list_providers(&providers)
+  }))
+}
+
+pub async fn start_login(
+  provider: Path<String>,
+  db_pool: Data<Pool>,
+  providers: Data<OidcProviders>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::StartOidcLogin {
+    db_client: &db_client,
+    providers: &providers,
+    provider: &provider,
+    link_user_id: None,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn start_link(
+  user_details: UserAuth,
+  provider: Path<String>,
+  db_pool: Data<Pool>,
+  providers: Data<OidcProviders>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::StartOidcLogin {
+    db_client: &db_client,
+    providers: &providers,
+    provider: &provider,
+    link_user_id: Some(user_details.id),
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn callback(
+  provider: Path<String>,
+  body: Json<models::OidcCallbackDetails>,
+  client_info: ClientInfo,
+  db_pool: Data<Pool>,
+  providers: Data<OidcProviders>,
+  passwords: Data<Passwords>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, &providers, &passwords, &provider)
+    .exec()
+    .await;
+
+  let res = match res {
+    Ok(models::CallbackOutcome::SignedIn(res)) => res,
+
+    Ok(models::CallbackOutcome::Linked) => {
+      return HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": { "linked": true }
+      }))
+    }
+
+    Err((s, v)) => {
+      return HttpResponse::Ok().status(s).json(json!({
+        "success": false,
+        "message": v["message"],
+        "error": v
+      }))
+    }
+  };
+
+  let challenge = LoginChallenge {
+    db_client: &db_client,
+    user_details: &res,
+  }
+  .create()
+  .await;
+
+  match challenge {
+    Ok(Some(challenge)) => {
+      return HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+          "id": res.id,
+          "username": res.username,
+          "two_factor_required": true,
+          "challenge": challenge
+        }
+      }))
+    }
+
+    Ok(None) => (),
+
+    Err((s, v)) => {
+      return HttpResponse::Ok().status(s).json(json!({
+        "success": false,
+        "message": v["message"],
+        "error": v
+      }))
+    }
+  }
+
+  let tokens = IssueTokens {
+    db_client: &db_client,
+    user_details: &res,
+    client_info: &client_info,
+  }
+  .exec()
+  .await;
+
+  match tokens {
+    Ok(tokens) => HttpResponse::Ok().json(json!({
+        "success": true,
+        "data": {
+          "id": res.id,
+          "username": res.username,
+          "access_token": tokens.access_token,
+          "refresh_token": tokens.refresh_token,
+          "expires_at": tokens.expires_at
+    }})),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
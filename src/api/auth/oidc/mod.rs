@@ -0,0 +1,13 @@
+use actix_web::web::{self, ServiceConfig};
+mod controllers;
+mod models;
+
+pub fn view(cfg: &mut ServiceConfig) {
+  cfg.route("", web::get().to(controllers::fetch_providers));
+  cfg.route("/{provider}", web::post().to(controllers::start_login));
+  cfg.route("/{provider}/link", web::post().to(controllers::start_link));
+  cfg.route(
+    "/{provider}/callback",
+    web::post().to(controllers::callback),
+  );
+}
@@ -0,0 +1,553 @@
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDateTime, Utc};
+use deadpool_postgres::Client;
+use openidconnect::{
+  core::CoreAuthenticationFlow, AccessTokenHash, AuthorizationCode, CsrfToken, Nonce,
+  OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier, TokenResponse,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+  api::UserAuthDetails,
+  oidc::{OidcProvider, OidcProviders},
+  password::Passwords,
+};
+
+use super::super::models::{generate_token, hash_password, hash_token, ACCESS_TOKEN_MINUTES};
+
+const STATE_MINUTES: i64 = 10;
+
+#[derive(Serialize)]
+pub struct Provider<'a> {
+  name: &'a str,
+  display_name: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct AuthorizationRequest {
+  authorization_url: String,
+  expires_at: NaiveDateTime,
+}
+
+pub enum CallbackOutcome {
+  SignedIn(UserAuthDetails),
+  Linked,
+}
+
+pub struct StartOidcLogin<'a> {
+  pub db_client: &'a Client,
+  pub providers: &'a OidcProviders,
+  pub provider: &'a str,
+  pub link_user_id: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OidcCallbackDetails {
+  code: Option<String>,
+  state: Option<String>,
+}
+
+pub struct OidcCallbackDetailsWithDBClient<'a> {
+  code: Option<String>,
+  state: Option<String>,
+  db_client: &'a Client,
+  providers: &'a OidcProviders,
+  passwords: &'a Passwords,
+  provider: &'a str,
+}
+
+struct ExternalIdentity {
+  subject: String,
+  email: Option<String>,
+  email_verified: bool,
+  preferred_username: Option<String>,
+}
+
+pub fn list_providers(providers: &OidcProviders) -> Vec<Provider<'_>> {
+  providers
+    .list()
+    .into_iter()
+    .map(|p| Provider {
+      name: &p.name,
+      display_name: &p.display_name,
+    })
+    .collect()
+}
+
+impl<'a> StartOidcLogin<'a> {
+  pub async fn exec(&self) -> Result<AuthorizationRequest, (StatusCode, Value)> {
+    let provider = get_provider(self.providers, self.provider)?;
+
+    let client = provider
+      .client(self.providers.http_client())
+      .await
+      .map_err(provider_unreachable_error)?;
+
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    let mut request = client
+      .authorize_url(
+        CoreAuthenticationFlow::AuthorizationCode,
+        CsrfToken::new_random,
+        Nonce::new_random,
+      )
+      .set_pkce_challenge(pkce_challenge);
+
+    for scope in provider.scopes() {
+      request = request.add_scope(scope.clone());
+    }
+
+    let (authorization_url, state, nonce) = request.url();
+
+    let now = Utc::now().naive_utc();
+    let expires_at = now + Duration::minutes(STATE_MINUTES);
+
+    let stmt = "INSERT INTO oidc_states
+      (state_hash, provider, pkce_verifier, nonce, user_id, created_at, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .execute(
+        &stmt,
+        &[
+          &hash_token(state.secret()),
+          &provider.name,
+          pkce_verifier.secret(),
+          nonce.secret(),
+          &self.link_user_id,
+          &now,
+          &expires_at,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    Ok(AuthorizationRequest {
+      authorization_url: authorization_url.to_string(),
+      expires_at,
+    })
+  }
+}
+
+impl OidcCallbackDetails {
+  pub fn add_details<'a>(
+    self,
+    db_client: &'a Client,
+    providers: &'a OidcProviders,
+    passwords: &'a Passwords,
+    provider: &'a str,
+  ) -> OidcCallbackDetailsWithDBClient<'a> {
+    OidcCallbackDetailsWithDBClient {
+      code: self.code,
+      state: self.state,
+      db_client,
+      providers,
+      passwords,
+      provider,
+    }
+  }
+}
+
+impl<'a> OidcCallbackDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<CallbackOutcome, (StatusCode, Value)> {
+    let provider = get_provider(self.providers, self.provider)?;
+
+    let code = self.code.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "code", "message": "Authorization code is required"}),
+    ))?;
+
+    let state = self.state.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "state", "message": "State is required"}),
+    ))?;
+
+    let (pkce_verifier, nonce, link_user_id) = self.use_state(provider, state).await?;
+
+    let identity = self
+      .fetch_identity(provider, code, pkce_verifier, nonce)
+      .await?;
+
+    match link_user_id {
+      Some(user_id) => self
+        .link_identity(provider, &identity, user_id)
+        .await
+        .map(|_| CallbackOutcome::Linked),
+
+      None => match self.sign_in_identity(provider, &identity).await? {
+        Some(user_details) => Ok(CallbackOutcome::SignedIn(user_details)),
+        None => self
+          .create_user(provider, &identity)
+          .await
+          .map(CallbackOutcome::SignedIn),
+      },
+    }
+  }
+
+  async fn use_state(
+    &self,
+    provider: &OidcProvider,
+    state: &str,
+  ) -> Result<(PkceCodeVerifier, Nonce, Option<i32>), (StatusCode, Value)> {
+    let stmt = "UPDATE oidc_states SET used_at = $3
+      WHERE state_hash = $1 AND provider = $2 AND used_at IS NULL AND expires_at > $3
+      RETURNING pkce_verifier, nonce, user_id";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(
+        &stmt,
+        &[&hash_token(state), &provider.name, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let row = rows.first().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "state", "message": "Invalid or expired sign in attempt. Try again"}),
+    ))?;
+
+    let pkce_verifier = row.try_get::<&str, String>("pkce_verifier");
+    let nonce = row.try_get::<&str, String>("nonce");
+    let user_id = row.try_get::<&str, Option<i32>>("user_id");
+
+    match (pkce_verifier, nonce, user_id) {
+      (Ok(pkce_verifier), Ok(nonce), Ok(user_id)) => Ok((
+        PkceCodeVerifier::new(pkce_verifier),
+        Nonce::new(nonce),
+        user_id,
+      )),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+
+  async fn fetch_identity(
+    &self,
+    provider: &OidcProvider,
+    code: &str,
+    pkce_verifier: PkceCodeVerifier,
+    nonce: Nonce,
+  ) -> Result<ExternalIdentity, (StatusCode, Value)> {
+    let http_client = self.providers.http_client();
+
+    let client = provider
+      .client(http_client)
+      .await
+      .map_err(provider_unreachable_error)?;
+
+    let token_response = client
+      .exchange_code(AuthorizationCode::new(code.to_owned()))
+      .map_err(|e| verification_error(e.to_string()))?
+      .set_pkce_verifier(pkce_verifier)
+      .request_async(http_client)
+      .await
+      .map_err(|e| verification_error(e.to_string()))?;
+
+    let id_token = token_response
+      .id_token()
+      .ok_or_else(|| verification_error("Provider did not return an ID token"))?;
+
+    let id_token_verifier = client.id_token_verifier();
+
+    let claims = id_token
+      .claims(&id_token_verifier, &nonce)
+      .map_err(|e| verification_error(e.to_string()))?;
+
+    if let Some(expected_hash) = claims.access_token_hash() {
+      let actual_hash = AccessTokenHash::from_token(
+        token_response.access_token(),
+        id_token
+          .signing_alg()
+          .map_err(|e| verification_error(e.to_string()))?,
+        id_token
+          .signing_key(&id_token_verifier)
+          .map_err(|e| verification_error(e.to_string()))?,
+      )
+      .map_err(|e| verification_error(e.to_string()))?;
+
+      if actual_hash != *expected_hash {
+        return Err(verification_error("Access token hash does not match"));
+      }
+    }
+
+    Ok(ExternalIdentity {
+      subject: claims.subject().to_string(),
+      email: claims.email().map(|e| e.to_string()),
+      email_verified: claims.email_verified().unwrap_or(false),
+      preferred_username: claims.preferred_username().map(|u| u.to_string()),
+    })
+  }
+
+  async fn sign_in_identity(
+    &self,
+    provider: &OidcProvider,
+    identity: &ExternalIdentity,
+  ) -> Result<Option<UserAuthDetails>, (StatusCode, Value)> {
+    let stmt = "UPDATE user_identities i SET last_used_at = $3, email = $4 FROM users u
+      WHERE i.provider = $1 AND i.subject = $2 AND u.id = i.user_id
+      RETURNING u.id, u.username";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(
+        &stmt,
+        &[
+          &provider.name,
+          &identity.subject,
+          &Utc::now().naive_utc(),
+          &identity.email,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let Some(row) = rows.first() else {
+      return Ok(None);
+    };
+
+    let id = row.try_get::<&str, i32>("id");
+    let username = row.try_get::<&str, String>("username");
+
+    match (id, username) {
+      (Ok(id), Ok(username)) => Ok(Some(UserAuthDetails {
+        id,
+        username,
+        expires_at: Utc::now().naive_utc() + Duration::minutes(ACCESS_TOKEN_MINUTES),
+        session_id: None,
+        scopes: None,
+      })),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+
+  async fn link_identity(
+    &self,
+    provider: &OidcProvider,
+    identity: &ExternalIdentity,
+    user_id: i32,
+  ) -> Result<(), (StatusCode, Value)> {
+    let stmt = "INSERT INTO user_identities
+      (user_id, provider, subject, email, created_at, last_used_at)
+      VALUES ($1, $2, $3, $4, $5, $5)
+      ON CONFLICT (provider, subject) DO UPDATE SET email = $4, last_used_at = $5
+      WHERE user_identities.user_id = $1
+      RETURNING id";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(
+        &stmt,
+        &[
+          &user_id,
+          &provider.name,
+          &identity.subject,
+          &identity.email,
+          &Utc::now().naive_utc(),
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if rows.is_empty() {
+      return Err((
+        StatusCode::CONFLICT,
+        json!({"message": format!("This {} account is linked to another user", provider.display_name)}),
+      ));
+    }
+
+    Ok(())
+  }
+
+  async fn create_user(
+    &self,
+    provider: &OidcProvider,
+    identity: &ExternalIdentity,
+  ) -> Result<UserAuthDetails, (StatusCode, Value)> {
+    let password_hash = hash_password(self.passwords, &generate_token())
+      .await
+      .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    let email = match (&identity.email, identity.email_verified) {
+      (Some(email), true) => Some(email.as_str()),
+      _ => None,
+    };
+
+    let stmt = "WITH u AS (INSERT INTO users
+        (username, password_hash, created_at, email, email_verified_at)
+        SELECT $1, $2, $3::TIMESTAMP, e, CASE WHEN e IS NULL THEN NULL ELSE $3::TIMESTAMP END
+        FROM (SELECT CASE WHEN EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($4))
+          THEN NULL ELSE $4::VARCHAR END AS e) e
+        ON CONFLICT DO NOTHING
+        RETURNING id)
+      INSERT INTO user_identities (user_id, provider, subject, email, created_at, last_used_at)
+      SELECT id, $5, $6, $7, $3, $3 FROM u
+      RETURNING user_id";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let base = get_base_username(identity);
+
+    for attempt in 0..5 {
+      let username = match attempt {
+        0 => base.clone(),
+        _ => format!("{base}_{}", rand::thread_rng().gen_range(1000..10000)),
+      };
+
+      let now = Utc::now().naive_utc();
+
+      let rows = self
+        .db_client
+        .query(
+          &stmt,
+          &[
+            &username,
+            &password_hash,
+            &now,
+            &email,
+            &provider.name,
+            &identity.subject,
+            &identity.email,
+          ],
+        )
+        .await
+        .map_err(|e| {
+          (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({"message": e.to_string()}),
+          )
+        })?;
+
+      if let Some(row) = rows.first() {
+        let id = row.try_get::<&str, i32>("user_id").map_err(|_| {
+          (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({"message":"Error converting postgres types" }),
+          )
+        })?;
+
+        return Ok(UserAuthDetails {
+          id,
+          username,
+          expires_at: now + Duration::minutes(ACCESS_TOKEN_MINUTES),
+          session_id: None,
+          scopes: None,
+        });
+      }
+    }
+
+    Err((
+      StatusCode::CONFLICT,
+      json!({"message": "Could not find a free username for this account"}),
+    ))
+  }
+}
+
+fn get_provider<'a>(
+  providers: &'a OidcProviders,
+  name: &str,
+) -> Result<&'a OidcProvider, (StatusCode, Value)> {
+  providers.get(name).ok_or((
+    StatusCode::NOT_FOUND,
+    json!({"message": format!("No sign in provider named {name}")}),
+  ))
+}
+
+fn get_base_username(identity: &ExternalIdentity) -> String {
+  let candidate = identity
+    .preferred_username
+    .as_deref()
+    .or(identity.email.as_deref().and_then(|e| e.split('@').next()))
+    .unwrap_or_default();
+
+  let username: String = candidate
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    .take(40)
+    .collect();
+
+  if username.is_empty() {
+    return "user".to_owned();
+  }
+
+  username
+}
+
+fn provider_unreachable_error(e: String) -> (StatusCode, Value) {
+  eprintln!("{e}");
+
+  (
+    StatusCode::BAD_GATEWAY,
+    json!({"message": "Could not reach sign in provider"}),
+  )
+}
+
+fn verification_error(e: impl ToString) -> (StatusCode, Value) {
+  eprintln!("OIDC verification failed: {}", e.to_string());
+
+  (
+    StatusCode::UNAUTHORIZED,
+    json!({"name": "re-auth", "message": "Could not verify sign in with provider"}),
+  )
+}
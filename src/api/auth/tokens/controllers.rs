@@ -0,0 +1,186 @@
+use actix_web::{
+  web::{Data, Json, Path},
+  HttpResponse,
+};
+use deadpool_postgres::Pool;
+use serde_json::json;
+
+use crate::api::UserAuth;
+
+use super::models;
+
+pub async fn fetch_tokens(user_details: UserAuth, db_pool: Data<Pool>) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::FetchTokens {
+    db_client: &db_client,
+    user_details,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn create_token(
+  user_details: UserAuth,
+  body: Json<models::CreateTokenDetails>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn revoke_token(
+  user_details: UserAuth,
+  id: Path<i32>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::RevokeToken {
+    db_client: &db_client,
+    user_details,
+    id: id.into_inner(),
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
@@ -0,0 +1,9 @@
+use actix_web::web::{self, ServiceConfig};
+mod controllers;
+mod models;
+
+pub fn view(cfg: &mut ServiceConfig) {
+  cfg.route("", web::get().to(controllers::fetch_tokens));
+  cfg.route("", web::post().to(controllers::create_token));
+  cfg.route("/{id}", web::delete().to(controllers::revoke_token));
+}
@@ -0,0 +1,274 @@
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDateTime, Utc};
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::Row;
+
+use crate::api::UserAuthDetails;
+
+use super::super::models::{generate_token, hash_token, TokenScope, PERSONAL_ACCESS_TOKEN_PREFIX};
+
+#[derive(Serialize)]
+pub struct PersonalAccessToken {
+  id: i32,
+  name: String,
+  scopes: Vec<TokenScope>,
+  created_at: NaiveDateTime,
+  expires_at: Option<NaiveDateTime>,
+  last_used_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct CreatedToken {
+  #[serde(flatten)]
+  details: PersonalAccessToken,
+  token: String,
+}
+
+pub struct FetchTokens<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateTokenDetails {
+  name: Option<String>,
+  scopes: Option<Vec<String>>,
+  expires_in_days: Option<i64>,
+}
+
+pub struct CreateTokenDetailsWithDBClient<'a> {
+  name: Option<String>,
+  scopes: Option<Vec<String>>,
+  expires_in_days: Option<i64>,
+  db_client: &'a Client,
+  user_details: UserAuthDetails,
+}
+
+pub struct RevokeToken<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+  pub id: i32,
+}
+
+impl<'a> FetchTokens<'a> {
+  pub async fn exec(&self) -> Result<Vec<PersonalAccessToken>, (StatusCode, Value)> {
+    let stmt = "SELECT id, name, scopes, created_at, expires_at, last_used_at
+      FROM personal_access_tokens
+      WHERE user_id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > $2)
+      ORDER BY created_at DESC";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .query(&stmt, &[&self.user_details.id, &Utc::now().naive_utc()])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .iter()
+      .map(PersonalAccessToken::from_row)
+      .collect()
+  }
+}
+
+impl CreateTokenDetails {
+  pub fn add_details(
+    self,
+    db_client: &Client,
+    user_details: UserAuthDetails,
+  ) -> CreateTokenDetailsWithDBClient<'_> {
+    CreateTokenDetailsWithDBClient {
+      name: self.name,
+      scopes: self.scopes,
+      expires_in_days: self.expires_in_days,
+      db_client,
+      user_details,
+    }
+  }
+}
+
+impl<'a> CreateTokenDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<CreatedToken, (StatusCode, Value)> {
+    let (name, scopes) = self.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let token = format!("{PERSONAL_ACCESS_TOKEN_PREFIX}{}", generate_token());
+    let now = Utc::now().naive_utc();
+    let expires_at = self.expires_in_days.map(|d| now + Duration::days(d));
+
+    let stmt = "INSERT INTO personal_access_tokens
+      (user_id, name, token_hash, scopes, created_at, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING id, name, scopes, created_at, expires_at, last_used_at";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(
+        &stmt,
+        &[
+          &self.user_details.id,
+          &name,
+          &hash_token(&token),
+          &scopes,
+          &now,
+          &expires_at,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let row = rows.first().ok_or((
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": "Error creating token"}),
+    ))?;
+
+    Ok(CreatedToken {
+      details: PersonalAccessToken::from_row(row)?,
+      token,
+    })
+  }
+
+  fn validate(&self) -> Result<(String, Vec<TokenScope>), Value> {
+    let name = self.name.as_deref().map(str::trim).unwrap_or_default();
+
+    if name.is_empty() {
+      return Err(json!({
+        "name": "name",
+        "message": "Token name is required"
+      }));
+    }
+
+    if name.chars().count() > 100 {
+      return Err(json!({
+        "name": "name",
+        "message": "Token name should not be more than 100 characters"
+      }));
+    }
+
+    let mut scopes = Vec::new();
+
+    for scope in self.scopes.iter().flatten() {
+      let scope = match scope.trim() {
+        "read" => TokenScope::Read,
+        "post" => TokenScope::Post,
+        "comment" => TokenScope::Comment,
+        "moderate" => TokenScope::Moderate,
+        s => {
+          return Err(json!({
+            "name": "scopes",
+            "message": format!("Unknown scope {s}. Use read, post, comment or moderate")
+          }))
+        }
+      };
+
+      if !scopes.contains(&scope) {
+        scopes.push(scope);
+      }
+    }
+
+    if scopes.is_empty() {
+      return Err(json!({
+        "name": "scopes",
+        "message": "At least one scope is required"
+      }));
+    }
+
+    if let Some(days) = self.expires_in_days {
+      if !(1..=365).contains(&days) {
+        return Err(json!({
+          "name": "expires_in_days",
+          "message": "Tokens should expire between 1 and 365 days"
+        }));
+      }
+    }
+
+    Ok((name.to_owned(), scopes))
+  }
+}
+
+impl<'a> RevokeToken<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let stmt = "UPDATE personal_access_tokens SET revoked_at = $3
+      WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let revoked = self
+      .db_client
+      .execute(
+        &stmt,
+        &[&self.id, &self.user_details.id, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if revoked == 0 {
+      return Err((
+        StatusCode::NOT_FOUND,
+        json!({"message": "No active token found with such id"}),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+impl PersonalAccessToken {
+  fn from_row(row: &Row) -> Result<PersonalAccessToken, (StatusCode, Value)> {
+    let id = row.try_get::<&str, i32>("id");
+    let name = row.try_get::<&str, String>("name");
+    let scopes = row.try_get::<&str, Vec<TokenScope>>("scopes");
+    let created_at = row.try_get::<&str, NaiveDateTime>("created_at");
+    let expires_at = row.try_get::<&str, Option<NaiveDateTime>>("expires_at");
+    let last_used_at = row.try_get::<&str, Option<NaiveDateTime>>("last_used_at");
+
+    match (id, name, scopes, created_at, expires_at, last_used_at) {
+      (Ok(id), Ok(name), Ok(scopes), Ok(created_at), Ok(expires_at), Ok(last_used_at)) => {
+        Ok(PersonalAccessToken {
+          id,
+          name,
+          scopes,
+          created_at,
+          expires_at,
+          last_used_at,
+        })
+      }
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
@@ -0,0 +1,302 @@
+use actix_web::{
+  web::{Data, Json},
+  HttpResponse,
+};
+use deadpool_postgres::Pool;
+use serde_json::json;
+
+use crate::{api::UserAuth, password::Passwords};
+
+use super::models;
+
+pub async fn fetch_status(user_details: UserAuth, db_pool: Data<Pool>) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::FetchTwoFactorStatus {
+    db_client: &db_client,
+    user_details,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn enroll(user_details: UserAuth, db_pool: Data<Pool>) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = models::EnrollTwoFactor {
+    db_client: &db_client,
+    user_details,
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn confirm(
+  user_details: UserAuth,
+  body: Json<models::ConfirmTwoFactorDetails>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(recovery_codes) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": { "recovery_codes": recovery_codes }
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn regenerate_recovery_codes(
+  user_details: UserAuth,
+  body: Json<models::RegenerateRecoveryCodesDetails>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(recovery_codes) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": { "recovery_codes": recovery_codes }
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn disable(
+  user_details: UserAuth,
+  body: Json<models::DisableTwoFactorDetails>,
+  db_pool: Data<Pool>,
+  passwords: Data<Passwords>,
+) -> HttpResponse {
+  let user_details = match user_details.require_session() {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_details(&db_client, &passwords, user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
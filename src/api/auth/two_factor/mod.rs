@@ -0,0 +1,14 @@
+use actix_web::web::{self, ServiceConfig};
+mod controllers;
+pub mod models;
+
+pub fn view(cfg: &mut ServiceConfig) {
+  cfg.route("", web::get().to(controllers::fetch_status));
+  cfg.route("", web::delete().to(controllers::disable));
+  cfg.route("/enroll", web::post().to(controllers::enroll));
+  cfg.route("/confirm", web::post().to(controllers::confirm));
+  cfg.route(
+    "/recovery-codes",
+    web::post().to(controllers::regenerate_recovery_codes),
+  );
+}
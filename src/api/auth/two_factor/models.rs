@@ -0,0 +1,886 @@
+use std::env;
+
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDateTime, Utc};
+use deadpool_postgres::Client;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::{api::UserAuthDetails, password::Passwords};
+
+use super::super::models::{generate_token, hash_token, ACCESS_TOKEN_MINUTES};
+
+const TOTP_STEP: u64 = 30;
+const CHALLENGE_MINUTES: i64 = 5;
+const CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+const RECOVERY_CODES: usize = 10;
+const RECOVERY_CODE_CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Serialize)]
+pub struct TwoFactorStatus {
+  enabled: bool,
+  recovery_codes_left: i64,
+}
+
+#[derive(Serialize)]
+pub struct Enrolment {
+  secret: String,
+  otpauth_uri: String,
+}
+
+#[derive(Serialize)]
+pub struct Challenge {
+  challenge_token: String,
+  expires_at: NaiveDateTime,
+}
+
+pub struct FetchTwoFactorStatus<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+}
+
+pub struct EnrollTwoFactor<'a> {
+  pub db_client: &'a Client,
+  pub user_details: UserAuthDetails,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConfirmTwoFactorDetails {
+  code: Option<String>,
+}
+
+pub struct ConfirmTwoFactorDetailsWithDBClient<'a> {
+  code: Option<String>,
+  db_client: &'a Client,
+  user_details: UserAuthDetails,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DisableTwoFactorDetails {
+  password: Option<String>,
+  code: Option<String>,
+  recovery_code: Option<String>,
+}
+
+pub struct DisableTwoFactorDetailsWithDBClient<'a> {
+  password: Option<String>,
+  code: Option<String>,
+  recovery_code: Option<String>,
+  db_client: &'a Client,
+  passwords: &'a Passwords,
+  user_details: UserAuthDetails,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RegenerateRecoveryCodesDetails {
+  code: Option<String>,
+}
+
+pub struct RegenerateRecoveryCodesDetailsWithDBClient<'a> {
+  code: Option<String>,
+  db_client: &'a Client,
+  user_details: UserAuthDetails,
+}
+
+pub struct LoginChallenge<'a> {
+  pub db_client: &'a Client,
+  pub user_details: &'a UserAuthDetails,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TwoFactorLoginDetails {
+  challenge_token: Option<String>,
+  code: Option<String>,
+  recovery_code: Option<String>,
+}
+
+pub struct TwoFactorLoginDetailsWithDBClient<'a> {
+  challenge_token: Option<String>,
+  code: Option<String>,
+  recovery_code: Option<String>,
+  db_client: &'a Client,
+}
+
+struct ConfirmedTotp {
+  secret: String,
+  last_used_step: Option<i64>,
+}
+
+impl<'a> FetchTwoFactorStatus<'a> {
+  pub async fn exec(&self) -> Result<TwoFactorStatus, (StatusCode, Value)> {
+    let stmt = "SELECT
+      EXISTS (SELECT 1 FROM user_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL) AS enabled,
+      (SELECT COUNT(*) FROM totp_recovery_codes WHERE user_id = $1 AND used_at IS NULL) AS recovery_codes_left";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(&stmt, &[&self.user_details.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let row = rows.first().ok_or((
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": "Error fetching two-factor status"}),
+    ))?;
+
+    let enabled = row.try_get::<&str, bool>("enabled");
+    let recovery_codes_left = row.try_get::<&str, i64>("recovery_codes_left");
+
+    match (enabled, recovery_codes_left) {
+      (Ok(enabled), Ok(recovery_codes_left)) => Ok(TwoFactorStatus {
+        enabled,
+        recovery_codes_left: if enabled { recovery_codes_left } else { 0 },
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+impl<'a> EnrollTwoFactor<'a> {
+  pub async fn exec(&self) -> Result<Enrolment, (StatusCode, Value)> {
+    let secret = Secret::Raw(rand::thread_rng().gen::<[u8; 20]>().to_vec()).to_encoded();
+
+    let Secret::Encoded(secret) = secret else {
+      return Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": "Error generating two-factor secret"}),
+      ));
+    };
+
+    let stmt = "INSERT INTO user_totp (user_id, secret, created_at) VALUES ($1, $2, $3)
+      ON CONFLICT (user_id) DO UPDATE SET secret = $2, created_at = $3, last_used_step = NULL
+      WHERE user_totp.confirmed_at IS NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let inserted = self
+      .db_client
+      .execute(
+        &stmt,
+        &[&self.user_details.id, &secret, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if inserted == 0 {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"message": "Two-factor authentication is already enabled"}),
+      ));
+    }
+
+    let otpauth_uri = get_totp(&secret, &self.user_details.username)?.get_url();
+
+    Ok(Enrolment {
+      secret,
+      otpauth_uri,
+    })
+  }
+}
+
+impl ConfirmTwoFactorDetails {
+  pub fn add_details(
+    self,
+    db_client: &Client,
+    user_details: UserAuthDetails,
+  ) -> ConfirmTwoFactorDetailsWithDBClient<'_> {
+    ConfirmTwoFactorDetailsWithDBClient {
+      code: self.code,
+      db_client,
+      user_details,
+    }
+  }
+}
+
+impl<'a> ConfirmTwoFactorDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<Vec<String>, (StatusCode, Value)> {
+    let code = get_code(&self.code)?;
+
+    let stmt =
+      "SELECT secret, confirmed_at IS NOT NULL AS confirmed FROM user_totp WHERE user_id = $1";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(&stmt, &[&self.user_details.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let row = rows.first().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"message": "Two-factor enrolment has not been started"}),
+    ))?;
+
+    let secret = row.try_get::<&str, String>("secret");
+    let confirmed = row.try_get::<&str, bool>("confirmed");
+
+    let (secret, confirmed) = match (secret, confirmed) {
+      (Ok(secret), Ok(confirmed)) => (secret, confirmed),
+      _ => {
+        return Err((
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message":"Error converting postgres types" }),
+        ))
+      }
+    };
+
+    if confirmed {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"message": "Two-factor authentication is already enabled"}),
+      ));
+    }
+
+    let step = verify_totp(&get_totp(&secret, &self.user_details.username)?, code, None)
+      .ok_or(invalid_code_error())?;
+
+    let recovery_codes = generate_recovery_codes();
+
+    let stmt = "WITH t AS (UPDATE user_totp SET confirmed_at = $2, last_used_step = $3
+        WHERE user_id = $1 AND secret = $4 AND confirmed_at IS NULL RETURNING user_id),
+      d AS (DELETE FROM totp_recovery_codes WHERE user_id IN (SELECT user_id FROM t))
+      INSERT INTO totp_recovery_codes (user_id, code_hash, created_at)
+      SELECT t.user_id, c, $2 FROM t, UNNEST($5::VARCHAR[]) c
+      RETURNING id";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let inserted = self
+      .db_client
+      .query(
+        &stmt,
+        &[
+          &self.user_details.id,
+          &Utc::now().naive_utc(),
+          &step,
+          &secret,
+          &hash_recovery_codes(&recovery_codes),
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if inserted.is_empty() {
+      return Err((
+        StatusCode::CONFLICT,
+        json!({"message": "Two-factor enrolment changed. Try again"}),
+      ));
+    }
+
+    Ok(recovery_codes)
+  }
+}
+
+impl DisableTwoFactorDetails {
+  pub fn add_details<'a>(
+    self,
+    db_client: &'a Client,
+    passwords: &'a Passwords,
+    user_details: UserAuthDetails,
+  ) -> DisableTwoFactorDetailsWithDBClient<'a> {
+    DisableTwoFactorDetailsWithDBClient {
+      password: self.password,
+      code: self.code,
+      recovery_code: self.recovery_code,
+      db_client,
+      passwords,
+      user_details,
+    }
+  }
+}
+
+impl<'a> DisableTwoFactorDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let password = self.password.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "password", "message": "Password is required"}),
+    ))?;
+
+    let stmt = "SELECT password_hash FROM users WHERE id = $1";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(&stmt, &[&self.user_details.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let password_hash = rows
+      .first()
+      .and_then(|r| r.try_get::<&str, String>("password_hash").ok())
+      .ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": "Error fetching user"}),
+      ))?;
+
+    let correct_password = self
+      .passwords
+      .verify(password, &password_hash)
+      .await
+      .map_err(|_| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": "Error verifying password"}),
+        )
+      })?;
+
+    if !correct_password {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "password", "message": "Wrong password"}),
+      ));
+    }
+
+    let totp = get_confirmed_totp(self.db_client, self.user_details.id)
+      .await?
+      .ok_or(not_enabled_error())?;
+
+    verify_second_factor(
+      self.db_client,
+      &self.user_details,
+      &totp,
+      &self.code,
+      &self.recovery_code,
+    )
+    .await?;
+
+    let stmt = "WITH d AS (DELETE FROM totp_recovery_codes WHERE user_id = $1)
+      DELETE FROM user_totp WHERE user_id = $1";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .execute(&stmt, &[&self.user_details.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    Ok(())
+  }
+}
+
+impl RegenerateRecoveryCodesDetails {
+  pub fn add_details(
+    self,
+    db_client: &Client,
+    user_details: UserAuthDetails,
+  ) -> RegenerateRecoveryCodesDetailsWithDBClient<'_> {
+    RegenerateRecoveryCodesDetailsWithDBClient {
+      code: self.code,
+      db_client,
+      user_details,
+    }
+  }
+}
+
+impl<'a> RegenerateRecoveryCodesDetailsWithDBClient<'a> {
+  pub async fn exec(&self) -> Result<Vec<String>, (StatusCode, Value)> {
+    get_code(&self.code)?;
+
+    let totp = get_confirmed_totp(self.db_client, self.user_details.id)
+      .await?
+      .ok_or(not_enabled_error())?;
+
+    verify_second_factor(self.db_client, &self.user_details, &totp, &self.code, &None).await?;
+
+    let recovery_codes = generate_recovery_codes();
+
+    let stmt = "WITH d AS (DELETE FROM totp_recovery_codes WHERE user_id = $1)
+      INSERT INTO totp_recovery_codes (user_id, code_hash, created_at)
+      SELECT $1, c, $2 FROM UNNEST($3::VARCHAR[]) c";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .execute(
+        &stmt,
+        &[
+          &self.user_details.id,
+          &Utc::now().naive_utc(),
+          &hash_recovery_codes(&recovery_codes),
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    Ok(recovery_codes)
+  }
+}
+
+impl<'a> LoginChallenge<'a> {
+  pub async fn create(&self) -> Result<Option<Challenge>, (StatusCode, Value)> {
+    let challenge_token = generate_token();
+    let now = Utc::now().naive_utc();
+    let expires_at = now + Duration::minutes(CHALLENGE_MINUTES);
+
+    let stmt = "INSERT INTO login_challenges (user_id, token_hash, created_at, expires_at)
+      SELECT user_id, $2, $3, $4 FROM user_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL
+      RETURNING id";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(
+        &stmt,
+        &[
+          &self.user_details.id,
+          &hash_token(&challenge_token),
+          &now,
+          &expires_at,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if rows.is_empty() {
+      return Ok(None);
+    }
+
+    Ok(Some(Challenge {
+      challenge_token,
+      expires_at,
+    }))
+  }
+}
+
+impl TwoFactorLoginDetails {
+  pub fn add_db_client(self, db_client: &Client) -> TwoFactorLoginDetailsWithDBClient<'_> {
+    TwoFactorLoginDetailsWithDBClient {
+      challenge_token: self.challenge_token,
+      code: self.code,
+      recovery_code: self.recovery_code,
+      db_client,
+    }
+  }
+}
+
+impl<'a> TwoFactorLoginDetailsWithDBClient<'a> {
+  pub async fn username(&self) -> Result<Option<String>, (StatusCode, Value)> {
+    let Some(challenge_token) = self.challenge_token.as_deref() else {
+      return Ok(None);
+    };
+
+    let stmt = "SELECT u.username FROM login_challenges c INNER JOIN users u ON u.id = c.user_id
+      WHERE c.token_hash = $1";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(&stmt, &[&hash_token(challenge_token)])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    Ok(rows.first().and_then(|row| row.try_get("username").ok()))
+  }
+
+  pub async fn exec(&self) -> Result<UserAuthDetails, (StatusCode, Value)> {
+    let challenge_token = self.challenge_token.as_deref().ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "challenge_token", "message": "Challenge token is required"}),
+    ))?;
+
+    let now = Utc::now().naive_utc();
+
+    let stmt = "UPDATE login_challenges c SET attempts = c.attempts + 1 FROM users u
+      WHERE c.token_hash = $1 AND c.used_at IS NULL AND c.expires_at > $2 AND c.attempts < $3
+      AND u.id = c.user_id
+      RETURNING c.id, u.id AS user_id, u.username";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let rows = self
+      .db_client
+      .query(
+        &stmt,
+        &[&hash_token(challenge_token), &now, &CHALLENGE_MAX_ATTEMPTS],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    let row = rows.first().ok_or((
+      StatusCode::UNAUTHORIZED,
+      json!({"name": "re-auth", "message": "Invalid or expired challenge. Sign in again"}),
+    ))?;
+
+    let id = row.try_get::<&str, i32>("id");
+    let user_id = row.try_get::<&str, i32>("user_id");
+    let username = row.try_get::<&str, String>("username");
+
+    let (id, user_details) = match (id, user_id, username) {
+      (Ok(id), Ok(user_id), Ok(username)) => (
+        id,
+        UserAuthDetails {
+          id: user_id,
+          username,
+          expires_at: now + Duration::minutes(ACCESS_TOKEN_MINUTES),
+          session_id: None,
+          scopes: None,
+        },
+      ),
+      _ => {
+        return Err((
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message":"Error converting postgres types" }),
+        ))
+      }
+    };
+
+    let totp = get_confirmed_totp(self.db_client, user_details.id)
+      .await?
+      .ok_or((
+        StatusCode::UNAUTHORIZED,
+        json!({"name": "re-auth", "message": "Invalid or expired challenge. Sign in again"}),
+      ))?;
+
+    verify_second_factor(
+      self.db_client,
+      &user_details,
+      &totp,
+      &self.code,
+      &self.recovery_code,
+    )
+    .await?;
+
+    let stmt = "UPDATE login_challenges SET used_at = $2 WHERE id = $1 AND used_at IS NULL";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    let used = self
+      .db_client
+      .execute(&stmt, &[&id, &now])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if used == 0 {
+      return Err((
+        StatusCode::UNAUTHORIZED,
+        json!({"name": "re-auth", "message": "Invalid or expired challenge. Sign in again"}),
+      ));
+    }
+
+    Ok(user_details)
+  }
+}
+
+async fn get_confirmed_totp(
+  db_client: &Client,
+  user_id: i32,
+) -> Result<Option<ConfirmedTotp>, (StatusCode, Value)> {
+  let stmt = "SELECT secret, last_used_step FROM user_totp
+    WHERE user_id = $1 AND confirmed_at IS NOT NULL";
+
+  let stmt = db_client.prepare(stmt).await.map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
+
+  let rows = db_client.query(&stmt, &[&user_id]).await.map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
+
+  let Some(row) = rows.first() else {
+    return Ok(None);
+  };
+
+  let secret = row.try_get::<&str, String>("secret");
+  let last_used_step = row.try_get::<&str, Option<i64>>("last_used_step");
+
+  match (secret, last_used_step) {
+    (Ok(secret), Ok(last_used_step)) => Ok(Some(ConfirmedTotp {
+      secret,
+      last_used_step,
+    })),
+    _ => Err((
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message":"Error converting postgres types" }),
+    )),
+  }
+}
+
+async fn verify_second_factor(
+  db_client: &Client,
+  user_details: &UserAuthDetails,
+  totp: &ConfirmedTotp,
+  code: &Option<String>,
+  recovery_code: &Option<String>,
+) -> Result<(), (StatusCode, Value)> {
+  let updated = match (code.as_deref(), recovery_code.as_deref()) {
+    (Some(code), _) => {
+      let step = verify_totp(
+        &get_totp(&totp.secret, &user_details.username)?,
+        code.trim(),
+        totp.last_used_step,
+      )
+      .ok_or(invalid_code_error())?;
+
+      let stmt = "UPDATE user_totp SET last_used_step = $2
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)";
+
+      let stmt = db_client.prepare(stmt).await.map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+      db_client.execute(&stmt, &[&user_details.id, &step]).await
+    }
+
+    (None, Some(recovery_code)) => {
+      let stmt = "UPDATE totp_recovery_codes SET used_at = $3
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL";
+
+      let stmt = db_client.prepare(stmt).await.map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+      db_client
+        .execute(
+          &stmt,
+          &[
+            &user_details.id,
+            &hash_token(&normalize_recovery_code(recovery_code)),
+            &Utc::now().naive_utc(),
+          ],
+        )
+        .await
+    }
+
+    (None, None) => {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "code", "message": "Two-factor code is required"}),
+      ))
+    }
+  }
+  .map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
+
+  if updated == 0 {
+    return Err(invalid_code_error());
+  }
+
+  Ok(())
+}
+
+fn get_totp(secret: &str, username: &str) -> Result<TOTP, (StatusCode, Value)> {
+  let issuer = env::var("TOTP_ISSUER").unwrap_or("Forum".to_owned());
+
+  Secret::Encoded(secret.to_owned())
+    .to_bytes()
+    .map_err(|e| e.to_string())
+    .and_then(|secret| {
+      TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        TOTP_STEP,
+        secret,
+        Some(issuer),
+        username.to_owned(),
+      )
+      .map_err(|e| e.to_string())
+    })
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({ "message": format!("Error loading two-factor secret {e}") }),
+      )
+    })
+}
+
+fn verify_totp(totp: &TOTP, code: &str, last_used_step: Option<i64>) -> Option<i64> {
+  let current_step = Utc::now().timestamp() / TOTP_STEP as i64;
+
+  (current_step - 1..=current_step + 1)
+    .filter(|step| last_used_step.is_none_or(|last| *step > last))
+    .find(|step| totp.check(code, *step as u64 * TOTP_STEP))
+}
+
+fn get_code(code: &Option<String>) -> Result<&str, (StatusCode, Value)> {
+  code
+    .as_deref()
+    .map(str::trim)
+    .filter(|c| !c.is_empty())
+    .ok_or((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "code", "message": "Two-factor code is required"}),
+    ))
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+  let mut rng = rand::thread_rng();
+
+  (0..RECOVERY_CODES)
+    .map(|_| {
+      let code: String = (0..10)
+        .map(|_| RECOVERY_CODE_CHARS[rng.gen_range(0..RECOVERY_CODE_CHARS.len())] as char)
+        .collect();
+
+      format!("{}-{}", &code[..5], &code[5..])
+    })
+    .collect()
+}
+
+fn hash_recovery_codes(recovery_codes: &[String]) -> Vec<String> {
+  recovery_codes
+    .iter()
+    .map(|c| hash_token(&normalize_recovery_code(c)))
+    .collect()
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+  code
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric())
+    .map(|c| c.to_ascii_lowercase())
+    .collect()
+}
+
+fn invalid_code_error() -> (StatusCode, Value) {
+  (
+    StatusCode::BAD_REQUEST,
+    json!({"name": "code", "message": "Invalid two-factor code"}),
+  )
+}
+
+fn not_enabled_error() -> (StatusCode, Value) {
+  (
+    StatusCode::BAD_REQUEST,
+    json!({"message": "Two-factor authentication is not enabled"}),
+  )
+}
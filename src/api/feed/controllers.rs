@@ -0,0 +1,69 @@
+use actix_web::{
+  web::{self, Query},
+  HttpResponse,
+};
+use deadpool_postgres::{Client, Pool};
+use serde_json::json;
+
+use super::models;
+
+use crate::api::{
+  handler_utils::{NoDBClient, NoUserDetails, NotValidated},
+  UserAuth,
+};
+
+pub async fn fetch_feed(
+  db_pool: web::Data<Pool>,
+  user_details: UserAuth,
+  query: Query<models::FetchFeed<NoDBClient, NoUserDetails, NotValidated>>,
+) -> HttpResponse {
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let query = query
+    .into_inner()
+    .add_db_client(&db_client)
+    .add_user_details(&user_details)
+    .validate();
+
+  let res = match query {
+    Ok(q) => q.fetch_posts().await,
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(v) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": v.data,
+      "next_cursor": v.next_cursor,
+      "prev_cursor": v.prev_cursor
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
@@ -0,0 +1,132 @@
+use std::marker::PhantomData;
+
+use actix_web::http::StatusCode;
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::types::ToSql;
+
+use crate::api::{
+  handler_utils::{
+    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithUserDetails,
+  },
+  pagination::{fetch_page, Clause, Page, Pagination},
+  posts::{FetchPostsResponse, Sort},
+  UserAuthDetails,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchFeed<D, U, V> {
+  sort: Option<Sort>,
+  limit: Option<i64>,
+  page: Option<i64>,
+  cursor: Option<String>,
+  include_users: Option<bool>,
+  #[serde(skip)]
+  pagination: Pagination,
+  #[serde(skip_deserializing)]
+  db_client: D,
+  #[serde(skip_deserializing)]
+  user_details: U,
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl<D, U> FetchFeed<D, U, NotValidated> {
+  pub fn validate(self) -> Result<FetchFeed<D, U, Validated>, (StatusCode, Value)> {
+    if let Some(s) = self.limit {
+      if s > 50 {
+        return Err((
+          StatusCode::BAD_REQUEST,
+          json!({"message": "Cannot retrieve more than 50 posts"}),
+        ));
+      }
+    }
+
+    Ok(FetchFeed {
+      pagination: Pagination::new(self.limit, self.page, self.cursor.as_deref())?,
+      sort: self.sort,
+      limit: self.limit,
+      page: self.page,
+      cursor: self.cursor,
+      include_users: self.include_users,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<U, V> FetchFeed<NoDBClient, U, V> {
+  pub fn add_db_client(self, db_client: &Client) -> FetchFeed<WithDBClient<'_>, U, V> {
+    FetchFeed {
+      sort: self.sort,
+      limit: self.limit,
+      page: self.page,
+      cursor: self.cursor,
+      include_users: self.include_users,
+      pagination: self.pagination,
+      db_client: WithDBClient(db_client),
+      user_details: self.user_details,
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<D, V> FetchFeed<D, NoUserDetails, V> {
+  pub fn add_user_details(
+    self,
+    user_details: &UserAuthDetails,
+  ) -> FetchFeed<D, WithUserDetails<'_>, V> {
+    FetchFeed {
+      sort: self.sort,
+      limit: self.limit,
+      page: self.page,
+      cursor: self.cursor,
+      include_users: self.include_users,
+      pagination: self.pagination,
+      db_client: self.db_client,
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a> FetchFeed<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name,
+      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags,
+      (SELECT COUNT(*) FROM post_comments c WHERE c.post_id = p.id) comments,
+      (SELECT COUNT(*) FROM saved_posts ss WHERE ss.post_id = p.id) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score,
+      COALESCE((SELECT v.value FROM post_votes v WHERE v.post_id = p.id AND v.user_id = $1), 0::SMALLINT) my_vote FROM posts p
+      INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id
+      INNER JOIN hashtags t ON t.id = r.hashtag_id
+      INNER JOIN users u ON u.id = p.user_id
+      LEFT JOIN saved_posts s ON s.post_id = p.id AND s.user_id = $1
+      WHERE (p.id IN (SELECT fr.post_id FROM posts_hashtags_relationship fr
+        INNER JOIN hashtag_follows f ON f.hashtag_id = fr.hashtag_id WHERE f.user_id = $1)
+      OR ($2 AND p.user_id IN (SELECT uf.following_id FROM user_follows uf WHERE uf.follower_id = $1)))
+      GROUP BY p.id, u.id, s.post_id";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![
+      Box::new(self.user_details.0.id),
+      Box::new(self.include_users.unwrap_or(true)),
+    ];
+
+    let stmt = self
+      .sort
+      .as_ref()
+      .unwrap_or(&Sort::Latest)
+      .add_pagination_statement(stmt, Clause::Having, &self.pagination, &mut params);
+
+    fetch_page(
+      self.db_client.0,
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
+  }
+}
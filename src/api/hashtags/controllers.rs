@@ -1,13 +1,19 @@
 use actix_web::{
-  web::{self, Query},
+  web::{self, Path, Query},
   HttpResponse,
 };
 use deadpool_postgres::{Client, Pool};
 use serde_json::json;
 
-use crate::api::handler_utils::NoDBClient;
+use crate::api::{
+  handler_utils::{NoDBClient, NoUserDetails, NotValidated},
+  posts::{normalize_hashtag, FetchPosts},
+  UserAuth,
+};
 
-use super::models::FetchTrendingHashtags;
+use super::models::{
+  FetchHashtagDetails, FetchHashtagSuggestions, FetchTrendingHashtags, FindHashtag, FollowHashtag,
+};
 
 pub async fn get_trending_hashtags(
   db_pool: web::Data<Pool>,
@@ -39,3 +45,242 @@ pub async fn get_trending_hashtags(
     })),
   }
 }
+
+pub async fn get_hashtag(
+  db_pool: web::Data<Pool>,
+  body: Path<FetchHashtagDetails<NoDBClient>>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let res = body.into_inner().add_db_client(&db_client).fetch().await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn get_hashtag_posts(
+  db_pool: web::Data<Pool>,
+  name: Path<String>,
+  user_details: UserAuth,
+  query: Query<FetchPosts<NoDBClient, NoUserDetails, NotValidated>>,
+) -> HttpResponse {
+  let user_details = user_details.details;
+
+  let name = normalize_hashtag(&name.into_inner());
+
+  if name.is_empty() {
+    return HttpResponse::NotFound().json(json!({
+      "success": false,
+      "message": "No hashtag found with such name",
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let hashtag = FindHashtag {
+    db_client: &db_client,
+    name: &name,
+  }
+  .exec()
+  .await;
+
+  if let Err((s, v)) = hashtag {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let query = query
+    .into_inner()
+    .add_hashtag(name)
+    .add_db_client(&db_client)
+    .validate();
+
+  if let Err((s, v)) = query {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error":v
+    }));
+  }
+
+  let query = query.unwrap();
+
+  let res = if let Some(u) = user_details {
+    query.add_user_details(&u).fetch_posts().await
+  } else {
+    query.fetch_posts().await
+  };
+
+  match res {
+    Ok(v) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": v.data,
+      "next_cursor": v.next_cursor,
+      "prev_cursor": v.prev_cursor
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn get_hashtag_suggestions(
+  db_pool: web::Data<Pool>,
+  body: Query<FetchHashtagSuggestions<NoDBClient>>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let res = body.into_inner().add_db_client(&db_client).fetch().await;
+
+  match res {
+    Ok(data) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": data
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn follow_hashtag(
+  user_details: UserAuth,
+  name: Path<String>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let res = FollowHashtag {
+    user_details,
+    db_client: &db_client,
+    name: name.into_inner(),
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn unfollow_hashtag(
+  user_details: UserAuth,
+  name: Path<String>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let res = FollowHashtag {
+    user_details,
+    db_client: &db_client,
+    name: name.into_inner(),
+  }
+  .exec_reverse()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
@@ -8,4 +8,21 @@ pub fn view(cfg: &mut ServiceConfig) {
     "/trending",
     web::get().to(controllers::get_trending_hashtags),
   );
+  cfg.route(
+    "/suggest",
+    web::get().to(controllers::get_hashtag_suggestions),
+  );
+  cfg.route("/{name}", web::get().to(controllers::get_hashtag));
+  cfg.route(
+    "/{name}/posts",
+    web::get().to(controllers::get_hashtag_posts),
+  );
+  cfg.route(
+    "/{name}/follow",
+    web::post().to(controllers::follow_hashtag),
+  );
+  cfg.route(
+    "/{name}/unfollow",
+    web::post().to(controllers::unfollow_hashtag),
+  );
 }
@@ -1,30 +1,166 @@
 use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDateTime, Utc};
 use deadpool_postgres::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tokio_postgres::{Row, Statement};
 
-use crate::api::handler_utils::{NoDBClient, WithDBClient};
+use crate::api::{
+  handler_utils::{NoDBClient, WithDBClient},
+  posts::normalize_hashtag,
+  UserAuthDetails,
+};
 
 #[derive(Deserialize)]
 pub struct FetchTrendingHashtags<D> {
+  window: Option<i32>,
+  limit: Option<i64>,
   #[serde(skip_deserializing)]
   db_client: D,
 }
 
+#[derive(Serialize)]
+pub struct TrendingHashtag {
+  name: String,
+  color: String,
+  score: f64,
+  post_count: i64,
+}
+
 impl<'a> FetchTrendingHashtags<NoDBClient> {
   pub fn add_db_client(self, db_client: &'a Client) -> FetchTrendingHashtags<WithDBClient<'a>> {
     FetchTrendingHashtags {
+      window: self.window,
+      limit: self.limit,
       db_client: WithDBClient(db_client),
     }
   }
 }
 
 impl<'a> FetchTrendingHashtags<WithDBClient<'a>> {
-  pub async fn fetch(&self) -> Result<Value, (StatusCode, Value)> {
+  pub async fn fetch(&self) -> Result<Vec<TrendingHashtag>, (StatusCode, Value)> {
+    let window = self.window.unwrap_or(48);
+    let limit = self.limit.unwrap_or(7);
+
+    if !(1..=720).contains(&window) {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "window", "message": "Window should be between 1 and 720 hours"}),
+      ));
+    }
+
+    if !(1..=50).contains(&limit) {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "limit", "message": "Limit should be between 1 and 50"}),
+      ));
+    }
+
+    self
+      .get_db_client()
+      .query(
+        &self.get_select_statement().await?,
+        &[&Utc::now().naive_utc(), &window, &limit],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .iter()
+      .map(TrendingHashtag::from_row)
+      .collect()
+  }
+
+  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT h.name, h.color::TEXT color, COUNT(*) post_count,
+      SUM(POWER(0.5::FLOAT8, EXTRACT(EPOCH FROM ($1::TIMESTAMP - p.created_at))::FLOAT8 / 3600 / ($2::INT::FLOAT8 / 2))) score
+      FROM posts_hashtags_relationship r
+      INNER JOIN posts p ON p.id = r.post_id
+      INNER JOIN hashtags h ON h.id = r.hashtag_id
+      WHERE p.created_at > $1::TIMESTAMP - MAKE_INTERVAL(hours => $2::INT)
+      GROUP BY h.id ORDER BY score DESC, post_count DESC LIMIT $3";
+
+    self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+
+  fn get_db_client(&self) -> &'a Client {
+    self.db_client.0
+  }
+}
+
+impl TrendingHashtag {
+  pub fn from_row(row: &Row) -> Result<TrendingHashtag, (StatusCode, Value)> {
+    let name = row.try_get::<&str, String>("name");
+    let color = row.try_get::<&str, String>("color");
+    let score = row.try_get::<&str, f64>("score");
+    let post_count = row.try_get::<&str, i64>("post_count");
+
+    match (name, color, score, post_count) {
+      (Ok(name), Ok(color), Ok(score), Ok(post_count)) => Ok(TrendingHashtag {
+        name,
+        color,
+        score,
+        post_count,
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+#[derive(Deserialize)]
+pub struct FetchHashtagDetails<D> {
+  name: String,
+  #[serde(skip_deserializing)]
+  db_client: D,
+}
+
+#[derive(Serialize)]
+pub struct HashtagDetails {
+  name: String,
+  color: String,
+  created_at: NaiveDateTime,
+  post_count: i64,
+  follower_count: i64,
+  recent_activity: HashtagActivity,
+}
+
+#[derive(Serialize)]
+struct HashtagActivity {
+  posts_this_week: i64,
+  comments_this_week: i64,
+  last_post_at: Option<NaiveDateTime>,
+}
+
+impl<'a> FetchHashtagDetails<NoDBClient> {
+  pub fn add_db_client(self, db_client: &'a Client) -> FetchHashtagDetails<WithDBClient<'a>> {
+    FetchHashtagDetails {
+      name: normalize_hashtag(&self.name),
+      db_client: WithDBClient(db_client),
+    }
+  }
+}
+
+impl<'a> FetchHashtagDetails<WithDBClient<'a>> {
+  pub async fn fetch(&self) -> Result<HashtagDetails, (StatusCode, Value)> {
+    let week_ago = Utc::now().naive_utc() - Duration::days(7);
+
     self
       .get_db_client()
-      .query(&self.get_select_statement().await?, &[])
+      .query(
+        &self.get_select_statement().await?,
+        &[&self.name, &week_ago],
+      )
       .await
       .map_err(|e| {
         (
@@ -32,17 +168,25 @@ impl<'a> FetchTrendingHashtags<WithDBClient<'a>> {
           json!({"message": e.to_string()}),
         )
       })?
-      .into_iter()
-      .map(|row| TrendingHashtagsResponse::value(row))
-      .collect::<Result<_, _>>()
-      .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+      .first()
+      .map(HashtagDetails::from_row)
+      .ok_or((
+        StatusCode::NOT_FOUND,
+        json!({ "message": format!("No hashtag found with name {}", self.name) }),
+      ))?
   }
 
   async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let stmt = r#"
-      SELECT COUNT(hashtag_id) score, name, color::TEXT, created_at 
-      FROM posts_hashtags_relationship ph LEFT JOIN hashtags h ON ph.hashtag_id = h.id
-      WHERE now() - created_at < interval '48 hours' GROUP BY h.id ORDER BY score DESC LIMIT 7"#;
+    let stmt = "SELECT h.name, h.color::TEXT color, h.created_at,
+      (SELECT COUNT(*) FROM posts_hashtags_relationship r WHERE r.hashtag_id = h.id) post_count,
+      (SELECT COUNT(*) FROM hashtag_follows f WHERE f.hashtag_id = h.id) follower_count,
+      (SELECT COUNT(*) FROM posts_hashtags_relationship r INNER JOIN posts p ON p.id = r.post_id
+        WHERE r.hashtag_id = h.id AND p.created_at > $2) posts_this_week,
+      (SELECT COUNT(*) FROM posts_hashtags_relationship r INNER JOIN post_comments c ON c.post_id = r.post_id
+        WHERE r.hashtag_id = h.id AND c.created_at > $2) comments_this_week,
+      (SELECT MAX(p.created_at) FROM posts_hashtags_relationship r INNER JOIN posts p ON p.id = r.post_id
+        WHERE r.hashtag_id = h.id) last_post_at
+      FROM hashtags h WHERE h.name = $1";
 
     self.get_db_client().prepare(stmt).await.map_err(|e| {
       (
@@ -57,17 +201,290 @@ impl<'a> FetchTrendingHashtags<WithDBClient<'a>> {
   }
 }
 
-struct TrendingHashtagsResponse;
+impl HashtagDetails {
+  pub fn from_row(row: &Row) -> Result<HashtagDetails, (StatusCode, Value)> {
+    let name = row.try_get::<&str, String>("name");
+    let color = row.try_get::<&str, String>("color");
+    let created_at = row.try_get::<&str, NaiveDateTime>("created_at");
+    let post_count = row.try_get::<&str, i64>("post_count");
+    let follower_count = row.try_get::<&str, i64>("follower_count");
+    let posts_this_week = row.try_get::<&str, i64>("posts_this_week");
+    let comments_this_week = row.try_get::<&str, i64>("comments_this_week");
+    let last_post_at = row.try_get::<&str, Option<NaiveDateTime>>("last_post_at");
+
+    match (
+      name,
+      color,
+      created_at,
+      post_count,
+      follower_count,
+      posts_this_week,
+      comments_this_week,
+      last_post_at,
+    ) {
+      (
+        Ok(name),
+        Ok(color),
+        Ok(created_at),
+        Ok(post_count),
+        Ok(follower_count),
+        Ok(posts_this_week),
+        Ok(comments_this_week),
+        Ok(last_post_at),
+      ) => Ok(HashtagDetails {
+        name,
+        color,
+        created_at,
+        post_count,
+        follower_count,
+        recent_activity: HashtagActivity {
+          posts_this_week,
+          comments_this_week,
+          last_post_at,
+        },
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+pub struct FindHashtag<'a> {
+  pub db_client: &'a Client,
+  pub name: &'a str,
+}
+
+impl<'a> FindHashtag<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    self
+      .db_client
+      .query(&self.get_select_statement().await?, &[&self.name])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::NOT_FOUND,
+        json!({ "message": format!("No hashtag found with name {}", self.name) }),
+      ))
+      .map(|_| ())
+  }
+
+  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT id FROM hashtags WHERE name = $1";
+
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+}
+
+#[derive(Deserialize)]
+pub struct FetchHashtagSuggestions<D> {
+  prefix: String,
+  limit: Option<i64>,
+  #[serde(skip_deserializing)]
+  db_client: D,
+}
 
-impl TrendingHashtagsResponse {
-  pub fn value(row: Row) -> Result<Value, Value> {
-    let name = row
-      .try_get::<&str, String>("name")
-      .map_err(|_| json!({"message": "Error converting hashtag name postgres to rust type"}))?;
-    let color = row
-      .try_get::<&str, String>("color")
-      .map_err(|_| json!({"message": "Error converting color postgres to rust type"}))?;
+#[derive(Serialize)]
+pub struct HashtagSuggestion {
+  name: String,
+  color: String,
+  post_count: i64,
+}
 
-    Ok(json!((name, color)))
+impl<'a> FetchHashtagSuggestions<NoDBClient> {
+  pub fn add_db_client(self, db_client: &'a Client) -> FetchHashtagSuggestions<WithDBClient<'a>> {
+    FetchHashtagSuggestions {
+      prefix: normalize_hashtag(&self.prefix),
+      limit: self.limit,
+      db_client: WithDBClient(db_client),
+    }
+  }
+}
+
+impl<'a> FetchHashtagSuggestions<WithDBClient<'a>> {
+  pub async fn fetch(&self) -> Result<Vec<HashtagSuggestion>, (StatusCode, Value)> {
+    let limit = self.limit.unwrap_or(10);
+
+    if self.prefix.trim().is_empty() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "prefix", "message": "Prefix should contain at least one letter"}),
+      ));
+    }
+
+    if !(1..=20).contains(&limit) {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "limit", "message": "Limit should be between 1 and 20"}),
+      ));
+    }
+
+    let prefix = self
+      .prefix
+      .replace('\\', "\\\\")
+      .replace('%', "\\%")
+      .replace('_', "\\_");
+
+    self
+      .get_db_client()
+      .query(&self.get_select_statement().await?, &[&prefix, &limit])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .iter()
+      .map(HashtagSuggestion::from_row)
+      .collect()
+  }
+
+  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT h.name, h.color::TEXT color, COUNT(r.post_id) post_count FROM hashtags h
+      LEFT JOIN posts_hashtags_relationship r ON r.hashtag_id = h.id
+      WHERE h.name LIKE $1 || '%'
+      GROUP BY h.id ORDER BY post_count DESC, h.name ASC LIMIT $2";
+
+    self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+
+  fn get_db_client(&self) -> &'a Client {
+    self.db_client.0
+  }
+}
+
+impl HashtagSuggestion {
+  pub fn from_row(row: &Row) -> Result<HashtagSuggestion, (StatusCode, Value)> {
+    let name = row.try_get::<&str, String>("name");
+    let color = row.try_get::<&str, String>("color");
+    let post_count = row.try_get::<&str, i64>("post_count");
+
+    match (name, color, post_count) {
+      (Ok(name), Ok(color), Ok(post_count)) => Ok(HashtagSuggestion {
+        name,
+        color,
+        post_count,
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+pub struct FollowHashtag<'a> {
+  pub user_details: UserAuthDetails,
+  pub db_client: &'a Client,
+  pub name: String,
+}
+
+impl<'a> FollowHashtag<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let hashtag_id: i32 = self
+      .db_client
+      .query(&self.get_hashtag_id_statement().await?, &[&self.get_name()])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::NOT_FOUND,
+        json!({"message": "No hashtag found with such name"}),
+      ))?
+      .try_get("id")
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    self
+      .db_client
+      .query(
+        &self.get_insert_statement().await?,
+        &[&self.user_details.id, &hashtag_id, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  pub async fn exec_reverse(&self) -> Result<(), (StatusCode, Value)> {
+    self
+      .db_client
+      .query(
+        &self.get_delete_statement().await?,
+        &[&self.user_details.id, &self.get_name()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  fn get_name(&self) -> String {
+    normalize_hashtag(&self.name)
+  }
+
+  async fn get_hashtag_id_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT id FROM hashtags WHERE name = $1";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_insert_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "INSERT INTO hashtag_follows (user_id, hashtag_id, created_at) VALUES ($1, $2, $3)
+      ON CONFLICT (user_id, hashtag_id) DO NOTHING";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_delete_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "DELETE FROM hashtag_follows
+      WHERE user_id = $1 AND hashtag_id = (SELECT id FROM hashtags WHERE name = $2)";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({ "message": e.to_string() }),
+      )
+    })
   }
 }
@@ -1,21 +1,28 @@
 mod auth;
+mod feed;
 mod hashtags;
+mod pagination;
 mod posts;
+mod search;
 mod users;
 
+pub use auth::models::TokenScope;
 pub use auth::models::UserAuth;
 pub use auth::models::UserAuthDetails;
 pub use auth::view as auth;
+pub use feed::view as feed;
 pub use hashtags::view as hashtags;
 pub use posts::view as post;
+pub use search::view as search;
 pub use users::view as user;
 
 pub mod handler_utils {
   use super::UserAuthDetails;
-  use deadpool_postgres::Client;
+  use deadpool_postgres::{Client, Transaction};
   #[derive(Default, Debug)]
   pub struct NoDBClient;
   pub struct WithDBClient<'a>(pub &'a Client);
+  pub struct WithDBTransaction<'a>(pub &'a Transaction<'a>);
   #[derive(Default, Debug)]
   pub struct NoUserDetails;
   pub struct WithUserDetails<'a>(pub &'a UserAuthDetails);
@@ -0,0 +1,426 @@
+use actix_web::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::{types::ToSql, Row};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+  Next,
+  Prev,
+}
+
+#[derive(Clone, Debug)]
+pub enum CursorKey {
+  Timestamp(NaiveDateTime),
+  Float(f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct Cursor {
+  pub key: CursorKey,
+  pub id: i32,
+  pub direction: Direction,
+  pub now: NaiveDateTime,
+}
+
+impl Cursor {
+  pub fn encode(&self) -> String {
+    let direction = match self.direction {
+      Direction::Next => "n",
+      Direction::Prev => "p",
+    };
+
+    let key = match self.key {
+      CursorKey::Timestamp(t) => format!("t{}", t.and_utc().timestamp_micros()),
+      CursorKey::Float(f) => format!("f{:x}", f.to_bits()),
+    };
+
+    URL_SAFE_NO_PAD.encode(format!(
+      "{}|{}|{}|{}",
+      direction,
+      key,
+      self.id,
+      self.now.and_utc().timestamp_micros()
+    ))
+  }
+
+  pub fn decode(cursor: &str) -> Result<Cursor, (StatusCode, Value)> {
+    let err = (
+      StatusCode::BAD_REQUEST,
+      json!({"name": "cursor", "message": "Invalid cursor"}),
+    );
+
+    let decoded = URL_SAFE_NO_PAD
+      .decode(cursor)
+      .ok()
+      .and_then(|b| String::from_utf8(b).ok())
+      .ok_or(err.clone())?;
+
+    let parts: Vec<&str> = decoded.split('|').collect();
+
+    if parts.len() != 4 {
+      return Err(err);
+    }
+
+    let direction = match parts[0] {
+      "n" => Direction::Next,
+      "p" => Direction::Prev,
+      _ => return Err(err),
+    };
+
+    let key = match parts[1].split_at_checked(1) {
+      Some(("t", k)) => k
+        .parse::<i64>()
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+        .map(|d| CursorKey::Timestamp(d.naive_utc())),
+      Some(("f", k)) => u64::from_str_radix(k, 16)
+        .ok()
+        .map(|k| CursorKey::Float(f64::from_bits(k))),
+      _ => None,
+    };
+    let id = parts[2].parse::<i32>();
+    let now = parts[3]
+      .parse::<i64>()
+      .ok()
+      .and_then(DateTime::from_timestamp_micros)
+      .map(|d| d.naive_utc());
+
+    match (key, id, now) {
+      (Some(key), Ok(id), Some(now)) => Ok(Cursor {
+        key,
+        id,
+        direction,
+        now,
+      }),
+      _ => Err(err),
+    }
+  }
+}
+
+impl CursorKey {
+  pub fn from_row(row: &Row) -> Result<CursorKey, (StatusCode, Value)> {
+    row
+      .try_get::<&str, NaiveDateTime>("sort_key")
+      .map(CursorKey::Timestamp)
+      .or_else(|_| row.try_get::<&str, f64>("sort_key").map(CursorKey::Float))
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+  }
+
+  fn timestamp(&self) -> NaiveDateTime {
+    match self {
+      CursorKey::Timestamp(t) => *t,
+      CursorKey::Float(f) => DateTime::from_timestamp_micros(*f as i64)
+        .unwrap_or_default()
+        .naive_utc(),
+    }
+  }
+
+  fn float(&self) -> f64 {
+    match self {
+      CursorKey::Timestamp(t) => t.and_utc().timestamp_micros() as f64,
+      CursorKey::Float(f) => *f,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Clause {
+  Having,
+  And,
+}
+
+impl Clause {
+  fn keyword(&self) -> &'static str {
+    match self {
+      Clause::Having => "HAVING",
+      Clause::And => "AND",
+    }
+  }
+}
+
+pub enum SortKey<'a> {
+  Column {
+    column: &'a str,
+    id: &'a str,
+    name: &'a str,
+  },
+  Expr(&'a str),
+}
+
+#[derive(Clone, Debug)]
+pub struct Pagination {
+  pub limit: i64,
+  pub offset: i64,
+  pub cursor: Option<Cursor>,
+  pub now: NaiveDateTime,
+}
+
+impl Default for Pagination {
+  fn default() -> Self {
+    Pagination {
+      limit: 20,
+      offset: 0,
+      cursor: None,
+      now: Utc::now().naive_utc(),
+    }
+  }
+}
+
+impl Pagination {
+  pub fn new(
+    limit: Option<i64>,
+    page: Option<i64>,
+    cursor: Option<&str>,
+  ) -> Result<Pagination, (StatusCode, Value)> {
+    let limit = limit.unwrap_or(20);
+    let page = page.unwrap_or(1);
+
+    if limit < 1 {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "limit", "message": "Limit should be at least 1"}),
+      ));
+    }
+
+    if page < 1 {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "page", "message": "Page should be at least 1"}),
+      ));
+    }
+
+    let cursor = match cursor {
+      Some(c) => Some(Cursor::decode(c)?),
+      None => None,
+    };
+
+    Ok(Pagination {
+      limit,
+      offset: match cursor {
+        Some(_) => 0,
+        None => (page - 1) * limit,
+      },
+      now: cursor
+        .as_ref()
+        .map(|c| c.now)
+        .unwrap_or(Utc::now().naive_utc()),
+      cursor,
+    })
+  }
+
+  pub fn at(self, now: Option<NaiveDateTime>) -> Pagination {
+    match (&self.cursor, now) {
+      (None, Some(now)) => Pagination { now, ..self },
+      _ => self,
+    }
+  }
+
+  pub fn add_statement(
+    &self,
+    stmt: &str,
+    clause: Clause,
+    key: &SortKey,
+    ascending: bool,
+    mut conditions: Vec<String>,
+    params: &mut Vec<Box<dyn ToSql + Sync>>,
+  ) -> String {
+    let (cmp, order) = match ascending != self.is_backwards() {
+      true => (">", "ASC"),
+      false => ("<", "DESC"),
+    };
+
+    match key {
+      SortKey::Column { column, id, name } => {
+        if let Some(c) = &self.cursor {
+          params.push(Box::new(c.key.timestamp()));
+          params.push(Box::new(c.id));
+          conditions.push(format!(
+            "({column}, {id}) {cmp} (${}, ${})",
+            params.len() - 1,
+            params.len()
+          ));
+        }
+
+        params.push(Box::new(self.limit + 1));
+        params.push(Box::new(self.offset));
+
+        format!(
+          "WITH t AS ({} ORDER BY {column} {order}, {id} {order} LIMIT ${} OFFSET ${})
+          SELECT * FROM (SELECT t.*, t.{name} sort_key FROM t) t
+          ORDER BY sort_key {order}, id {order}",
+          add_conditions(stmt, clause, &conditions),
+          params.len() - 1,
+          params.len()
+        )
+      }
+
+      // The key is computed from the inner statement's output, so the cursor can only be
+      // applied once every matching row has been built. Conditions passed by the caller are
+      // still pushed down to keep that set small.
+      SortKey::Expr(key) => {
+        let stmt = add_conditions(stmt, clause, &conditions);
+        let mut cursor = String::new();
+
+        if let Some(c) = &self.cursor {
+          params.push(Box::new(c.key.float()));
+          params.push(Box::new(c.id));
+          cursor = format!(
+            "WHERE (sort_key, id) {cmp} (${}, ${})",
+            params.len() - 1,
+            params.len()
+          );
+        }
+
+        params.push(Box::new(self.limit + 1));
+        params.push(Box::new(self.offset));
+
+        format!(
+          "WITH t AS ({stmt}) SELECT * FROM (SELECT t.*, ({key})::FLOAT8 sort_key FROM t) t {cursor}
+          ORDER BY sort_key {order}, id {order} LIMIT ${} OFFSET ${}",
+          params.len() - 1,
+          params.len()
+        )
+      }
+    }
+  }
+
+  pub fn is_backwards(&self) -> bool {
+    matches!(
+      self.cursor,
+      Some(Cursor {
+        direction: Direction::Prev,
+        ..
+      })
+    )
+  }
+}
+
+fn add_conditions(stmt: &str, clause: Clause, conditions: &[String]) -> String {
+  match conditions.is_empty() {
+    true => stmt.to_owned(),
+    false => format!("{stmt} {} {}", clause.keyword(), conditions.join(" AND ")),
+  }
+}
+
+#[derive(Deserialize)]
+pub struct PaginationQuery {
+  limit: Option<i64>,
+  page: Option<i64>,
+  cursor: Option<String>,
+}
+
+impl PaginationQuery {
+  pub fn validate(self) -> Result<Pagination, (StatusCode, Value)> {
+    if let Some(s) = self.limit {
+      if s > 50 {
+        return Err((
+          StatusCode::BAD_REQUEST,
+          json!({"message": "Cannot retrieve more than 50 posts"}),
+        ));
+      }
+    }
+
+    Pagination::new(self.limit, self.page, self.cursor.as_deref())
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+  pub data: Vec<T>,
+  pub next_cursor: Option<String>,
+  pub prev_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+  pub fn new(mut items: Vec<(T, CursorKey, i32)>, pagination: &Pagination) -> Page<T> {
+    let has_more = items.len() as i64 > pagination.limit;
+    let backwards = pagination.is_backwards();
+
+    items.truncate(pagination.limit as usize);
+
+    if backwards {
+      items.reverse();
+    }
+
+    let cursor = |item: Option<&(T, CursorKey, i32)>, direction: Direction| {
+      item.map(|(_, key, id)| {
+        Cursor {
+          key: key.clone(),
+          id: *id,
+          direction,
+          now: pagination.now,
+        }
+        .encode()
+      })
+    };
+
+    let next_cursor = match has_more || backwards {
+      true => cursor(items.last(), Direction::Next),
+      false => None,
+    };
+
+    let prev_cursor = match backwards {
+      true if has_more => cursor(items.first(), Direction::Prev),
+      false if pagination.cursor.is_some() || pagination.offset > 0 => {
+        cursor(items.first(), Direction::Prev)
+      }
+      _ => None,
+    };
+
+    Page {
+      data: items.into_iter().map(|(t, _, _)| t).collect(),
+      next_cursor,
+      prev_cursor,
+    }
+  }
+}
+
+pub async fn fetch_page<T>(
+  db_client: &Client,
+  stmt: &str,
+  params: &[Box<dyn ToSql + Sync>],
+  pagination: &Pagination,
+  from_row: impl Fn(&Row) -> Result<T, (StatusCode, Value)>,
+) -> Result<Page<T>, (StatusCode, Value)> {
+  let stmt = db_client.prepare(stmt).await.map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
+
+  let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p.as_ref()).collect();
+
+  let rows = db_client
+    .query(&stmt, &params)
+    .await
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?
+    .into_iter()
+    .map(|r| {
+      let id = r.try_get::<&str, i32>("id").map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+      Ok((from_row(&r)?, CursorKey::from_row(&r)?, id))
+    })
+    .collect::<Result<Vec<_>, (StatusCode, Value)>>()?;
+
+  Ok(Page::new(rows, pagination))
+}
@@ -1,4 +1,5 @@
 use actix_web::{
+  http::StatusCode,
   web::{self, Query},
   HttpResponse,
 };
@@ -9,7 +10,7 @@ use super::models;
 
 use crate::api::{
   handler_utils::{NoDBClient, NoUserDetails, NotValidated},
-  UserAuth,
+  TokenScope, UserAuth,
 };
 
 pub async fn create_post(
@@ -17,6 +18,17 @@ pub async fn create_post(
   db_pool: web::Data<Pool>,
   body: web::Json<models::CreatePostDetails<NoDBClient, NoUserDetails, NotValidated>>,
 ) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Post) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
   if user_details.details.is_none() {
     return HttpResponse::Forbidden().json(json!({
       "success": false,
@@ -39,11 +51,22 @@ pub async fn create_post(
     }));
   }
 
-  let db_client: Client = db_client_res.unwrap();
+  let mut db_client: Client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
 
   let res = body
     .into_inner()
-    .add_db_client(&db_client)
+    .add_db_client(&transaction)
     .add_user_details(&user_details)
     .validate();
 
@@ -52,6 +75,16 @@ pub async fn create_post(
     Err(e) => Err(e),
   };
 
+  let res = match res {
+    Ok(id) => transaction.commit().await.map(|_| id).map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
   match res {
     Ok(id) => HttpResponse::Ok().json(json!({
       "success": true,
@@ -107,7 +140,9 @@ pub async fn fetch_posts(
   match res {
     Ok(v) => HttpResponse::Ok().json(json!({
       "success": true,
-      "data": v
+      "data": v.data,
+      "next_cursor": v.next_cursor,
+      "prev_cursor": v.prev_cursor
     })),
 
     Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
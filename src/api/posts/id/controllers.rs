@@ -1,13 +1,16 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{http::StatusCode, web, HttpResponse};
 use deadpool_postgres::Pool;
 use serde_json::json;
 
 use crate::api::{
   handler_utils::{NoDBClient, NoUserDetails, NotValidated},
-  UserAuth,
+  TokenScope, UserAuth,
 };
 
-use super::models::{CreateComment, FetchComments, FetchPost, SavePost};
+use super::models::{
+  CreateComment, DeleteComment, DeletePost, FetchCommentThread, FetchComments, FetchPost, SavePost,
+  UpdateComment, UpdatePost, VoteComment, VotePost,
+};
 
 pub async fn fetch_post(
   id: web::Path<i32>,
@@ -53,11 +56,117 @@ pub async fn fetch_post(
   }
 }
 
-pub async fn save_post(
+pub async fn update_post(
+  user_details: UserAuth,
+  id: web::Path<i32>,
+  body: web::Json<UpdatePost<NoDBClient, NoUserDetails, NotValidated>>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Moderate) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+  let id = id.into_inner();
+
+  let body = body.into_inner().validate();
+
+  if let Err((s, v)) = body {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
+
+  let res = body
+    .unwrap()
+    .add_details(id, &transaction, &user_details)
+    .exec()
+    .await;
+
+  let res = match res {
+    Ok(_) => transaction.commit().await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": {
+        "id": id
+      }
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn delete_post(
   user_details: UserAuth,
   id: web::Path<i32>,
   db_pool: web::Data<Pool>,
 ) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Moderate) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
   if user_details.details.is_none() {
     return HttpResponse::Forbidden().json(json!({
       "success": false,
@@ -83,7 +192,7 @@ pub async fn save_post(
 
   let db_client = db_client_res.unwrap();
 
-  let res = SavePost {
+  let res = DeletePost {
     user_details,
     db_client: &db_client,
     id,
@@ -94,10 +203,91 @@ pub async fn save_post(
   match res {
     Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
 
-    Err(e) => HttpResponse::InternalServerError().json(json!({
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
       "success": false,
-      "message": e["message"],
-      "error": e
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn save_post(
+  user_details: UserAuth,
+  id: web::Path<i32>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Post) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+  let id = id.into_inner();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
+
+  let res = SavePost {
+    user_details,
+    db_client: &transaction,
+    id,
+  }
+  .exec()
+  .await;
+
+  let res = match res {
+    Ok(_) => transaction.commit().await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
     })),
   }
 }
@@ -107,6 +297,17 @@ pub async fn unsave_post(
   id: web::Path<i32>,
   db_pool: web::Data<Pool>,
 ) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Post) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
   if user_details.details.is_none() {
     return HttpResponse::Forbidden().json(json!({
       "success": false,
@@ -130,23 +331,137 @@ pub async fn unsave_post(
     }));
   }
 
-  let db_client = db_client_res.unwrap();
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
 
   let res = SavePost {
     user_details,
-    db_client: &db_client,
+    db_client: &transaction,
     id,
   }
   .exec_reverse()
   .await;
 
+  let res = match res {
+    Ok(_) => transaction.commit().await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
   match res {
     Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
 
-    Err(e) => HttpResponse::InternalServerError().json(json!({
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
       "success": false,
-      "message": e["message"],
-      "error": e
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn vote_post(
+  user_details: UserAuth,
+  id: web::Path<i32>,
+  body: web::Json<VotePost<NoDBClient, NoUserDetails, NotValidated>>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Post) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+  let id = id.into_inner();
+
+  let body = body.into_inner().validate();
+
+  if let Err((s, v)) = body {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
+
+  let res = body
+    .unwrap()
+    .add_details(id, &transaction, &user_details)
+    .exec()
+    .await;
+
+  let res = match res {
+    Ok(v) => transaction.commit().await.map(|_| v).map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(v) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": v
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
     })),
   }
 }
@@ -157,6 +472,17 @@ pub async fn create_comment(
   body: web::Json<CreateComment<NoDBClient, NoUserDetails, NotValidated>>,
   db_pool: web::Data<Pool>,
 ) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Comment) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
   if user_details.details.is_none() {
     return HttpResponse::Forbidden().json(json!({
       "success": false,
@@ -180,11 +506,22 @@ pub async fn create_comment(
     }));
   }
 
-  let db_client = db_client_res.unwrap();
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
 
   let body = body
     .into_inner()
-    .add_details(post_id, &db_client, &user_details)
+    .add_details(post_id, &transaction, &user_details)
     .validate()
     .await;
 
@@ -196,7 +533,18 @@ pub async fn create_comment(
     }));
   }
 
-  match body.unwrap().exec().await {
+  let res = body.unwrap().exec().await;
+
+  let res = match res {
+    Ok(id) => transaction
+      .commit()
+      .await
+      .map(|_| id)
+      .map_err(|e| json!({"message": e.to_string()})),
+    Err(e) => Err(e),
+  };
+
+  match res {
     Ok(id) => HttpResponse::Ok().json(json!({
       "success": true,
       "data": {
@@ -212,9 +560,280 @@ pub async fn create_comment(
   }
 }
 
+pub async fn update_comment(
+  user_details: UserAuth,
+  path: web::Path<(i32, i32)>,
+  body: web::Json<UpdateComment<NoDBClient, NoUserDetails, NotValidated>>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Moderate) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+  let (post_id, comment_id) = path.into_inner();
+
+  let body = body.into_inner().validate();
+
+  if let Err((s, v)) = body {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
+
+  let res = body
+    .unwrap()
+    .add_details(post_id, comment_id, &transaction, &user_details)
+    .exec()
+    .await;
+
+  let res = match res {
+    Ok(_) => transaction.commit().await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": {
+        "id": comment_id
+      }
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn delete_comment(
+  user_details: UserAuth,
+  path: web::Path<(i32, i32)>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Moderate) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+  let (post_id, id) = path.into_inner();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
+
+  let res = DeleteComment {
+    user_details,
+    db_client: &transaction,
+    post_id,
+    id,
+  }
+  .exec()
+  .await;
+
+  let res = match res {
+    Ok(_) => transaction.commit().await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn vote_comment(
+  user_details: UserAuth,
+  path: web::Path<(i32, i32)>,
+  body: web::Json<VoteComment<NoDBClient, NoUserDetails, NotValidated>>,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let user_details = match user_details.require_scope(TokenScope::Comment) {
+    Ok(u) => u,
+    Err(e) => {
+      return HttpResponse::Forbidden().json(json!({
+        "success": false,
+        "message": e["message"],
+        "error": e
+      }))
+    }
+  };
+
+  if user_details.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_details.details.unwrap();
+  let (post_id, id) = path.into_inner();
+
+  let body = body.into_inner().validate();
+
+  if let Err((s, v)) = body {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let mut db_client = db_client_res.unwrap();
+
+  let transaction_res = db_client.transaction().await;
+
+  if let Err(e) = transaction_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let transaction = transaction_res.unwrap();
+
+  let res = body
+    .unwrap()
+    .add_details(post_id, id, &transaction, &user_details)
+    .exec()
+    .await;
+
+  let res = match res {
+    Ok(v) => transaction.commit().await.map(|_| v).map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    }),
+    Err(e) => Err(e),
+  };
+
+  match res {
+    Ok(v) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": v
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
 pub async fn fetch_comments(
   post_id: web::Path<i32>,
   query: web::Query<FetchComments<NoDBClient, NotValidated>>,
+  user_details: UserAuth,
   db_pool: web::Data<Pool>,
 ) -> HttpResponse {
   let db_client_res = db_pool.get().await;
@@ -232,7 +851,7 @@ pub async fn fetch_comments(
 
   let query = query
     .into_inner()
-    .add_details(&db_client, post_id)
+    .add_details(&db_client, post_id, user_details.details.map(|u| u.id))
     .validate();
 
   if let Err(v) = query {
@@ -246,9 +865,11 @@ pub async fn fetch_comments(
   let res = query.unwrap().fetch_comments().await;
 
   match res {
-    Ok(d) => HttpResponse::Ok().json(json!({
+    Ok(page) => HttpResponse::Ok().json(json!({
       "success": true,
-      "data": d
+      "data": page.data,
+      "next_cursor": page.next_cursor,
+      "prev_cursor": page.prev_cursor
     })),
 
     Err(v) => HttpResponse::InternalServerError().json(json!({
@@ -258,3 +879,56 @@ pub async fn fetch_comments(
     })),
   }
 }
+
+pub async fn fetch_comment_thread(
+  path: web::Path<(i32, i32)>,
+  query: web::Query<FetchCommentThread<NoDBClient, NotValidated>>,
+  user_details: UserAuth,
+  db_pool: web::Data<Pool>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let (post_id, comment_id) = path.into_inner();
+
+  let query = query
+    .into_inner()
+    .add_details(
+      &db_client,
+      post_id,
+      comment_id,
+      user_details.details.map(|u| u.id),
+    )
+    .validate();
+
+  if let Err(v) = query {
+    return HttpResponse::BadRequest().json(json!({
+      "success": false,
+      "message": v["message"],
+      "error":v
+    }));
+  }
+
+  let res = query.unwrap().exec().await;
+
+  match res {
+    Ok(d) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": d
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
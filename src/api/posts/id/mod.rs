@@ -4,8 +4,27 @@ mod models;
 
 pub fn view(cfg: &mut ServiceConfig) {
   cfg.route("", web::get().to(controllers::fetch_post));
+  cfg.route("", web::patch().to(controllers::update_post));
+  cfg.route("", web::delete().to(controllers::delete_post));
   cfg.route("/save", web::post().to(controllers::save_post));
   cfg.route("/unsave", web::post().to(controllers::unsave_post));
+  cfg.route("/vote", web::post().to(controllers::vote_post));
   cfg.route("/comments", web::get().to(controllers::fetch_comments));
   cfg.route("/comments", web::post().to(controllers::create_comment));
+  cfg.route(
+    "/comments/{comment_id}",
+    web::get().to(controllers::fetch_comment_thread),
+  );
+  cfg.route(
+    "/comments/{comment_id}",
+    web::patch().to(controllers::update_comment),
+  );
+  cfg.route(
+    "/comments/{comment_id}",
+    web::delete().to(controllers::delete_comment),
+  );
+  cfg.route(
+    "/comments/{comment_id}/vote",
+    web::post().to(controllers::vote_comment),
+  );
 }
@@ -2,16 +2,20 @@ use std::marker::PhantomData;
 
 use actix_web::http::StatusCode;
 use chrono::{NaiveDateTime, Utc};
-use deadpool_postgres::Client;
+use deadpool_postgres::{Client, GenericClient, Transaction};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tokio_postgres::{Row, Statement};
+use tokio_postgres::{types::ToSql, Row, Statement};
 
 use crate::api::{
   handler_utils::{
-    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithUserDetails,
+    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithDBTransaction,
+    WithUserDetails,
+  },
+  pagination::{Clause, CursorKey, Page, Pagination, SortKey},
+  posts::models::{
+    validate_body, validate_hashtags, validate_title, FetchPostsResponse, SyncPostHashtags,
   },
-  posts::models::FetchPostsResponse,
   UserAuthDetails,
 };
 
@@ -36,17 +40,19 @@ impl<'a> FetchPost<'a> {
           json!({"message": e.to_string()}),
         )
       })?
-      .get(0)
+      .first()
       .ok_or((
         StatusCode::NOT_FOUND,
         json!({"message": "No post found with such id"}),
       ))
-      .map(|r| FetchPostsResponse::from_row(r))?
+      .map(FetchPostsResponse::from_row)?
   }
 
   async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
     let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
-      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name||':'||t.color) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves FROM posts p 
+      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name||':'||t.color) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score,
+      COALESCE((SELECT v.value FROM post_votes v WHERE v.post_id = p.id AND v.user_id = $2), 0::SMALLINT) my_vote FROM posts p 
       INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id 
       INNER JOIN hashtags t ON t.id = r.hashtag_id 
       INNER JOIN users u ON u.id = p.user_id 
@@ -65,14 +71,247 @@ impl<'a> FetchPost<'a> {
   }
 }
 
-pub struct SavePost<'a> {
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdatePost<D, U, V> {
+  #[serde(skip_deserializing)]
+  id: i32,
+
+  title: Option<String>,
+
+  body: Option<String>,
+
+  hashtags: Option<Vec<String>>,
+
+  #[serde(skip_deserializing)]
+  db_client: D,
+
+  #[serde(skip_deserializing)]
+  user_details: U,
+
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl UpdatePost<NoDBClient, NoUserDetails, NotValidated> {
+  pub fn validate(
+    self,
+  ) -> Result<UpdatePost<NoDBClient, NoUserDetails, Validated>, (StatusCode, Value)> {
+    if self.title.is_none() && self.body.is_none() && self.hashtags.is_none() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"message": "Nothing to update"}),
+      ));
+    }
+
+    Ok(UpdatePost {
+      id: self.id,
+      title: self.title.as_deref().map(validate_title).transpose()?,
+      body: self.body.as_deref().map(validate_body).transpose()?,
+      hashtags: self
+        .hashtags
+        .as_deref()
+        .map(validate_hashtags)
+        .transpose()?,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<'a, V> UpdatePost<NoDBClient, NoUserDetails, V> {
+  pub fn add_details(
+    self,
+    id: i32,
+    db_client: &'a Transaction<'a>,
+    user_details: &'a UserAuthDetails,
+  ) -> UpdatePost<WithDBTransaction<'a>, WithUserDetails<'a>, V> {
+    UpdatePost {
+      id,
+      title: self.title,
+      body: self.body,
+      hashtags: self.hashtags,
+      db_client: WithDBTransaction(db_client),
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a> UpdatePost<WithDBTransaction<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let author_id = get_post_author_id(self.get_db_client(), self.id, true).await?;
+
+    if author_id != self.user_details.0.id {
+      return Err((
+        StatusCode::FORBIDDEN,
+        json!({"name": "post", "message": "You can only edit your own posts"}),
+      ));
+    }
+
+    self
+      .get_db_client()
+      .execute(
+        &self.get_update_statement().await?,
+        &[&self.id, &self.title, &self.body],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if let Some(hashtags) = &self.hashtags {
+      SyncPostHashtags {
+        db_client: self.get_db_client(),
+        post_id: self.id,
+        hashtags,
+      }
+      .exec()
+      .await?;
+    }
+
+    Ok(())
+  }
+
+  fn get_db_client(&self) -> &'a Transaction<'a> {
+    self.db_client.0
+  }
+
+  async fn get_update_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "UPDATE posts SET title = COALESCE($2, title), body = COALESCE($3, body)
+      WHERE id = $1";
+
+    self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+}
+
+pub struct DeletePost<'a> {
   pub user_details: UserAuthDetails,
   pub db_client: &'a Client,
   pub id: i32,
 }
 
+impl<'a> DeletePost<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let author_id = get_post_author_id(self.db_client, self.id, false).await?;
+
+    if author_id != self.user_details.id {
+      return Err((
+        StatusCode::FORBIDDEN,
+        json!({"name": "post", "message": "You can only delete your own posts"}),
+      ));
+    }
+
+    let stmt = "DELETE FROM posts WHERE id = $1 AND user_id = $2";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .db_client
+      .execute(&stmt, &[&self.id, &self.user_details.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+}
+
+async fn get_post_author_id(
+  db_client: &impl GenericClient,
+  id: i32,
+  for_update: bool,
+) -> Result<i32, (StatusCode, Value)> {
+  let mut stmt = "SELECT user_id FROM posts WHERE id = $1".to_owned();
+
+  if for_update {
+    stmt += " FOR UPDATE";
+  }
+
+  let stmt = db_client.prepare(&stmt).await.map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
+
+  db_client
+    .query(&stmt, &[&id])
+    .await
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?
+    .first()
+    .ok_or((
+      StatusCode::NOT_FOUND,
+      json!({"message": "No post found with such id"}),
+    ))?
+    .try_get("user_id")
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+}
+
+pub struct SavePost<'a> {
+  pub user_details: UserAuthDetails,
+  pub db_client: &'a Transaction<'a>,
+  pub id: i32,
+}
+
 impl<'a> SavePost<'a> {
-  pub async fn exec(&self) -> Result<(), Value> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let post_exists: bool = self
+      .db_client
+      .query(&self.get_lock_post_statement().await?, &[&self.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": "No response from db"}),
+      ))?
+      .try_get("exists")
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if !post_exists {
+      return Err((
+        StatusCode::NOT_FOUND,
+        json!({"message": "No post found with such id"}),
+      ));
+    }
+
     self
       .db_client
       .query(
@@ -80,52 +319,508 @@ impl<'a> SavePost<'a> {
         &[&self.user_details.id, &self.id],
       )
       .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  pub async fn exec_reverse(&self) -> Result<(), (StatusCode, Value)> {
+    self
+      .db_client
+      .query(
+        &self.get_delete_statement().await?,
+        &[&self.user_details.id, &self.id],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  async fn get_lock_post_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT EXISTS (SELECT 1 FROM posts WHERE id = $1 FOR SHARE) exists";
+
+    self.prepare(stmt).await
+  }
+
+  pub async fn get_insert_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "INSERT INTO saved_posts (user_id, post_id) VALUES ($1, $2)
+      ON CONFLICT (user_id, post_id) DO NOTHING";
+
+    self.prepare(stmt).await
+  }
+
+  pub async fn get_delete_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "DELETE FROM saved_posts WHERE user_id = $1 AND post_id = $2";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({ "message": e.to_string() }),
+      )
+    })
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VotePost<D, U, V> {
+  #[serde(skip_deserializing)]
+  id: i32,
+
+  value: i16,
+
+  #[serde(skip_deserializing)]
+  db_client: D,
+
+  #[serde(skip_deserializing)]
+  user_details: U,
+
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VoteResponse {
+  score: i64,
+  my_vote: i16,
+}
+
+fn validate_vote(value: i16) -> Result<i16, (StatusCode, Value)> {
+  if ![-1, 0, 1].contains(&value) {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "value", "message": "Vote should be 1, -1 or 0"}),
+    ));
+  }
+
+  Ok(value)
+}
+
+impl VotePost<NoDBClient, NoUserDetails, NotValidated> {
+  pub fn validate(
+    self,
+  ) -> Result<VotePost<NoDBClient, NoUserDetails, Validated>, (StatusCode, Value)> {
+    Ok(VotePost {
+      id: self.id,
+      value: validate_vote(self.value)?,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<'a, V> VotePost<NoDBClient, NoUserDetails, V> {
+  pub fn add_details(
+    self,
+    id: i32,
+    db_client: &'a Transaction<'a>,
+    user_details: &'a UserAuthDetails,
+  ) -> VotePost<WithDBTransaction<'a>, WithUserDetails<'a>, V> {
+    VotePost {
+      id,
+      value: self.value,
+      db_client: WithDBTransaction(db_client),
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a> VotePost<WithDBTransaction<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn exec(&self) -> Result<VoteResponse, (StatusCode, Value)> {
+    get_post_author_id(self.get_db_client(), self.id, false).await?;
+
+    if self.value == 0 {
+      self
+        .get_db_client()
+        .execute(
+          &self.get_delete_statement().await?,
+          &[&self.user_details.0.id, &self.id],
+        )
+        .await
+    } else {
+      self
+        .get_db_client()
+        .execute(
+          &self.get_upsert_statement().await?,
+          &[
+            &self.user_details.0.id,
+            &self.id,
+            &self.value,
+            &Utc::now().naive_utc(),
+          ],
+        )
+        .await
+    }
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .get_db_client()
+      .query(&self.get_score_statement().await?, &[&self.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": "No response from db"}),
+      ))?
+      .try_get("score")
+      .map(|score| VoteResponse {
+        score,
+        my_vote: self.value,
+      })
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+  }
+
+  fn get_db_client(&self) -> &'a Transaction<'a> {
+    self.db_client.0
+  }
+
+  async fn get_upsert_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "INSERT INTO post_votes (user_id, post_id, value, created_at) VALUES ($1, $2, $3, $4)
+      ON CONFLICT (user_id, post_id) DO UPDATE SET value = EXCLUDED.value, created_at = EXCLUDED.created_at";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_delete_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "DELETE FROM post_votes WHERE user_id = $1 AND post_id = $2";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_score_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT COALESCE(SUM(value), 0)::BIGINT score FROM post_votes WHERE post_id = $1";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateComment<D, U, V> {
+  #[serde(skip_deserializing)]
+  post_id: i32,
+
+  body: String,
+
+  comment_id: Option<i32>,
+
+  #[serde(skip_deserializing)]
+  db_client: D,
+
+  #[serde(skip_deserializing)]
+  user_details: U,
+
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl<'a, V> CreateComment<NoDBClient, NoUserDetails, V> {
+  pub fn add_details(
+    self,
+    post_id: i32,
+    db_client: &'a Transaction<'a>,
+    user_details: &'a UserAuthDetails,
+  ) -> CreateComment<WithDBTransaction<'a>, WithUserDetails<'a>, V> {
+    CreateComment {
+      post_id,
+      body: self.body,
+      comment_id: self.comment_id,
+      db_client: WithDBTransaction(db_client),
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a, D, V> CreateComment<D, WithUserDetails<'a>, V> {
+  fn get_user_details(&self) -> &'a UserAuthDetails {
+    self.user_details.0
+  }
+}
+
+impl<'a, U, V> CreateComment<WithDBTransaction<'a>, U, V> {
+  fn get_db_client(&self) -> &'a Transaction<'a> {
+    self.db_client.0
+  }
+}
+
+impl<'a, U> CreateComment<WithDBTransaction<'a>, U, NotValidated> {
+  pub async fn validate(
+    mut self,
+  ) -> Result<CreateComment<WithDBTransaction<'a>, U, Validated>, Value> {
+    self.body = validate_comment_body(&self.body)?;
+
+    let is_comment_under_post = self.is_comment_under_post().await?;
+
+    if !is_comment_under_post {
+      return Err(json!({"message": "Comment does not exists in post"}));
+    }
+
+    Ok(CreateComment {
+      post_id: self.post_id,
+      comment_id: self.comment_id,
+      body: self.body,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+
+  async fn is_comment_under_post(&self) -> Result<bool, Value> {
+    if self.comment_id.is_none() {
+      return Ok(true);
+    }
+
+    let stmt = "SELECT EXISTS (SELECT 1 FROM post_comments
+      WHERE post_id = $1 AND id = $2 AND deleted_at IS NULL FOR SHARE) exists";
+
+    let stmt = self
+      .get_db_client()
+      .prepare(stmt)
+      .await
+      .map_err(|e| json!({"message": e.to_string()}))?;
+
+    self
+      .get_db_client()
+      .query(&stmt, &[&self.post_id, &self.comment_id])
+      .await
+      .map_err(|e| json!({"message": e.to_string()}))?
+      .first()
+      .ok_or(json!({"message": "No response from db"}))?
+      .try_get("exists")
+      .map_err(|e| json!({"message": e.to_string()}))
+  }
+}
+
+impl<'a, U> CreateComment<WithDBTransaction<'a>, U, Validated> {
+  async fn get_insert_statement(&self) -> Result<Statement, Value> {
+    let stmt = "INSERT INTO post_comments (post_id, user_id, comment_id, body, created_at)
+      VALUES ($1, $2, $3, $4, $5) RETURNING id";
+
+    self
+      .get_db_client()
+      .prepare(stmt)
+      .await
       .map_err(|e| json!({"message": e.to_string()}))
+  }
+}
+
+impl<'a> CreateComment<WithDBTransaction<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn exec(&self) -> Result<i32, Value> {
+    self
+      .get_db_client()
+      .query(
+        &self.get_insert_statement().await?,
+        &[
+          &self.post_id,
+          &self.get_user_details().id,
+          &self.comment_id,
+          &self.body,
+          &Utc::now().naive_utc(),
+        ],
+      )
+      .await
+      .map_err(|e| json!({"message": e.to_string()}))?
+      .first()
+      .ok_or(json!({"message": "No response from db"}))?
+      .try_get("id")
+      .map_err(|e| json!({"message": e.to_string()}))
+  }
+}
+
+fn validate_comment_body(body: &str) -> Result<String, Value> {
+  let body = body.trim();
+
+  if body.len() > 500 {
+    return Err(json!({
+      "name": "body",
+      "message": "Comment should be less than 500 characters"
+    }));
+  }
+
+  Ok(body.to_owned())
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateComment<D, U, V> {
+  #[serde(skip_deserializing)]
+  post_id: i32,
+
+  #[serde(skip_deserializing)]
+  id: i32,
+
+  body: String,
+
+  #[serde(skip_deserializing)]
+  db_client: D,
+
+  #[serde(skip_deserializing)]
+  user_details: U,
+
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl UpdateComment<NoDBClient, NoUserDetails, NotValidated> {
+  pub fn validate(
+    self,
+  ) -> Result<UpdateComment<NoDBClient, NoUserDetails, Validated>, (StatusCode, Value)> {
+    Ok(UpdateComment {
+      post_id: self.post_id,
+      id: self.id,
+      body: validate_comment_body(&self.body).map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<'a, V> UpdateComment<NoDBClient, NoUserDetails, V> {
+  pub fn add_details(
+    self,
+    post_id: i32,
+    id: i32,
+    db_client: &'a Transaction<'a>,
+    user_details: &'a UserAuthDetails,
+  ) -> UpdateComment<WithDBTransaction<'a>, WithUserDetails<'a>, V> {
+    UpdateComment {
+      post_id,
+      id,
+      body: self.body,
+      db_client: WithDBTransaction(db_client),
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a> UpdateComment<WithDBTransaction<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let author_id = get_comment_author_id(self.get_db_client(), self.post_id, self.id).await?;
+
+    if author_id != self.user_details.0.id {
+      return Err((
+        StatusCode::FORBIDDEN,
+        json!({"name": "comment", "message": "You can only edit your own comments"}),
+      ));
+    }
+
+    let stmt = "UPDATE post_comments SET body = $2, edited_at = $3 WHERE id = $1";
+
+    let stmt = self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .get_db_client()
+      .execute(&stmt, &[&self.id, &self.body, &Utc::now().naive_utc()])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
       .map(|_| ())
   }
 
-  pub async fn exec_reverse(&self) -> Result<(), Value> {
-    self
-      .db_client
-      .query(
-        &self.get_delete_statement().await?,
-        &[&self.user_details.id, &self.id],
-      )
-      .await
-      .map_err(|e| json!({"message": e.to_string()}))
-      .map(|_| ())
+  fn get_db_client(&self) -> &'a Transaction<'a> {
+    self.db_client.0
   }
+}
 
-  pub async fn get_insert_statement(&self) -> Result<Statement, Value> {
-    let stmt = "INSERT INTO saved_posts (user_id, post_id) VALUES ($1, $2)
-      ON CONFLICT (user_id, post_id) DO NOTHING";
+pub struct DeleteComment<'a> {
+  pub user_details: UserAuthDetails,
+  pub db_client: &'a Transaction<'a>,
+  pub post_id: i32,
+  pub id: i32,
+}
 
-    self
-      .db_client
-      .prepare(stmt)
-      .await
-      .map_err(|e| json!({ "message":e.to_string()  }))
-  }
+impl<'a> DeleteComment<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let author_id = get_comment_author_id(self.db_client, self.post_id, self.id).await?;
 
-  pub async fn get_delete_statement(&self) -> Result<Statement, Value> {
-    let stmt = "DELETE FROM saved_posts WHERE user_id = $1 AND post_id = $2";
+    if author_id != self.user_details.id {
+      return Err((
+        StatusCode::FORBIDDEN,
+        json!({"name": "comment", "message": "You can only delete your own comments"}),
+      ));
+    }
+
+    let stmt = "UPDATE post_comments SET body = '[deleted]', deleted_at = $2 WHERE id = $1";
+
+    let stmt = self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
 
     self
       .db_client
-      .prepare(stmt)
+      .execute(&stmt, &[&self.id, &Utc::now().naive_utc()])
       .await
-      .map_err(|e| json!({ "message":e.to_string()  }))
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
   }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct CreateComment<D, U, V> {
+pub struct VoteComment<D, U, V> {
   #[serde(skip_deserializing)]
   post_id: i32,
 
-  body: String,
+  #[serde(skip_deserializing)]
+  id: i32,
 
-  comment_id: Option<i32>,
+  value: i16,
 
   #[serde(skip_deserializing)]
   db_client: D,
@@ -137,131 +832,187 @@ pub struct CreateComment<D, U, V> {
   validated: PhantomData<V>,
 }
 
-impl<'a, V> CreateComment<NoDBClient, NoUserDetails, V> {
+impl VoteComment<NoDBClient, NoUserDetails, NotValidated> {
+  pub fn validate(
+    self,
+  ) -> Result<VoteComment<NoDBClient, NoUserDetails, Validated>, (StatusCode, Value)> {
+    Ok(VoteComment {
+      post_id: self.post_id,
+      id: self.id,
+      value: validate_vote(self.value)?,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<'a, V> VoteComment<NoDBClient, NoUserDetails, V> {
   pub fn add_details(
     self,
     post_id: i32,
-    db_client: &'a Client,
+    id: i32,
+    db_client: &'a Transaction<'a>,
     user_details: &'a UserAuthDetails,
-  ) -> CreateComment<WithDBClient<'a>, WithUserDetails<'a>, V> {
-    CreateComment {
+  ) -> VoteComment<WithDBTransaction<'a>, WithUserDetails<'a>, V> {
+    VoteComment {
       post_id,
-      body: self.body,
-      comment_id: self.comment_id,
-      db_client: WithDBClient(db_client),
+      id,
+      value: self.value,
+      db_client: WithDBTransaction(db_client),
       user_details: WithUserDetails(user_details),
       validated: PhantomData,
     }
   }
 }
 
-impl<'a, D, V> CreateComment<D, WithUserDetails<'a>, V> {
-  fn get_user_details(&self) -> &'a UserAuthDetails {
-    self.user_details.0
+impl<'a> VoteComment<WithDBTransaction<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn exec(&self) -> Result<VoteResponse, (StatusCode, Value)> {
+    get_comment_author_id(self.get_db_client(), self.post_id, self.id).await?;
+
+    if self.value == 0 {
+      self
+        .get_db_client()
+        .execute(
+          &self.get_delete_statement().await?,
+          &[&self.user_details.0.id, &self.id],
+        )
+        .await
+    } else {
+      self
+        .get_db_client()
+        .execute(
+          &self.get_upsert_statement().await?,
+          &[
+            &self.user_details.0.id,
+            &self.id,
+            &self.value,
+            &Utc::now().naive_utc(),
+          ],
+        )
+        .await
+    }
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })?;
+
+    self
+      .get_db_client()
+      .query(&self.get_score_statement().await?, &[&self.id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?
+      .first()
+      .ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": "No response from db"}),
+      ))?
+      .try_get("score")
+      .map(|score| VoteResponse {
+        score,
+        my_vote: self.value,
+      })
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
   }
-}
 
-impl<'a, U, V> CreateComment<WithDBClient<'a>, U, V> {
-  fn get_db_client(&self) -> &'a Client {
+  fn get_db_client(&self) -> &'a Transaction<'a> {
     self.db_client.0
   }
-}
 
-impl<'a, U> CreateComment<WithDBClient<'a>, U, NotValidated> {
-  pub async fn validate(mut self) -> Result<CreateComment<WithDBClient<'a>, U, Validated>, Value> {
-    self.body = self.body.trim().to_owned();
+  async fn get_upsert_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "INSERT INTO comment_votes (user_id, comment_id, value, created_at) VALUES ($1, $2, $3, $4)
+      ON CONFLICT (user_id, comment_id) DO UPDATE SET value = EXCLUDED.value, created_at = EXCLUDED.created_at";
 
-    if self.body.len() > 500 {
-      return Err(json!({
-        "name": "body",
-        "message": "Comment should be less than 500 characters"
-      }));
-    }
-
-    let is_comment_under_post = self.is_comment_under_post().await?;
+    self.prepare(stmt).await
+  }
 
-    if !is_comment_under_post {
-      return Err(json!({"message": "Comment does not exists in post"}));
-    }
+  async fn get_delete_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "DELETE FROM comment_votes WHERE user_id = $1 AND comment_id = $2";
 
-    Ok(CreateComment {
-      post_id: self.post_id,
-      comment_id: self.comment_id,
-      body: self.body,
-      db_client: self.db_client,
-      user_details: self.user_details,
-      validated: PhantomData,
-    })
+    self.prepare(stmt).await
   }
 
-  async fn is_comment_under_post(&self) -> Result<bool, Value> {
-    if let None = self.comment_id {
-      return Ok(true);
-    }
-
-    let stmt = "SELECT EXISTS (SELECT 1 FROM post_comments WHERE post_id = $1 AND id = $2) exists";
+  async fn get_score_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt =
+      "SELECT COALESCE(SUM(value), 0)::BIGINT score FROM comment_votes WHERE comment_id = $1";
 
-    let stmt = self
-      .get_db_client()
-      .prepare(stmt)
-      .await
-      .map_err(|e| json!({"message": e.to_string()}))?;
+    self.prepare(stmt).await
+  }
 
-    self
-      .get_db_client()
-      .query(&stmt, &[&self.post_id, &self.comment_id])
-      .await
-      .map_err(|e| json!({"message": e.to_string()}))?
-      .get(0)
-      .ok_or(json!({"message": "No response from db"}))?
-      .try_get("exists")
-      .map_err(|e| json!({"message": e.to_string()}))
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
   }
 }
 
-impl<'a, U> CreateComment<WithDBClient<'a>, U, Validated> {
-  async fn get_insert_statement(&self) -> Result<Statement, Value> {
-    let stmt = "INSERT INTO post_comments (post_id, user_id, comment_id, body, created_at)
-      VALUES ($1, $2, $3, $4, $5) RETURNING id";
+async fn get_comment_author_id(
+  db_client: &Transaction<'_>,
+  post_id: i32,
+  id: i32,
+) -> Result<i32, (StatusCode, Value)> {
+  let stmt = "SELECT user_id FROM post_comments
+    WHERE post_id = $1 AND id = $2 AND deleted_at IS NULL FOR UPDATE";
 
-    self
-      .get_db_client()
-      .prepare(stmt)
-      .await
-      .map_err(|e| json!({"message": e.to_string()}))
-  }
-}
+  let stmt = db_client.prepare(stmt).await.map_err(|e| {
+    (
+      StatusCode::INTERNAL_SERVER_ERROR,
+      json!({"message": e.to_string()}),
+    )
+  })?;
 
-impl<'a> CreateComment<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
-  pub async fn exec(&self) -> Result<i32, Value> {
-    self
-      .get_db_client()
-      .query(
-        &self.get_insert_statement().await?,
-        &[
-          &self.post_id,
-          &self.get_user_details().id,
-          &self.comment_id,
-          &self.body,
-          &Utc::now().naive_utc(),
-        ],
+  db_client
+    .query(&stmt, &[&post_id, &id])
+    .await
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
       )
-      .await
-      .map_err(|e| json!({"message": e.to_string()}))?
-      .get(0)
-      .ok_or(json!({"message": "No response from db"}))?
-      .try_get("id")
-      .map_err(|e| json!({"message": e.to_string()}))
-  }
+    })?
+    .first()
+    .ok_or((
+      StatusCode::NOT_FOUND,
+      json!({"message": "No comment found with such id in post"}),
+    ))?
+    .try_get("user_id")
+    .map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct FetchComments<D, V> {
   sort: Option<Sort>,
+  limit: Option<i64>,
   page: Option<i64>,
+  cursor: Option<String>,
+  max_depth: Option<i32>,
+  #[serde(skip)]
+  pagination: Pagination,
   #[serde(skip_deserializing)]
   post_id: i32,
   #[serde(skip_deserializing)]
+  user_id: Option<i32>,
+  #[serde(skip_deserializing)]
   db_client: D,
   #[serde(skip_deserializing)]
   validated: PhantomData<V>,
@@ -274,6 +1025,18 @@ enum Sort {
   Oldest,
   Highest,
   Lowest,
+  Top,
+  Controversial,
+  Best,
+}
+
+fn validate_max_depth(max_depth: Option<i32>) -> Result<Option<i32>, Value> {
+  match max_depth {
+    Some(d) if d < 0 => {
+      Err(json!({"name": "max_depth", "message": "Max depth cannot be negative"}))
+    }
+    _ => Ok(max_depth),
+  }
 }
 
 impl<'a> FetchComments<WithDBClient<'a>, NotValidated> {
@@ -282,10 +1045,22 @@ impl<'a> FetchComments<WithDBClient<'a>, NotValidated> {
       return Err(json!({"message": "Post id not added"}));
     }
 
+    if let Some(s) = self.limit {
+      if s > 50 {
+        return Err(json!({"message": "Cannot retrieve more than 50 comments"}));
+      }
+    }
+
     Ok(FetchComments {
+      pagination: Pagination::new(self.limit, self.page, self.cursor.as_deref())
+        .map_err(|(_, v)| v)?,
+      max_depth: validate_max_depth(self.max_depth)?,
       sort: self.sort,
+      limit: self.limit,
       page: self.page,
+      cursor: self.cursor,
       post_id: self.post_id,
+      user_id: self.user_id,
       db_client: self.db_client,
       validated: PhantomData,
     })
@@ -297,11 +1072,17 @@ impl FetchComments<NoDBClient, NotValidated> {
     self,
     db_client: &Client,
     post_id: i32,
-  ) -> FetchComments<WithDBClient, NotValidated> {
+    user_id: Option<i32>,
+  ) -> FetchComments<WithDBClient<'_>, NotValidated> {
     FetchComments {
       sort: self.sort,
+      limit: self.limit,
       page: self.page,
+      cursor: self.cursor,
+      max_depth: self.max_depth,
+      pagination: self.pagination,
       post_id,
+      user_id,
       db_client: WithDBClient(db_client),
       validated: PhantomData,
     }
@@ -312,61 +1093,239 @@ impl<'a, V> FetchComments<WithDBClient<'a>, V> {
   fn get_db_client(&self) -> &'a Client {
     self.db_client.0
   }
+}
 
-  async fn get_fetch_comments_statement(&self) -> Result<Statement, Value> {
-    let stmt = "WITH RECURSIVE t(id, body, comment_id, created_at, user_id) AS (
-      SELECT id, body, comment_id, created_at, user_id FROM post_comments WHERE post_id = $1
-      UNION ALL
-      SELECT b.id, b.body, b.comment_id, b.created_at, b.user_id FROM t INNER JOIN post_comments b ON t.comment_id = b.id)
-      SELECT t.*, (COUNT(t.id) - 1) replies, u.username author_name, u.id author_id FROM t 
-      INNER JOIN users u ON u.id = t.user_id 
-      GROUP BY t.id, t.comment_id, t.created_at, t.body, t.user_id, u.id";
+impl<'a> FetchComments<WithDBClient<'a>, Validated> {
+  pub async fn fetch_comments(&self) -> Result<Page<FetchCommentsResponseParsed>, Value> {
+    let sort = self.sort.clone().unwrap_or(Sort::Latest);
 
-    self
+    let stmt = format!(
+      "WITH RECURSIVE {REPLY_COUNTS}
+      SELECT c.id, c.created_at,
+      (SELECT COUNT(*) FROM comment_votes v WHERE v.comment_id = c.id AND v.value = 1) ups,
+      (SELECT COUNT(*) FROM comment_votes v WHERE v.comment_id = c.id AND v.value = -1) downs,
+      COALESCE(n.replies, 0) replies
+      FROM post_comments c LEFT JOIN reply_counts n ON n.id = c.id
+      WHERE c.post_id = $1 AND c.comment_id IS NULL"
+    );
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(self.post_id)];
+
+    let stmt = sort.add_pagination_statement(&stmt, &self.pagination, &mut params);
+
+    let stmt = self
       .get_db_client()
-      .prepare(stmt)
+      .prepare(&stmt)
       .await
-      .map_err(|e| json!({"message": e.to_string()}))
-  }
-}
+      .map_err(|e| json!({"message": e.to_string()}))?;
 
-impl<'a> FetchComments<WithDBClient<'a>, Validated> {
-  pub async fn fetch_comments(&self) -> Result<Vec<FetchCommentsResponseParsed>, Value> {
-    let res = self
+    let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p.as_ref()).collect();
+
+    let rows = self
       .get_db_client()
-      .query(
-        &self.get_fetch_comments_statement().await?,
-        &[&self.post_id],
-      )
+      .query(&stmt, &params)
       .await
       .map_err(|e| json!({"message": e.to_string()}))?
       .into_iter()
-      .map(|r| FetchCommentsResponse::from_row(&r))
+      .map(|r| {
+        let id = r.try_get::<&str, i32>("id");
+        let key = CursorKey::from_row(&r);
+
+        match (id, key) {
+          (Ok(id), Ok(key)) => Ok((id, key, id)),
+          _ => Err(json!({"message": "Error converting postgres to rust type"})),
+        }
+      })
       .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(FetchCommentsResponse::parse(&res, &self.sort))
+    let page = Page::new(rows, &self.pagination);
+
+    let res = fetch_comment_trees(
+      self.get_db_client(),
+      self.post_id,
+      &page.data,
+      self.user_id,
+      &sort,
+      self.max_depth,
+    )
+    .await?;
+
+    Ok(Page {
+      data: FetchCommentsResponse::parse(&res, &page.data, &sort, self.max_depth),
+      next_cursor: page.next_cursor,
+      prev_cursor: page.prev_cursor,
+    })
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchCommentThread<D, V> {
+  sort: Option<Sort>,
+  max_depth: Option<i32>,
+  #[serde(skip_deserializing)]
+  post_id: i32,
+  #[serde(skip_deserializing)]
+  id: i32,
+  #[serde(skip_deserializing)]
+  user_id: Option<i32>,
+  #[serde(skip_deserializing)]
+  db_client: D,
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl FetchCommentThread<NoDBClient, NotValidated> {
+  pub fn add_details(
+    self,
+    db_client: &Client,
+    post_id: i32,
+    id: i32,
+    user_id: Option<i32>,
+  ) -> FetchCommentThread<WithDBClient<'_>, NotValidated> {
+    FetchCommentThread {
+      sort: self.sort,
+      max_depth: self.max_depth,
+      post_id,
+      id,
+      user_id,
+      db_client: WithDBClient(db_client),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a> FetchCommentThread<WithDBClient<'a>, NotValidated> {
+  pub fn validate(self) -> Result<FetchCommentThread<WithDBClient<'a>, Validated>, Value> {
+    Ok(FetchCommentThread {
+      max_depth: validate_max_depth(self.max_depth)?,
+      sort: self.sort,
+      post_id: self.post_id,
+      id: self.id,
+      user_id: self.user_id,
+      db_client: self.db_client,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<'a> FetchCommentThread<WithDBClient<'a>, Validated> {
+  pub async fn exec(&self) -> Result<FetchCommentsResponseParsed, (StatusCode, Value)> {
+    let sort = self.sort.clone().unwrap_or(Sort::Latest);
+
+    let res = fetch_comment_trees(
+      self.db_client.0,
+      self.post_id,
+      &[self.id],
+      self.user_id,
+      &sort,
+      self.max_depth,
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    FetchCommentsResponse::parse(&res, &[self.id], &sort, self.max_depth)
+      .pop()
+      .ok_or((
+        StatusCode::NOT_FOUND,
+        json!({"message": "No comment found with such id in post"}),
+      ))
   }
 }
 
+const REPLY_COUNTS: &str = "descendants(ancestor_id, id) AS (
+    SELECT comment_id, id FROM post_comments WHERE post_id = $1 AND comment_id IS NOT NULL
+    UNION ALL
+    SELECT p.comment_id, d.id FROM descendants d INNER JOIN post_comments p ON p.id = d.ancestor_id
+    WHERE p.comment_id IS NOT NULL),
+    reply_counts(id, replies) AS (SELECT ancestor_id, COUNT(*) FROM descendants GROUP BY ancestor_id)";
+
+async fn fetch_comment_trees(
+  db_client: &Client,
+  post_id: i32,
+  root_ids: &[i32],
+  user_id: Option<i32>,
+  sort: &Sort,
+  max_depth: Option<i32>,
+) -> Result<Vec<FetchCommentsResponse>, Value> {
+  let stmt = format!(
+    "WITH RECURSIVE t(id, body, comment_id, created_at, edited_at, deleted_at, user_id, depth) AS (
+    SELECT id, body, comment_id, created_at, edited_at, deleted_at, user_id, 0 FROM post_comments
+    WHERE post_id = $1 AND id = ANY($2)
+    UNION ALL
+    SELECT b.id, b.body, b.comment_id, b.created_at, b.edited_at, b.deleted_at, b.user_id, t.depth + 1 FROM t
+    INNER JOIN post_comments b ON b.comment_id = t.id WHERE $4::INT IS NULL OR t.depth < $4),
+    {REPLY_COUNTS}
+    SELECT c.*, ({})::FLOAT8 sort_key FROM (SELECT t.*, u.username author_name, u.id author_id,
+    COALESCE(n.replies, 0) replies,
+    (SELECT COUNT(*) FROM comment_votes v WHERE v.comment_id = t.id AND v.value = 1) ups,
+    (SELECT COUNT(*) FROM comment_votes v WHERE v.comment_id = t.id AND v.value = -1) downs,
+    COALESCE((SELECT v.value FROM comment_votes v WHERE v.comment_id = t.id AND v.user_id = $3), 0::SMALLINT) my_vote FROM t
+    INNER JOIN users u ON u.id = t.user_id
+    LEFT JOIN reply_counts n ON n.id = t.id) c",
+    sort.key()
+  );
+
+  let stmt = db_client
+    .prepare(&stmt)
+    .await
+    .map_err(|e| json!({"message": e.to_string()}))?;
+
+  db_client
+    .query(
+      &stmt,
+      &[
+        &post_id,
+        &root_ids,
+        &user_id.unwrap_or_default(),
+        &max_depth,
+      ],
+    )
+    .await
+    .map_err(|e| json!({"message": e.to_string()}))?
+    .into_iter()
+    .map(|r| FetchCommentsResponse::from_row(&r))
+    .collect::<Result<Vec<_>, _>>()
+}
+
 #[derive(Debug, Serialize)]
 struct FetchCommentsResponse {
   id: i32,
   body: String,
   comment_id: Option<i32>,
-  author: CommentAuthor,
+  author: Option<CommentAuthor>,
   created_at: NaiveDateTime,
+  edited_at: Option<NaiveDateTime>,
+  deleted: bool,
   replies: i64,
+  ups: i64,
+  downs: i64,
+  my_vote: i16,
+  sort_key: f64,
 }
 
 #[derive(Debug, Serialize)]
 pub struct FetchCommentsResponseParsed {
   id: i32,
   body: String,
-  author: CommentAuthor,
+  author: Option<CommentAuthor>,
   created_at: NaiveDateTime,
+  edited_at: Option<NaiveDateTime>,
+  deleted: bool,
+  score: i64,
+  my_vote: i16,
+  #[serde(skip)]
+  sort_key: f64,
   reply_count: i64,
   replies: Vec<FetchCommentsResponseParsed>,
+  load_more: Option<LoadMoreReplies>,
+}
+
+#[derive(Debug, Serialize)]
+struct LoadMoreReplies {
+  comment_id: i32,
+  hidden_replies: i64,
 }
+
 #[derive(Debug, Clone, Serialize)]
 struct CommentAuthor {
   id: i32,
@@ -382,6 +1341,12 @@ impl FetchCommentsResponse {
     let author_name = r.try_get::<&str, String>("author_name");
     let replies = r.try_get::<&str, i64>("replies");
     let created_at = r.try_get::<&str, NaiveDateTime>("created_at");
+    let edited_at = r.try_get::<&str, Option<NaiveDateTime>>("edited_at");
+    let deleted_at = r.try_get::<&str, Option<NaiveDateTime>>("deleted_at");
+    let ups = r.try_get::<&str, i64>("ups");
+    let downs = r.try_get::<&str, i64>("downs");
+    let my_vote = r.try_get::<&str, i16>("my_vote");
+    let sort_key = r.try_get::<&str, f64>("sort_key");
 
     match (
       id,
@@ -391,6 +1356,12 @@ impl FetchCommentsResponse {
       author_name,
       replies,
       created_at,
+      edited_at,
+      deleted_at,
+      ups,
+      downs,
+      my_vote,
+      sort_key,
     ) {
       (
         Ok(id),
@@ -400,15 +1371,30 @@ impl FetchCommentsResponse {
         Ok(author_name),
         Ok(replies),
         Ok(created_at),
+        Ok(edited_at),
+        Ok(deleted_at),
+        Ok(ups),
+        Ok(downs),
+        Ok(my_vote),
+        Ok(sort_key),
       ) => Ok(FetchCommentsResponse {
         id,
         body,
         comment_id,
         replies,
         created_at,
-        author: CommentAuthor {
-          id: author_id,
-          name: author_name,
+        edited_at,
+        deleted: deleted_at.is_some(),
+        ups,
+        downs,
+        my_vote,
+        sort_key,
+        author: match deleted_at {
+          Some(_) => None,
+          None => Some(CommentAuthor {
+            id: author_id,
+            name: author_name,
+          }),
         },
       }),
       _ => Err(json!({"message": "Error converting postgres to rust type"})),
@@ -416,48 +1402,134 @@ impl FetchCommentsResponse {
   }
 
   fn parse(
-    data: &Vec<FetchCommentsResponse>,
-    sort: &Option<Sort>,
+    data: &[FetchCommentsResponse],
+    root_ids: &[i32],
+    sort: &Sort,
+    max_depth: Option<i32>,
   ) -> Vec<FetchCommentsResponseParsed> {
-    let mut vec = Vec::new();
+    root_ids
+      .iter()
+      .filter_map(|id| data.iter().find(|d| d.id == *id))
+      .map(|d| d.to_parsed(data, 0, sort, max_depth))
+      .collect()
+  }
 
-    FetchCommentsResponse::add_reply(&mut vec, data, None, sort);
+  fn to_parsed(
+    &self,
+    data: &[FetchCommentsResponse],
+    depth: i32,
+    sort: &Sort,
+    max_depth: Option<i32>,
+  ) -> FetchCommentsResponseParsed {
+    let mut parsed = FetchCommentsResponseParsed {
+      id: self.id,
+      body: self.body.clone(),
+      author: self.author.clone(),
+      created_at: self.created_at,
+      edited_at: self.edited_at,
+      deleted: self.deleted,
+      score: self.ups - self.downs,
+      my_vote: self.my_vote,
+      sort_key: self.sort_key,
+      reply_count: self.replies,
+      replies: vec![],
+      load_more: None,
+    };
 
-    vec
+    match max_depth {
+      Some(m) if depth >= m => {
+        if self.replies > 0 {
+          parsed.load_more = Some(LoadMoreReplies {
+            comment_id: self.id,
+            hidden_replies: self.replies,
+          });
+        }
+      }
+      _ => FetchCommentsResponse::add_reply(
+        &mut parsed.replies,
+        data,
+        self.id,
+        depth + 1,
+        sort,
+        max_depth,
+      ),
+    }
+
+    parsed
   }
 
   fn add_reply(
     vec: &mut Vec<FetchCommentsResponseParsed>,
-    data: &Vec<FetchCommentsResponse>,
-    comment_id: Option<i32>,
-    sort: &Option<Sort>,
+    data: &[FetchCommentsResponse],
+    comment_id: i32,
+    depth: i32,
+    sort: &Sort,
+    max_depth: Option<i32>,
   ) {
-    let res =
+    vec.extend(
       data
         .iter()
-        .filter(|d| d.comment_id == comment_id)
-        .map(|d| FetchCommentsResponseParsed {
-          id: d.id,
-          body: d.body.clone(),
-          author: d.author.clone(),
-          created_at: d.created_at,
-          reply_count: d.replies,
-          replies: vec![],
-        });
-
-    for mut d in res {
-      FetchCommentsResponse::add_reply(&mut d.replies, data, Some(d.id), sort);
-      vec.push(d);
+        .filter(|d| d.comment_id == Some(comment_id))
+        .map(|d| d.to_parsed(data, depth, sort, max_depth)),
+    );
+
+    vec.sort_by(|a, b| {
+      let o = a.sort_key.total_cmp(&b.sort_key).then(a.id.cmp(&b.id));
+
+      match sort.is_ascending() {
+        true => o,
+        false => o.reverse(),
+      }
+    });
+  }
+}
+
+impl Sort {
+  fn key(&self) -> &'static str {
+    match self {
+      Sort::Latest | Sort::Oldest => "EXTRACT(EPOCH FROM created_at)",
+      Sort::Highest | Sort::Lowest => "replies",
+      Sort::Top => "ups - downs",
+      Sort::Controversial => {
+        "CASE WHEN ups <= 0 OR downs <= 0 THEN 0
+        ELSE POWER((ups + downs)::FLOAT8, LEAST(ups, downs)::FLOAT8 / GREATEST(ups, downs)) END"
+      }
+      Sort::Best => {
+        "CASE WHEN ups + downs = 0 THEN 0
+        ELSE (ups::FLOAT8 / (ups + downs) + 1.281551565545 ^ 2 / (2 * (ups + downs))
+          - 1.281551565545 * SQRT((ups::FLOAT8 / (ups + downs) * downs / (ups + downs)
+          + 1.281551565545 ^ 2 / (4 * (ups + downs))) / (ups + downs)))
+          / (1 + 1.281551565545 ^ 2 / (ups + downs)) END"
+      }
     }
+  }
 
-    match sort {
-      Some(s) => match s {
-        Sort::Highest => vec.sort_by(|a, b| b.reply_count.cmp(&a.reply_count)),
-        Sort::Lowest => vec.sort_by(|a, b| a.reply_count.cmp(&b.reply_count)),
-        Sort::Latest => vec.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
-        Sort::Oldest => vec.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+  fn add_pagination_statement(
+    &self,
+    stmt: &str,
+    pagination: &Pagination,
+    params: &mut Vec<Box<dyn ToSql + Sync>>,
+  ) -> String {
+    let key = match self {
+      Sort::Latest | Sort::Oldest => SortKey::Column {
+        column: "c.created_at",
+        id: "c.id",
+        name: "created_at",
       },
-      None => vec.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
-    }
+      _ => SortKey::Expr(self.key()),
+    };
+
+    pagination.add_statement(
+      stmt,
+      Clause::And,
+      &key,
+      self.is_ascending(),
+      Vec::new(),
+      params,
+    )
+  }
+
+  fn is_ascending(&self) -> bool {
+    matches!(self, Sort::Oldest | Sort::Lowest)
   }
 }
@@ -3,7 +3,9 @@ mod controllers;
 mod id;
 mod models;
 
-pub use models::FetchPostsResponse;
+pub use models::{
+  normalize_hashtag, parse_hashtag_list, FetchPosts, FetchPostsResponse, HashtagFilter, Sort,
+};
 
 pub fn view(cfg: &mut ServiceConfig) {
   cfg
@@ -2,14 +2,15 @@ use std::marker::PhantomData;
 
 use crate::api::{
   handler_utils::{
-    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithUserDetails,
+    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithDBTransaction,
+    WithUserDetails,
   },
+  pagination::{fetch_page, Clause, Page, Pagination, SortKey},
   UserAuthDetails,
 };
 use actix_web::http::StatusCode;
 use chrono::{NaiveDateTime, Utc};
-use deadpool_postgres::Client;
-use futures_util::{future, TryStreamExt};
+use deadpool_postgres::{Client, Transaction};
 use lazy_static::lazy_static;
 use postgres_types::{FromSql, ToSql};
 use rand::Rng;
@@ -33,7 +34,7 @@ pub struct CreatePostDetails<D, U, V> {
 
 #[derive(Debug, ToSql, FromSql)]
 #[postgres(name = "color")]
-enum Color {
+pub enum Color {
   #[postgres(name = "green")]
   Green,
   #[postgres(name = "blue")]
@@ -46,8 +47,8 @@ enum Color {
   Purple,
 }
 
-impl<D, U, V> CreatePostDetails<D, U, V> {
-  fn get_random_color(&self) -> Color {
+impl Color {
+  pub fn random() -> Color {
     match rand::thread_rng().gen_range(0..5) {
       0 => Color::Green,
       1 => Color::Blue,
@@ -59,13 +60,178 @@ impl<D, U, V> CreatePostDetails<D, U, V> {
   }
 }
 
+pub fn validate_title(title: &str) -> Result<String, (StatusCode, Value)> {
+  let title = title.trim();
+
+  if title.is_empty() {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "title", "message": "Post title has no content"}),
+    ));
+  }
+
+  if title.len() > 100 {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "title", "message": "Post title should not have more 100 characters"}),
+    ));
+  }
+
+  Ok(title.to_owned())
+}
+
+pub fn validate_body(body: &str) -> Result<String, (StatusCode, Value)> {
+  let body = body.trim();
+
+  if body.is_empty() {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "body", "message": "Post body has no content"}),
+    ));
+  }
+
+  if body.len() > 5000 {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "body", "message": "Post body should not have more 5000 characters"}),
+    ));
+  }
+
+  Ok(body.to_owned())
+}
+
+pub fn normalize_hashtag(hashtag: &str) -> String {
+  lazy_static! {
+    static ref RE: Result<Regex, regex::Error> = Regex::new(r"[^A-Za-z\s]+");
+  }
+
+  let mut s = String::from(hashtag.trim());
+
+  s.make_ascii_lowercase();
+
+  if RE.is_ok() {
+    s = RE.as_ref().unwrap().replace_all(s.as_str(), "").to_string();
+  }
+
+  s
+}
+
+pub fn validate_hashtags(hashtags: &[String]) -> Result<Vec<String>, (StatusCode, Value)> {
+  let hashtags: Vec<String> = hashtags
+    .iter()
+    .map(|s| normalize_hashtag(s))
+    .filter(|s| !s.is_empty())
+    .collect();
+
+  if hashtags.iter().any(|s| s.len() > 50) {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"message": "Hashtags should not be more than 50 characters"}),
+    ));
+  }
+
+  if hashtags.is_empty() {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": "hashtags", "message": "Please add hashtags"}),
+    ));
+  }
+
+  Ok(hashtags)
+}
+
+pub struct SyncPostHashtags<'a> {
+  pub db_client: &'a Transaction<'a>,
+  pub post_id: i32,
+  pub hashtags: &'a [String],
+}
+
+impl<'a> SyncPostHashtags<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    let colors: Vec<Color> = self.hashtags.iter().map(|_| Color::random()).collect();
+
+    self
+      .db_client
+      .execute(
+        &self.get_insert_hashtags_statement().await?,
+        &[&self.hashtags, &colors, &Utc::now().naive_utc()],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    self
+      .db_client
+      .execute(&self.get_delete_statement().await?, &[&self.post_id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    self
+      .db_client
+      .execute(
+        &self.get_insert_relationship_statement().await?,
+        &[&self.post_id, &self.hashtags],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  async fn get_insert_hashtags_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "INSERT INTO hashtags (name, color, created_at)
+      SELECT name, color, $3 FROM UNNEST($1::VARCHAR[], $2::color[]) t(name, color)
+      ON CONFLICT (name) DO NOTHING";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_delete_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "DELETE FROM posts_hashtags_relationship WHERE post_id = $1";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_insert_relationship_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "INSERT INTO posts_hashtags_relationship (post_id, hashtag_id)
+      SELECT $1, id FROM hashtags WHERE name = ANY($2)";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.db_client.prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+}
+
 impl<U, V> CreatePostDetails<NoDBClient, U, V> {
-  pub fn add_db_client(self, db_client: &Client) -> CreatePostDetails<WithDBClient, U, V> {
+  pub fn add_db_client<'a>(
+    self,
+    db_client: &'a Transaction<'a>,
+  ) -> CreatePostDetails<WithDBTransaction<'a>, U, V> {
     CreatePostDetails {
       title: self.title,
       hashtags: self.hashtags,
       body: self.body,
-      db_client: WithDBClient(db_client),
+      db_client: WithDBTransaction(db_client),
       user_details: self.user_details,
       validated: PhantomData,
     }
@@ -76,7 +242,7 @@ impl<D, V> CreatePostDetails<D, NoUserDetails, V> {
   pub fn add_user_details(
     self,
     user_details: &UserAuthDetails,
-  ) -> CreatePostDetails<D, WithUserDetails, V> {
+  ) -> CreatePostDetails<D, WithUserDetails<'_>, V> {
     CreatePostDetails {
       title: self.title,
       hashtags: self.hashtags,
@@ -88,85 +254,12 @@ impl<D, V> CreatePostDetails<D, NoUserDetails, V> {
   }
 }
 
-impl<'a> CreatePostDetails<WithDBClient<'a>, WithUserDetails<'a>, NotValidated> {
-  pub fn validate(
-    mut self,
-  ) -> Result<
-    CreatePostDetails<WithDBClient<'a>, WithUserDetails<'a>, Validated>,
-    (StatusCode, Value),
-  > {
-    self.title = self.title.trim().to_owned();
-    self.body = self.body.trim().to_owned();
-
-    if self.title.len() == 0 {
-      return Err((
-        StatusCode::BAD_REQUEST,
-        json!({"name": "title", "message": "Post title has no content"}),
-      ));
-    }
-
-    if self.body.len() == 0 {
-      return Err((
-        StatusCode::BAD_REQUEST,
-        json!({"name": "body", "message": "Post body has no content"}),
-      ));
-    }
-
-    if self.title.len() > 100 {
-      return Err((
-        StatusCode::BAD_REQUEST,
-        json!({"name": "title", "message": "Post title should not have more 100 characters"}),
-      ));
-    }
-    if self.body.len() > 5000 {
-      return Err((
-        StatusCode::BAD_REQUEST,
-        json!({"name": "body", "message": "Post body should not have more 5000 characters"}),
-      ));
-    }
-    lazy_static! {
-      static ref RE: Result<Regex, regex::Error> = Regex::new(r"[^A-Za-z\s]+");
-    }
-
-    let mut all_under_51 = true;
-
-    self.hashtags = self
-      .hashtags
-      .iter()
-      .map(|s| {
-        let mut s = String::from(s.trim());
-
-        s.make_ascii_lowercase();
-
-        if RE.is_ok() {
-          s = String::from(RE.as_ref().unwrap().replace_all(s.as_str(), "").to_string());
-        }
-
-        all_under_51 = all_under_51 && s.len() <= 50;
-
-        s
-      })
-      .filter(|s| s.len() > 0)
-      .collect();
-
-    if !all_under_51 {
-      return Err((
-        StatusCode::BAD_REQUEST,
-        json!({"message": "Hashtags should not be more than 50 characters"}),
-      ));
-    }
-
-    if self.hashtags.len() == 0 {
-      return Err((
-        StatusCode::BAD_REQUEST,
-        json!({"name": "hashtags", "message": "Please add hashtags"}),
-      ));
-    }
-
+impl<D, U> CreatePostDetails<D, U, NotValidated> {
+  pub fn validate(self) -> Result<CreatePostDetails<D, U, Validated>, (StatusCode, Value)> {
     Ok(CreatePostDetails {
-      title: self.title,
-      hashtags: self.hashtags,
-      body: self.body,
+      title: validate_title(&self.title)?,
+      body: validate_body(&self.body)?,
+      hashtags: validate_hashtags(&self.hashtags)?,
       db_client: self.db_client,
       user_details: self.user_details,
       validated: PhantomData,
@@ -180,8 +273,8 @@ impl<'a, D, V> CreatePostDetails<D, WithUserDetails<'a>, V> {
   }
 }
 
-impl<'a, U> CreatePostDetails<WithDBClient<'a>, U, Validated> {
-  fn get_db_client(&self) -> &'a Client {
+impl<'a, U> CreatePostDetails<WithDBTransaction<'a>, U, Validated> {
+  fn get_db_client(&self) -> &'a Transaction<'a> {
     self.db_client.0
   }
 
@@ -195,87 +288,19 @@ impl<'a, U> CreatePostDetails<WithDBClient<'a>, U, Validated> {
       )
     })
   }
-
-  async fn get_insert_hashtags_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let mut stmt = "INSERT INTO hashtags (name, color, created_at) VALUES ".to_owned();
-
-    let mut i = 0;
-    let n = self.hashtags.len() * 3;
-
-    while i < n {
-      stmt.push_str(&format!("(${}, ${}, ${})", i + 1, i + 2, i + 3));
-      if i + 3 != n {
-        stmt.push_str(",")
-      }
-      i = i + 3;
-    }
-
-    stmt += "ON CONFLICT (name) DO NOTHING";
-
-    self.get_db_client().prepare(&stmt).await.map_err(|e| {
-      (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        json!({"message": e.to_string()}),
-      )
-    })
-  }
-
-  fn get_insert_hashtags_params(&self) -> Vec<Box<dyn ToSql + Sync>> {
-    let mut v: Vec<Box<dyn ToSql + Sync>> = Vec::new();
-
-    self.hashtags.iter().for_each(|t| {
-      v.push(Box::new(t.to_owned()));
-      v.push(Box::new(self.get_random_color()));
-      v.push(Box::new(Utc::now().naive_utc()));
-    });
-
-    v
-  }
-
-  async fn get_insert_post_and_hashtags_ids_statement(
-    &self,
-  ) -> Result<Statement, (StatusCode, Value)> {
-    let mut stmt = "INSERT INTO posts_hashtags_relationship (post_id, hashtag_id) (SELECT $1, id FROM hashtags WHERE name IN (".to_owned();
-
-    let mut i = 1;
-    let n = self.hashtags.len() + 1;
-
-    while i < n {
-      i += 1;
-      stmt += &format!("${}", i);
-      if i != n {
-        stmt += ",";
-      }
-    }
-
-    stmt += "))";
-
-    self.get_db_client().prepare(&stmt).await.map_err(|e| {
-      (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        json!({"message": e.to_string()}),
-      )
-    })
-  }
-
-  fn get_insert_post_and_hashtags_ids_params(&self, post_id: &i32) -> Vec<Box<dyn ToSql + Sync>> {
-    let mut v: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(*post_id)];
-
-    self.hashtags.iter().for_each(|t_id| {
-      v.push(Box::new(String::from(t_id)));
-    });
-
-    v
-  }
 }
 
-impl<'a> CreatePostDetails<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
+impl<'a> CreatePostDetails<WithDBTransaction<'a>, WithUserDetails<'a>, Validated> {
   pub async fn create_post(&self) -> Result<i32, (StatusCode, Value)> {
-    let res = future::join(self.insert_post(), self.insert_hashtags()).await;
+    let post_id = self.insert_post().await?;
 
-    let post_id = res.0?;
-
-    self.insert_post_and_hashtags_ids(post_id).await?;
+    SyncPostHashtags {
+      db_client: self.get_db_client(),
+      post_id,
+      hashtags: &self.hashtags,
+    }
+    .exec()
+    .await?;
 
     Ok(post_id)
   }
@@ -299,7 +324,7 @@ impl<'a> CreatePostDetails<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
           json!({ "message": e.to_string() }),
         )
       })?
-      .get(0)
+      .first()
       .ok_or((
         StatusCode::NOT_FOUND,
         json!({ "message": "No id returned" }),
@@ -312,48 +337,6 @@ impl<'a> CreatePostDetails<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
         )
       })
   }
-
-  async fn insert_hashtags(&self) -> Result<(), (StatusCode, Value)> {
-    self
-      .get_db_client()
-      .query_raw(
-        &self.get_insert_hashtags_statement().await?,
-        self.get_insert_hashtags_params(),
-      )
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({"message": e.to_string()}),
-        )
-      })?
-      .try_collect::<Vec<Row>>()
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({"message": e.to_string()}),
-        )
-      })
-      .map(|_| ())
-  }
-
-  async fn insert_post_and_hashtags_ids(&self, post_id: i32) -> Result<(), (StatusCode, Value)> {
-    self
-      .get_db_client()
-      .query_raw(
-        &self.get_insert_post_and_hashtags_ids_statement().await?,
-        self.get_insert_post_and_hashtags_ids_params(&post_id),
-      )
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({"message": e.to_string()}),
-        )
-      })
-      .map(|_| ())
-  }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -361,7 +344,16 @@ pub struct FetchPosts<D, U, V> {
   sort: Option<Sort>,
   limit: Option<i64>,
   page: Option<i64>,
+  cursor: Option<String>,
+  now: Option<NaiveDateTime>,
   hashtag: Option<String>,
+  all_hashtags: Option<String>,
+  any_hashtags: Option<String>,
+  exclude_hashtags: Option<String>,
+  #[serde(skip)]
+  hashtag_filter: HashtagFilter,
+  #[serde(skip)]
+  pagination: Pagination,
   #[serde(skip_deserializing)]
   db_client: D,
   #[serde(skip_deserializing)]
@@ -370,13 +362,136 @@ pub struct FetchPosts<D, U, V> {
   validated: PhantomData<V>,
 }
 
+#[derive(Default, Debug)]
+pub struct HashtagFilter {
+  all: Vec<String>,
+  any: Vec<String>,
+  exclude: Vec<String>,
+}
+
+pub fn parse_hashtag_list(list: &Option<String>) -> Vec<String> {
+  let mut hashtags: Vec<String> = list
+    .as_deref()
+    .unwrap_or_default()
+    .split(',')
+    .map(normalize_hashtag)
+    .filter(|s| !s.is_empty())
+    .collect();
+
+  hashtags.sort();
+  hashtags.dedup();
+
+  hashtags
+}
+
+impl HashtagFilter {
+  pub fn new(
+    all: Vec<String>,
+    any: Vec<String>,
+    exclude: Vec<String>,
+  ) -> Result<HashtagFilter, (StatusCode, Value)> {
+    if all.len() + any.len() + exclude.len() > 20 {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "hashtags", "message": "Cannot filter by more than 20 hashtags"}),
+      ));
+    }
+
+    if let Some(h) = all.iter().chain(any.iter()).find(|h| exclude.contains(h)) {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "hashtags", "message": format!("Hashtag {h} cannot be both included and excluded")}),
+      ));
+    }
+
+    Ok(HashtagFilter { all, any, exclude })
+  }
+
+  pub fn add_having_statement(
+    &self,
+    stmt: &str,
+    params: &mut Vec<Box<dyn ToSql + Sync>>,
+  ) -> String {
+    params.push(Box::new(self.all.clone()));
+    params.push(Box::new(self.any.clone()));
+    params.push(Box::new(self.exclude.clone()));
+
+    let (all, any, exclude) = (params.len() - 2, params.len() - 1, params.len());
+
+    format!(
+      "{stmt}
+      HAVING ARRAY_AGG(t.name) @> ${all}::VARCHAR[]
+      AND (CARDINALITY(${any}::VARCHAR[]) = 0 OR ARRAY_AGG(t.name) && ${any}::VARCHAR[])
+      AND NOT ARRAY_AGG(t.name) && ${exclude}::VARCHAR[]"
+    )
+  }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
-enum Sort {
+pub enum Sort {
   Latest,
   Oldest,
   Highest,
   Lowest,
+  Hot,
+  Rising,
+}
+
+impl Sort {
+  pub fn add_pagination_statement(
+    &self,
+    stmt: &str,
+    clause: Clause,
+    pagination: &Pagination,
+    params: &mut Vec<Box<dyn ToSql + Sync>>,
+  ) -> String {
+    let mut conditions = Vec::new();
+
+    let key = match self {
+      Sort::Latest | Sort::Oldest => {
+        return pagination.add_statement(
+          stmt,
+          clause,
+          &SortKey::Column {
+            column: "p.created_at",
+            id: "p.id",
+            name: "created_at",
+          },
+          self.is_ascending(),
+          conditions,
+          params,
+        )
+      }
+      Sort::Highest | Sort::Lowest => "score".to_owned(),
+      Sort::Hot => "SIGN(score) * LOG(GREATEST(ABS(score), 1)::FLOAT8)
+        + (EXTRACT(EPOCH FROM created_at)::FLOAT8 - 1134028003) / 45000"
+        .to_owned(),
+      Sort::Rising => {
+        params.push(Box::new(pagination.now));
+        let now = format!("${}::TIMESTAMP", params.len());
+
+        conditions.push(format!("p.created_at > {now} - INTERVAL '1 day'"));
+
+        format!(
+          "score::FLOAT8 / POWER(EXTRACT(EPOCH FROM ({now} - created_at))::FLOAT8 / 3600 + 2, 1.5)"
+        )
+      }
+    };
+
+    pagination.add_statement(
+      stmt,
+      clause,
+      &SortKey::Expr(&key),
+      self.is_ascending(),
+      conditions,
+      params,
+    )
+  }
+
+  fn is_ascending(&self) -> bool {
+    matches!(self, Sort::Oldest | Sort::Lowest)
+  }
 }
 
 #[derive(Debug, Serialize)]
@@ -390,6 +505,10 @@ pub struct FetchPostsResponse {
   created_at: NaiveDateTime,
   comments: i64,
   saves: i64,
+  score: i64,
+  my_vote: i16,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  snippet: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -399,6 +518,13 @@ struct PostAuthor {
 }
 
 impl<D, U> FetchPosts<D, U, NotValidated> {
+  pub fn add_hashtag(self, hashtag: String) -> FetchPosts<D, U, NotValidated> {
+    FetchPosts {
+      hashtag: Some(hashtag),
+      ..self
+    }
+  }
+
   pub fn validate(self) -> Result<FetchPosts<D, U, Validated>, (StatusCode, Value)> {
     if let Some(s) = self.limit {
       if s > 50 {
@@ -409,11 +535,25 @@ impl<D, U> FetchPosts<D, U, NotValidated> {
       }
     }
 
+    let mut all = parse_hashtag_list(&self.all_hashtags);
+    all.extend(parse_hashtag_list(&self.hashtag));
+
     Ok(FetchPosts {
+      pagination: Pagination::new(self.limit, self.page, self.cursor.as_deref())?.at(self.now),
+      hashtag_filter: HashtagFilter::new(
+        all,
+        parse_hashtag_list(&self.any_hashtags),
+        parse_hashtag_list(&self.exclude_hashtags),
+      )?,
       sort: self.sort,
       limit: self.limit,
       page: self.page,
+      cursor: self.cursor,
+      now: self.now,
       hashtag: self.hashtag,
+      all_hashtags: self.all_hashtags,
+      any_hashtags: self.any_hashtags,
+      exclude_hashtags: self.exclude_hashtags,
       db_client: self.db_client,
       user_details: self.user_details,
       validated: PhantomData,
@@ -422,12 +562,19 @@ impl<D, U> FetchPosts<D, U, NotValidated> {
 }
 
 impl<U, V> FetchPosts<NoDBClient, U, V> {
-  pub fn add_db_client(self, db_client: &Client) -> FetchPosts<WithDBClient, U, V> {
+  pub fn add_db_client(self, db_client: &Client) -> FetchPosts<WithDBClient<'_>, U, V> {
     FetchPosts {
       sort: self.sort,
       limit: self.limit,
       page: self.page,
+      cursor: self.cursor,
+      now: self.now,
       hashtag: self.hashtag,
+      all_hashtags: self.all_hashtags,
+      any_hashtags: self.any_hashtags,
+      exclude_hashtags: self.exclude_hashtags,
+      hashtag_filter: self.hashtag_filter,
+      pagination: self.pagination,
       db_client: WithDBClient(db_client),
       user_details: self.user_details,
       validated: PhantomData,
@@ -439,12 +586,19 @@ impl<D, V> FetchPosts<D, NoUserDetails, V> {
   pub fn add_user_details(
     self,
     user_details: &UserAuthDetails,
-  ) -> FetchPosts<D, WithUserDetails, V> {
+  ) -> FetchPosts<D, WithUserDetails<'_>, V> {
     FetchPosts {
       sort: self.sort,
       limit: self.limit,
       page: self.page,
+      cursor: self.cursor,
+      now: self.now,
       hashtag: self.hashtag,
+      all_hashtags: self.all_hashtags,
+      any_hashtags: self.any_hashtags,
+      exclude_hashtags: self.exclude_hashtags,
+      hashtag_filter: self.hashtag_filter,
+      pagination: self.pagination,
       db_client: self.db_client,
       user_details: WithUserDetails(user_details),
       validated: PhantomData,
@@ -465,121 +619,70 @@ impl<'a, U, V> FetchPosts<WithDBClient<'a>, U, V> {
 }
 
 impl<'a> FetchPosts<WithDBClient<'a>, NoUserDetails, Validated> {
-  pub async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let mut stmt = "SELECT p.id, p.title, left(p.body, 100) body, u.id author_id, u.username author_name, 
-     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT s.*) saves FROM posts p 
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, left(p.body, 100) body, u.id author_id, u.username author_name, 
+     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT s.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score FROM posts p 
      INNER JOIN posts_hashtags_relationship r ON p.id = r.post_id 
      INNER JOIN hashtags t ON t.id = r.hashtag_id
      INNER JOIN users u ON u.id = p.user_id
      LEFT JOIN post_comments c ON p.id = c.post_id
      LEFT JOIN saved_posts s ON s.post_id = p.id
-     GROUP BY p.id, u.id
-     HAVING CASE WHEN $3 != '' THEN $3 = ANY(ARRAY_AGG(t.name)) ELSE 1 = 1 END
-     ".to_owned();
-
-    match self.sort.clone() {
-      Some(s) => match s {
-        Sort::Latest => stmt += " ORDER BY created_at DESC",
-        Sort::Oldest => stmt += " ORDER BY created_at ASC",
-        Sort::Highest => stmt = "WITH t(id, title, body, author_id, author_name, created_at, hashtags, comments, saves) AS ( ".to_owned() + &stmt + " ) SELECT t.*, (t.comments + 2 * t.saves) score FROM t ORDER BY score DESC, created_at DESC ",
-        Sort::Lowest => stmt = "WITH t(id, title, body, author_id, author_name, created_at, hashtags, comments, saves) AS ( ".to_owned() + &stmt + " ) SELECT t.*, (t.comments + 2 * t.saves) score FROM t ORDER BY score ASC, created_at DESC ",
-      },
-      None => stmt += " ORDER BY created_at DESC",
-    }
-
-    stmt += " LIMIT $1 OFFSET $2";
-
-    self.get_db_client().prepare(&stmt).await.map_err(|e| {
-      (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        json!({ "message": e.to_string() }),
-      )
-    })
-  }
-
-  pub async fn fetch_posts(&self) -> Result<Vec<FetchPostsResponse>, (StatusCode, Value)> {
-    self
-      .get_db_client()
-      .query(
-        &self.get_select_statement().await?,
-        &[
-          self.limit.as_ref().unwrap_or(&20),
-          &((self.page.as_ref().unwrap_or(&1) - &1) * self.limit.as_ref().unwrap_or(&20)),
-          self.hashtag.as_ref().unwrap_or(&"".to_owned()),
-        ],
-      )
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({
-            "message": e.to_string()
-          }),
-        )
-      })?
-      .into_iter()
-      .map(|r| FetchPostsResponse::from_row(&r))
-      .collect::<Result<Vec<_>, _>>()
+     GROUP BY p.id, u.id";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = Vec::new();
+
+    let stmt = self.hashtag_filter.add_having_statement(stmt, &mut params);
+
+    let stmt = self
+      .sort
+      .as_ref()
+      .unwrap_or(&Sort::Latest)
+      .add_pagination_statement(&stmt, Clause::And, &self.pagination, &mut params);
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
   }
 }
 
 impl<'a> FetchPosts<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
-  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let mut stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
-      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves FROM posts p 
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
+      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score,
+      COALESCE((SELECT v.value FROM post_votes v WHERE v.post_id = p.id AND v.user_id = $1), 0::SMALLINT) my_vote FROM posts p 
       INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id 
       INNER JOIN hashtags t ON t.id = r.hashtag_id 
       INNER JOIN users u ON u.id = p.user_id 
       LEFT JOIN saved_posts s ON s.post_id = p.id AND s.user_id = $1 
       LEFT JOIN saved_posts ss ON ss.post_id = p.id
       LEFT JOIN post_comments c ON p.id = c.post_id
-      GROUP BY p.id, u.id, s.post_id
-      HAVING CASE WHEN $4 != '' THEN $4 = ANY(ARRAY_AGG(t.name)) ELSE 1 = 1 END
-      ".to_owned();
-
-    match self.sort.clone() {
-      Some(s) => match s {
-        Sort::Latest => stmt += " ORDER BY created_at DESC",
-        Sort::Oldest => stmt += " ORDER BY created_at ASC",
-        Sort::Highest => stmt = "WITH t(id, title, body, author_id, author_name, saved, created_at, hashtags, comments, saves) AS ( ".to_owned() + &stmt + " ) SELECT t.*, (t.comments + 2 * t.saves) score FROM t ORDER BY score DESC, created_at DESC ",
-        Sort::Lowest => stmt = "WITH t(id, title, body, author_id, author_name, saved, created_at, hashtags, comments, saves) AS ( ".to_owned() + &stmt + " ) SELECT t.*, (t.comments + 2 * t.saves) score FROM t ORDER BY score ASC, created_at DESC ",
-      },
-      None => stmt += " ORDER BY created_at DESC",
-    }
-
-    stmt += " LIMIT $2 OFFSET $3";
-
-    self.get_db_client().prepare(&stmt).await.map_err(|e| {
-      (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        json!({"message": e.to_string()}),
-      )
-    })
-  }
-  pub async fn fetch_posts(&self) -> Result<Vec<FetchPostsResponse>, (StatusCode, Value)> {
-    self
-      .get_db_client()
-      .query(
-        &self.get_select_statement().await?,
-        &[
-          &self.get_user_details().id,
-          self.limit.as_ref().unwrap_or(&20),
-          &((self.page.as_ref().unwrap_or(&1) - &1) * self.limit.as_ref().unwrap_or(&20)),
-          self.hashtag.as_ref().unwrap_or(&"".to_owned()),
-        ],
-      )
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({
-            "message": e.to_string()
-          }),
-        )
-      })?
-      .into_iter()
-      .map(|r| FetchPostsResponse::from_row(&r))
-      .collect::<Result<Vec<_>, _>>()
+      GROUP BY p.id, u.id, s.post_id";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(self.get_user_details().id)];
+
+    let stmt = self.hashtag_filter.add_having_statement(stmt, &mut params);
+
+    let stmt = self
+      .sort
+      .as_ref()
+      .unwrap_or(&Sort::Latest)
+      .add_pagination_statement(&stmt, Clause::And, &self.pagination, &mut params);
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
   }
 }
 
@@ -593,8 +696,11 @@ impl FetchPostsResponse {
     let author_id = row.try_get::<&str, i32>("author_id");
     let comments = row.try_get::<&str, i64>("comments");
     let saves = row.try_get::<&str, i64>("saves");
+    let score = row.try_get::<&str, i64>("score");
+    let my_vote = row.try_get::<&str, i16>("my_vote");
     let saved = row.try_get::<&str, bool>("saved");
     let created_at = row.try_get::<&str, NaiveDateTime>("created_at");
+    let snippet = row.try_get::<&str, Option<String>>("snippet");
 
     match (
       id,
@@ -604,6 +710,7 @@ impl FetchPostsResponse {
       author_id,
       comments,
       saves,
+      score,
       author_name,
       created_at,
     ) {
@@ -615,6 +722,7 @@ impl FetchPostsResponse {
         Ok(author_id),
         Ok(comments),
         Ok(saves),
+        Ok(score),
         Ok(author_name),
         Ok(created_at),
       ) => Ok(FetchPostsResponse {
@@ -638,6 +746,9 @@ impl FetchPostsResponse {
         saved: saved.unwrap_or(false),
         comments,
         saves,
+        score,
+        my_vote: my_vote.unwrap_or(0),
+        snippet: snippet.ok().flatten(),
       }),
       _ => Err((
         StatusCode::INTERNAL_SERVER_ERROR,
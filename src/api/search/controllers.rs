@@ -0,0 +1,65 @@
+use actix_web::{
+  web::{self, Query},
+  HttpResponse,
+};
+use deadpool_postgres::{Client, Pool};
+use serde_json::json;
+
+use super::models;
+
+use crate::api::{
+  handler_utils::{NoDBClient, NoUserDetails, NotValidated},
+  UserAuth,
+};
+
+pub async fn search_posts(
+  db_pool: web::Data<Pool>,
+  user_details: UserAuth,
+  query: Query<models::SearchPosts<NoDBClient, NoUserDetails, NotValidated>>,
+) -> HttpResponse {
+  let user_details = user_details.details;
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client: Client = db_client_res.unwrap();
+
+  let query = query.into_inner().add_db_client(&db_client).validate();
+
+  if let Err((s, v)) = query {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error":v
+    }));
+  }
+
+  let query = query.unwrap();
+
+  let res = if let Some(u) = user_details {
+    query.add_user_details(&u).search().await
+  } else {
+    query.search().await
+  };
+
+  match res {
+    Ok(v) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": v.data,
+      "next_cursor": v.next_cursor,
+      "prev_cursor": v.prev_cursor
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
@@ -0,0 +1,8 @@
+mod controllers;
+mod models;
+
+use actix_web::web::{self, ServiceConfig};
+
+pub fn view(cfg: &mut ServiceConfig) {
+  cfg.route("", web::get().to(controllers::search_posts));
+}
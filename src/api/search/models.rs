@@ -0,0 +1,278 @@
+use std::marker::PhantomData;
+
+use actix_web::http::StatusCode;
+use chrono::NaiveDate;
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::types::ToSql;
+
+use crate::api::{
+  handler_utils::{
+    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithUserDetails,
+  },
+  pagination::{fetch_page, Clause, Page, Pagination, SortKey},
+  posts::{parse_hashtag_list, FetchPostsResponse, HashtagFilter},
+  UserAuthDetails,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SearchPosts<D, U, V> {
+  q: String,
+  hashtag: Option<String>,
+  all_hashtags: Option<String>,
+  any_hashtags: Option<String>,
+  exclude_hashtags: Option<String>,
+  author: Option<String>,
+  from: Option<NaiveDate>,
+  to: Option<NaiveDate>,
+  limit: Option<i64>,
+  page: Option<i64>,
+  cursor: Option<String>,
+  #[serde(skip)]
+  hashtag_filter: HashtagFilter,
+  #[serde(skip)]
+  pagination: Pagination,
+  #[serde(skip_deserializing)]
+  db_client: D,
+  #[serde(skip_deserializing)]
+  user_details: U,
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl<D, U> SearchPosts<D, U, NotValidated> {
+  pub fn validate(self) -> Result<SearchPosts<D, U, Validated>, (StatusCode, Value)> {
+    let q = self.q.trim();
+
+    if q.is_empty() {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "q", "message": "Search query has no content"}),
+      ));
+    }
+
+    if q.len() > 200 {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"name": "q", "message": "Search query should not have more than 200 characters"}),
+      ));
+    }
+
+    if let (Some(from), Some(to)) = (self.from, self.to) {
+      if from > to {
+        return Err((
+          StatusCode::BAD_REQUEST,
+          json!({"name": "from", "message": "Start date should not be after end date"}),
+        ));
+      }
+    }
+
+    if let Some(s) = self.limit {
+      if s > 50 {
+        return Err((
+          StatusCode::BAD_REQUEST,
+          json!({"message": "Cannot retrieve more than 50 posts"}),
+        ));
+      }
+    }
+
+    let mut all = parse_hashtag_list(&self.all_hashtags);
+    all.extend(parse_hashtag_list(&self.hashtag));
+
+    Ok(SearchPosts {
+      pagination: Pagination::new(self.limit, self.page, self.cursor.as_deref())?,
+      hashtag_filter: HashtagFilter::new(
+        all,
+        parse_hashtag_list(&self.any_hashtags),
+        parse_hashtag_list(&self.exclude_hashtags),
+      )?,
+      q: q.to_owned(),
+      hashtag: self.hashtag,
+      all_hashtags: self.all_hashtags,
+      any_hashtags: self.any_hashtags,
+      exclude_hashtags: self.exclude_hashtags,
+      author: self
+        .author
+        .map(|a| a.trim().to_owned())
+        .filter(|a| !a.is_empty()),
+      from: self.from,
+      to: self.to,
+      limit: self.limit,
+      page: self.page,
+      cursor: self.cursor,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<U, V> SearchPosts<NoDBClient, U, V> {
+  pub fn add_db_client(self, db_client: &Client) -> SearchPosts<WithDBClient<'_>, U, V> {
+    SearchPosts {
+      q: self.q,
+      hashtag: self.hashtag,
+      all_hashtags: self.all_hashtags,
+      any_hashtags: self.any_hashtags,
+      exclude_hashtags: self.exclude_hashtags,
+      author: self.author,
+      from: self.from,
+      to: self.to,
+      limit: self.limit,
+      page: self.page,
+      cursor: self.cursor,
+      hashtag_filter: self.hashtag_filter,
+      pagination: self.pagination,
+      db_client: WithDBClient(db_client),
+      user_details: self.user_details,
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<D, V> SearchPosts<D, NoUserDetails, V> {
+  pub fn add_user_details(
+    self,
+    user_details: &UserAuthDetails,
+  ) -> SearchPosts<D, WithUserDetails<'_>, V> {
+    SearchPosts {
+      q: self.q,
+      hashtag: self.hashtag,
+      all_hashtags: self.all_hashtags,
+      any_hashtags: self.any_hashtags,
+      exclude_hashtags: self.exclude_hashtags,
+      author: self.author,
+      from: self.from,
+      to: self.to,
+      limit: self.limit,
+      page: self.page,
+      cursor: self.cursor,
+      hashtag_filter: self.hashtag_filter,
+      pagination: self.pagination,
+      db_client: self.db_client,
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a, D, V> SearchPosts<D, WithUserDetails<'a>, V> {
+  fn get_user_details(&self) -> &'a UserAuthDetails {
+    self.user_details.0
+  }
+}
+
+impl<'a, U, V> SearchPosts<WithDBClient<'a>, U, V> {
+  fn get_db_client(&self) -> &'a Client {
+    self.db_client.0
+  }
+
+  fn get_filter_params(&self) -> Vec<Box<dyn ToSql + Sync>> {
+    vec![
+      Box::new(self.q.clone()),
+      Box::new(self.author.clone()),
+      Box::new(self.from),
+      Box::new(self.to),
+    ]
+  }
+}
+
+impl<'a> SearchPosts<WithDBClient<'a>, NoUserDetails, Validated> {
+  pub async fn search(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, left(p.body, 100) body, u.id author_id, u.username author_name,
+     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT s.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score, m.rank, m.snippet FROM posts p
+     CROSS JOIN websearch_to_tsquery('english', $1) q
+     CROSS JOIN LATERAL (SELECT ts_rank(p.search_vector, q) + 0.5 * COALESCE(MAX(ts_rank(mc.search_vector, q)), 0) rank,
+       ts_headline('english', REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+         CASE WHEN p.search_vector @@ q THEN p.title || ' ' || p.body
+         ELSE (ARRAY_AGG(mc.body ORDER BY ts_rank(mc.search_vector, q) DESC))[1] END,
+         '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'), '''', '&#39;'), q,
+         'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2') snippet
+       FROM post_comments mc WHERE mc.post_id = p.id AND mc.deleted_at IS NULL AND mc.search_vector @@ q) m
+     INNER JOIN posts_hashtags_relationship r ON p.id = r.post_id
+     INNER JOIN hashtags t ON t.id = r.hashtag_id
+     INNER JOIN users u ON u.id = p.user_id
+     LEFT JOIN post_comments c ON p.id = c.post_id
+     LEFT JOIN saved_posts s ON s.post_id = p.id
+     WHERE (p.search_vector @@ q OR p.id IN (SELECT sc.post_id FROM post_comments sc WHERE sc.deleted_at IS NULL AND sc.search_vector @@ q))
+     AND ($2::TEXT IS NULL OR LOWER(u.username) = LOWER($2))
+     AND ($3::DATE IS NULL OR p.created_at >= $3::DATE)
+     AND ($4::DATE IS NULL OR p.created_at < $4::DATE + 1)
+     GROUP BY p.id, u.id, m.rank, m.snippet";
+
+    let mut params = self.get_filter_params();
+
+    let stmt = self.hashtag_filter.add_having_statement(stmt, &mut params);
+
+    let stmt = self.pagination.add_statement(
+      &stmt,
+      Clause::And,
+      &SortKey::Expr("rank"),
+      false,
+      Vec::new(),
+      &mut params,
+    );
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
+  }
+}
+
+impl<'a> SearchPosts<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn search(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name,
+      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score,
+      COALESCE((SELECT v.value FROM post_votes v WHERE v.post_id = p.id AND v.user_id = $5), 0::SMALLINT) my_vote, m.rank, m.snippet FROM posts p
+      CROSS JOIN websearch_to_tsquery('english', $1) q
+      CROSS JOIN LATERAL (SELECT ts_rank(p.search_vector, q) + 0.5 * COALESCE(MAX(ts_rank(mc.search_vector, q)), 0) rank,
+        ts_headline('english', REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+          CASE WHEN p.search_vector @@ q THEN p.title || ' ' || p.body
+          ELSE (ARRAY_AGG(mc.body ORDER BY ts_rank(mc.search_vector, q) DESC))[1] END,
+          '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'), '''', '&#39;'), q,
+          'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2') snippet
+        FROM post_comments mc WHERE mc.post_id = p.id AND mc.deleted_at IS NULL AND mc.search_vector @@ q) m
+      INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id
+      INNER JOIN hashtags t ON t.id = r.hashtag_id
+      INNER JOIN users u ON u.id = p.user_id
+      LEFT JOIN saved_posts s ON s.post_id = p.id AND s.user_id = $5
+      LEFT JOIN saved_posts ss ON ss.post_id = p.id
+      LEFT JOIN post_comments c ON p.id = c.post_id
+      WHERE (p.search_vector @@ q OR p.id IN (SELECT sc.post_id FROM post_comments sc WHERE sc.deleted_at IS NULL AND sc.search_vector @@ q))
+      AND ($2::TEXT IS NULL OR LOWER(u.username) = LOWER($2))
+      AND ($3::DATE IS NULL OR p.created_at >= $3::DATE)
+      AND ($4::DATE IS NULL OR p.created_at < $4::DATE + 1)
+      GROUP BY p.id, u.id, s.post_id, m.rank, m.snippet";
+
+    let mut params = self.get_filter_params();
+    params.push(Box::new(self.get_user_details().id));
+
+    let stmt = self.hashtag_filter.add_having_statement(stmt, &mut params);
+
+    let stmt = self.pagination.add_statement(
+      &stmt,
+      Clause::And,
+      &SortKey::Expr("rank"),
+      false,
+      Vec::new(),
+      &mut params,
+    );
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
+  }
+}
@@ -1 +1,69 @@
+use actix_web::{
+  web::{Data, Json},
+  HttpResponse,
+};
+use deadpool_postgres::Pool;
+use serde_json::json;
 
+use crate::api::{
+  handler_utils::{NoDBClient, NoUserDetails, NotValidated},
+  UserAuth,
+};
+
+use super::models::UpdateProfile;
+
+pub async fn update_profile(
+  user_auth: UserAuth,
+  body: Json<UpdateProfile<NoDBClient, NoUserDetails, NotValidated>>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  if user_auth.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_auth.details.unwrap();
+
+  let body = body.into_inner().validate();
+
+  if let Err((s, v)) = body {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .unwrap()
+    .add_details(&db_client, &user_details)
+    .exec()
+    .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
@@ -1,5 +1,5 @@
 use actix_web::{
-  web::{Data, Path},
+  web::{Data, Path, Query},
   HttpResponse,
 };
 
@@ -9,13 +9,18 @@ use serde_json::json;
 
 use crate::api::{
   handler_utils::{NoDBClient, NoUserDetails},
+  pagination::PaginationQuery,
   UserAuth,
 };
 
-use super::models::{FetchPostsCreatedByUser, FetchPostsSavedByUser, FetchUserDetails};
+use super::models::{
+  FetchFollowersOfUser, FetchHashtagsFollowedByUser, FetchPostsCreatedByUser,
+  FetchPostsSavedByUser, FetchUserDetails, FetchUsersFollowedByUser, FollowUser,
+};
 
 pub async fn fetch_user(
-  body: Path<FetchUserDetails<NoDBClient>>,
+  body: Path<FetchUserDetails<NoDBClient, NoUserDetails>>,
+  user_auth: UserAuth,
   db_pool: Data<Pool>,
 ) -> HttpResponse {
   let db_client_res = db_pool.get().await;
@@ -29,7 +34,14 @@ pub async fn fetch_user(
 
   let db_client = db_client_res.unwrap();
 
-  let res = body.into_inner().add_db_client(&db_client).fetch().await;
+  let user_details = user_auth.details;
+
+  let body = body.into_inner().add_db_client(&db_client);
+
+  let res = match user_details {
+    Some(u) => body.add_user_details(&u).fetch().await,
+    None => body.fetch().await,
+  };
 
   match res {
     Ok(data) => HttpResponse::Ok().json(json!({
@@ -51,9 +63,20 @@ pub async fn fetch_user(
 
 pub async fn fetch_posts_created_by_user(
   body: Path<FetchPostsCreatedByUser<NoDBClient, NoUserDetails>>,
+  query: Query<PaginationQuery>,
   user_auth: UserAuth,
   db_pool: Data<Pool>,
 ) -> HttpResponse {
+  let pagination = query.into_inner().validate();
+
+  if let Err((s, v)) = pagination {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
   let db_client_res = db_pool.get().await;
 
   if let Err(e) = db_client_res {
@@ -67,7 +90,10 @@ pub async fn fetch_posts_created_by_user(
 
   let user_details = user_auth.details;
 
-  let body = body.into_inner().add_db_client(&db_client);
+  let body = body
+    .into_inner()
+    .add_pagination(pagination.unwrap())
+    .add_db_client(&db_client);
 
   let res = match user_details {
     Some(u) => body.add_user_details(&u).fetch_posts().await,
@@ -75,9 +101,11 @@ pub async fn fetch_posts_created_by_user(
   };
 
   match res {
-    Ok(data) => HttpResponse::Ok().json(json!({
+    Ok(page) => HttpResponse::Ok().json(json!({
       "success": true,
-      "data": data,
+      "data": page.data,
+      "next_cursor": page.next_cursor,
+      "prev_cursor": page.prev_cursor,
     })),
 
     Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
@@ -94,9 +122,20 @@ pub async fn fetch_posts_created_by_user(
 
 pub async fn fetch_posts_saved_by_user(
   body: Path<FetchPostsSavedByUser<NoDBClient, NoUserDetails>>,
+  query: Query<PaginationQuery>,
   user_auth: UserAuth,
   db_pool: Data<Pool>,
 ) -> HttpResponse {
+  let pagination = query.into_inner().validate();
+
+  if let Err((s, v)) = pagination {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
   let db_client_res = db_pool.get().await;
 
   if let Err(e) = db_client_res {
@@ -110,13 +149,53 @@ pub async fn fetch_posts_saved_by_user(
 
   let user_details = user_auth.details;
 
-  let body = body.into_inner().add_db_client(&db_client);
+  let body = body
+    .into_inner()
+    .add_pagination(pagination.unwrap())
+    .add_db_client(&db_client);
 
   let res = match user_details {
     Some(u) => body.add_user_details(&u).fetch_posts().await,
     None => body.fetch_posts().await,
   };
 
+  match res {
+    Ok(page) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": page.data,
+      "next_cursor": page.next_cursor,
+      "prev_cursor": page.prev_cursor,
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": {
+        "status": s.as_u16(),
+        "message": v["message"],
+        "name": v["name"]
+      }
+    })),
+  }
+}
+
+pub async fn fetch_followed_hashtags(
+  body: Path<FetchHashtagsFollowedByUser<NoDBClient>>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body.into_inner().add_db_client(&db_client).fetch().await;
+
   match res {
     Ok(data) => HttpResponse::Ok().json(json!({
       "success": true,
@@ -134,3 +213,205 @@ pub async fn fetch_posts_saved_by_user(
     })),
   }
 }
+
+pub async fn fetch_followers(
+  body: Path<FetchFollowersOfUser<NoDBClient>>,
+  query: Query<PaginationQuery>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let pagination = query.into_inner().validate();
+
+  if let Err((s, v)) = pagination {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_pagination(pagination.unwrap())
+    .add_db_client(&db_client)
+    .fetch()
+    .await;
+
+  match res {
+    Ok(page) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": page.data,
+      "next_cursor": page.next_cursor,
+      "prev_cursor": page.prev_cursor,
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": {
+        "status": s.as_u16(),
+        "message": v["message"],
+        "name": v["name"]
+      }
+    })),
+  }
+}
+
+pub async fn fetch_following(
+  body: Path<FetchUsersFollowedByUser<NoDBClient>>,
+  query: Query<PaginationQuery>,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  let pagination = query.into_inner().validate();
+
+  if let Err((s, v)) = pagination {
+    return HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    }));
+  }
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = body
+    .into_inner()
+    .add_pagination(pagination.unwrap())
+    .add_db_client(&db_client)
+    .fetch()
+    .await;
+
+  match res {
+    Ok(page) => HttpResponse::Ok().json(json!({
+      "success": true,
+      "data": page.data,
+      "next_cursor": page.next_cursor,
+      "prev_cursor": page.prev_cursor,
+    })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": {
+        "status": s.as_u16(),
+        "message": v["message"],
+        "name": v["name"]
+      }
+    })),
+  }
+}
+
+pub async fn follow_user(
+  user_id: Path<i32>,
+  user_auth: UserAuth,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  if user_auth.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_auth.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = FollowUser {
+    user_details,
+    db_client: &db_client,
+    user_id: user_id.into_inner(),
+  }
+  .exec()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
+
+pub async fn unfollow_user(
+  user_id: Path<i32>,
+  user_auth: UserAuth,
+  db_pool: Data<Pool>,
+) -> HttpResponse {
+  if user_auth.details.is_none() {
+    return HttpResponse::Forbidden().json(json!({
+      "success": false,
+      "message": "User not signed in",
+      "error": {
+        "name": "re-auth",
+        "message": "User not signed in"
+      }
+    }));
+  };
+
+  let user_details = user_auth.details.unwrap();
+
+  let db_client_res = db_pool.get().await;
+
+  if let Err(e) = db_client_res {
+    return HttpResponse::InternalServerError().json(json!({
+      "success": false,
+      "message": e.to_string(),
+    }));
+  }
+
+  let db_client = db_client_res.unwrap();
+
+  let res = FollowUser {
+    user_details,
+    db_client: &db_client,
+    user_id: user_id.into_inner(),
+  }
+  .exec_reverse()
+  .await;
+
+  match res {
+    Ok(_) => HttpResponse::Ok().json(json!({ "success": true })),
+
+    Err((s, v)) => HttpResponse::Ok().status(s).json(json!({
+      "success": false,
+      "message": v["message"],
+      "error": v
+    })),
+  }
+}
@@ -13,4 +13,12 @@ pub fn view(cfg: &mut ServiceConfig) {
     "/saves",
     web::get().to(controllers::fetch_posts_saved_by_user),
   );
+  cfg.route(
+    "/followed-hashtags",
+    web::get().to(controllers::fetch_followed_hashtags),
+  );
+  cfg.route("/follow", web::post().to(controllers::follow_user));
+  cfg.route("/follow", web::delete().to(controllers::unfollow_user));
+  cfg.route("/followers", web::get().to(controllers::fetch_followers));
+  cfg.route("/following", web::get().to(controllers::fetch_following));
 }
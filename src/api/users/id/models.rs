@@ -1,42 +1,88 @@
 use actix_web::http::StatusCode;
+use chrono::{NaiveDateTime, Utc};
 use deadpool_postgres::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use tokio_postgres::types::ToSql;
 use tokio_postgres::{Row, Statement};
 
 use crate::api::{
   handler_utils::{NoDBClient, NoUserDetails, WithDBClient, WithUserDetails},
-  posts::FetchPostsResponse,
+  pagination::{fetch_page, Clause, Page, Pagination, SortKey},
+  posts::{FetchPostsResponse, Sort},
   UserAuthDetails,
 };
 
 #[derive(Deserialize)]
-pub struct FetchUserDetails<D> {
+pub struct FetchUserDetails<D, U> {
   user_id: i32,
   #[serde(skip_deserializing)]
   db_client: D,
+  #[serde(skip_deserializing)]
+  user_details: U,
 }
 
 #[derive(Serialize)]
 pub struct UserDetails {
   id: i32,
   username: String,
+  display_name: Option<String>,
+  bio: Option<String>,
+  website: Option<String>,
+  location: Option<String>,
+  avatar_url: Option<String>,
+  joined_at: NaiveDateTime,
+  posts: i64,
+  comments: i64,
+  saves_received: i64,
+  followers: i64,
+  following: i64,
+  is_following: bool,
 }
 
-impl<'a> FetchUserDetails<NoDBClient> {
-  pub fn add_db_client(self, db_client: &'a Client) -> FetchUserDetails<WithDBClient<'a>> {
+impl<'a, U> FetchUserDetails<NoDBClient, U> {
+  pub fn add_db_client(self, db_client: &'a Client) -> FetchUserDetails<WithDBClient<'a>, U> {
     FetchUserDetails {
       user_id: self.user_id,
       db_client: WithDBClient(db_client),
+      user_details: self.user_details,
+    }
+  }
+}
+
+impl<'a, D> FetchUserDetails<D, NoUserDetails> {
+  pub fn add_user_details(
+    self,
+    user_details: &'a UserAuthDetails,
+  ) -> FetchUserDetails<D, WithUserDetails<'a>> {
+    FetchUserDetails {
+      user_id: self.user_id,
+      db_client: self.db_client,
+      user_details: WithUserDetails(user_details),
     }
   }
 }
 
-impl<'a> FetchUserDetails<WithDBClient<'a>> {
+impl<'a> FetchUserDetails<WithDBClient<'a>, NoUserDetails> {
   pub async fn fetch(&self) -> Result<UserDetails, (StatusCode, Value)> {
+    self.fetch_for(None).await
+  }
+}
+
+impl<'a> FetchUserDetails<WithDBClient<'a>, WithUserDetails<'a>> {
+  pub async fn fetch(&self) -> Result<UserDetails, (StatusCode, Value)> {
+    self.fetch_for(Some(self.user_details.0.id)).await
+  }
+}
+
+impl<'a, U> FetchUserDetails<WithDBClient<'a>, U> {
+  async fn fetch_for(&self, viewer_id: Option<i32>) -> Result<UserDetails, (StatusCode, Value)> {
     self
       .get_db_client()
-      .query(&self.get_select_statement().await?, &[&self.user_id])
+      .query(
+        &self.get_select_statement().await?,
+        &[&self.user_id, &viewer_id],
+      )
       .await
       .map_err(|e| {
         (
@@ -54,7 +100,15 @@ impl<'a> FetchUserDetails<WithDBClient<'a>> {
   }
 
   async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let stmt = "SELECT id, username FROM users WHERE id = $1";
+    let stmt = "SELECT u.id, u.username, u.display_name, u.bio, u.website, u.location, u.avatar_url,
+      u.created_at joined_at,
+      (SELECT COUNT(*) FROM posts p WHERE p.user_id = u.id) posts,
+      (SELECT COUNT(*) FROM post_comments c WHERE c.user_id = u.id AND c.deleted_at IS NULL) comments,
+      (SELECT COUNT(*) FROM saved_posts s INNER JOIN posts p ON p.id = s.post_id WHERE p.user_id = u.id) saves_received,
+      (SELECT COUNT(*) FROM user_follows f WHERE f.following_id = u.id) followers,
+      (SELECT COUNT(*) FROM user_follows f WHERE f.follower_id = u.id) following,
+      EXISTS (SELECT 1 FROM user_follows f WHERE f.follower_id = $2 AND f.following_id = u.id) is_following
+      FROM users u WHERE u.id = $1";
 
     self.get_db_client().prepare(stmt).await.map_err(|e| {
       (
@@ -73,9 +127,56 @@ impl UserDetails {
   pub fn from_row(row: &Row) -> Result<UserDetails, (StatusCode, Value)> {
     let id = row.try_get::<&str, i32>("id");
     let username = row.try_get::<&str, String>("username");
-
-    match (id, username) {
-      (Ok(id), Ok(username)) => Ok(UserDetails { id, username }),
+    let display_name = row.try_get::<&str, Option<String>>("display_name");
+    let bio = row.try_get::<&str, Option<String>>("bio");
+    let website = row.try_get::<&str, Option<String>>("website");
+    let location = row.try_get::<&str, Option<String>>("location");
+    let avatar_url = row.try_get::<&str, Option<String>>("avatar_url");
+    let joined_at = row.try_get::<&str, NaiveDateTime>("joined_at");
+    let posts = row.try_get::<&str, i64>("posts");
+    let comments = row.try_get::<&str, i64>("comments");
+    let saves_received = row.try_get::<&str, i64>("saves_received");
+    let followers = row.try_get::<&str, i64>("followers");
+    let following = row.try_get::<&str, i64>("following");
+    let is_following = row.try_get::<&str, bool>("is_following");
+
+    match (
+      id,
+      username,
+      joined_at,
+      posts,
+      comments,
+      saves_received,
+      followers,
+      following,
+      is_following,
+    ) {
+      (
+        Ok(id),
+        Ok(username),
+        Ok(joined_at),
+        Ok(posts),
+        Ok(comments),
+        Ok(saves_received),
+        Ok(followers),
+        Ok(following),
+        Ok(is_following),
+      ) => Ok(UserDetails {
+        id,
+        username,
+        display_name: display_name.ok().flatten(),
+        bio: bio.ok().flatten(),
+        website: website.ok().flatten(),
+        location: location.ok().flatten(),
+        avatar_url: avatar_url.ok().flatten(),
+        joined_at,
+        posts,
+        comments,
+        saves_received,
+        followers,
+        following,
+        is_following,
+      }),
       _ => Err((
         StatusCode::INTERNAL_SERVER_ERROR,
         json!({"message":"Error converting postgres types" }),
@@ -88,6 +189,8 @@ impl UserDetails {
 pub struct FetchPostsCreatedByUser<D, U> {
   user_id: i32,
   #[serde(skip_deserializing)]
+  pagination: Pagination,
+  #[serde(skip_deserializing)]
   db_client: D,
   #[serde(skip_deserializing)]
   user_details: U,
@@ -100,6 +203,7 @@ impl<'a, U> FetchPostsCreatedByUser<NoDBClient, U> {
   ) -> FetchPostsCreatedByUser<WithDBClient<'a>, U> {
     FetchPostsCreatedByUser {
       user_id: self.user_id,
+      pagination: self.pagination,
       db_client: WithDBClient(db_client),
       user_details: self.user_details,
     }
@@ -113,6 +217,7 @@ impl<'a, D> FetchPostsCreatedByUser<D, NoUserDetails> {
   ) -> FetchPostsCreatedByUser<D, WithUserDetails<'a>> {
     FetchPostsCreatedByUser {
       user_id: self.user_id,
+      pagination: self.pagination,
       db_client: self.db_client,
       user_details: WithUserDetails(user_details),
     }
@@ -125,6 +230,12 @@ impl<'a, D> FetchPostsCreatedByUser<D, WithUserDetails<'a>> {
   }
 }
 
+impl<D, U> FetchPostsCreatedByUser<D, U> {
+  pub fn add_pagination(self, pagination: Pagination) -> FetchPostsCreatedByUser<D, U> {
+    FetchPostsCreatedByUser { pagination, ..self }
+  }
+}
+
 impl<'a, U> FetchPostsCreatedByUser<WithDBClient<'a>, U> {
   fn get_db_client(&self) -> &'a Client {
     self.db_client.0
@@ -132,66 +243,40 @@ impl<'a, U> FetchPostsCreatedByUser<WithDBClient<'a>, U> {
 }
 
 impl<'a> FetchPostsCreatedByUser<WithDBClient<'a>, NoUserDetails> {
-  pub async fn fetch_posts(&self) -> Result<Vec<FetchPostsResponse>, (StatusCode, Value)> {
-    self
-      .get_db_client()
-      .query(&self.get_select_statement().await?, &[&self.user_id])
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({"message": e.to_string()}),
-        )
-      })?
-      .into_iter()
-      .map(|r| FetchPostsResponse::from_row(&r))
-      .collect::<Result<Vec<_>, _>>()
-  }
-
-  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let stmt ="SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
-     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT s.*) saves FROM posts p 
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
+     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT s.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score FROM posts p 
      INNER JOIN posts_hashtags_relationship r ON p.id = r.post_id 
      INNER JOIN hashtags t ON t.id = r.hashtag_id
      INNER JOIN users u ON u.id = p.user_id
      LEFT JOIN post_comments c ON p.id = c.post_id
      LEFT JOIN saved_posts s ON s.post_id = p.id
      WHERE u.id = $1
-     GROUP BY p.id, u.id
-     ORDER BY created_at DESC";
+     GROUP BY p.id, u.id";
 
-    self.get_db_client().prepare(stmt).await.map_err(|e| {
-      (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        json!({"message": e.to_string()}),
-      )
-    })
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(self.user_id)];
+
+    let stmt =
+      Sort::Latest.add_pagination_statement(stmt, Clause::Having, &self.pagination, &mut params);
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
   }
 }
 
 impl<'a> FetchPostsCreatedByUser<WithDBClient<'a>, WithUserDetails<'a>> {
-  pub async fn fetch_posts(&self) -> Result<Vec<FetchPostsResponse>, (StatusCode, Value)> {
-    self
-      .get_db_client()
-      .query(
-        &self.get_select_statement().await?,
-        &[&self.user_id, &self.get_user_details().id],
-      )
-      .await
-      .map_err(|e| {
-        (
-          StatusCode::INTERNAL_SERVER_ERROR,
-          json!({"message": e.to_string()}),
-        )
-      })?
-      .into_iter()
-      .map(|r| FetchPostsResponse::from_row(&r))
-      .collect::<Result<Vec<_>, _>>()
-  }
-
-  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
     let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name,
-      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves FROM posts p
+      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, COUNT(DISTINCT ss.*) saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score,
+      COALESCE((SELECT v.value FROM post_votes v WHERE v.post_id = p.id AND v.user_id = $2), 0::SMALLINT) my_vote FROM posts p
       INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id
       INNER JOIN hashtags t ON t.id = r.hashtag_id
       INNER JOIN users u ON u.id = p.user_id
@@ -199,15 +284,22 @@ impl<'a> FetchPostsCreatedByUser<WithDBClient<'a>, WithUserDetails<'a>> {
       LEFT JOIN saved_posts ss ON ss.post_id = p.id
       LEFT JOIN post_comments c ON p.id = c.post_id
       WHERE u.id = $1 
-      GROUP BY p.id, u.id, s.post_id
-      ORDER BY created_at DESC";
-
-    self.get_db_client().prepare(stmt).await.map_err(|e| {
-      (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        json!({"message": e.to_string()}),
-      )
-    })
+      GROUP BY p.id, u.id, s.post_id";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> =
+      vec![Box::new(self.user_id), Box::new(self.get_user_details().id)];
+
+    let stmt =
+      Sort::Latest.add_pagination_statement(stmt, Clause::Having, &self.pagination, &mut params);
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
   }
 }
 
@@ -215,6 +307,8 @@ impl<'a> FetchPostsCreatedByUser<WithDBClient<'a>, WithUserDetails<'a>> {
 pub struct FetchPostsSavedByUser<D, U> {
   user_id: i32,
   #[serde(skip_deserializing)]
+  pagination: Pagination,
+  #[serde(skip_deserializing)]
   db_client: D,
   #[serde(skip_deserializing)]
   user_details: U,
@@ -224,6 +318,7 @@ impl<'a, U> FetchPostsSavedByUser<NoDBClient, U> {
   pub fn add_db_client(self, db_client: &'a Client) -> FetchPostsSavedByUser<WithDBClient<'a>, U> {
     FetchPostsSavedByUser {
       user_id: self.user_id,
+      pagination: self.pagination,
       db_client: WithDBClient(db_client),
       user_details: self.user_details,
     }
@@ -237,6 +332,7 @@ impl<'a, D> FetchPostsSavedByUser<D, NoUserDetails> {
   ) -> FetchPostsSavedByUser<D, WithUserDetails<'a>> {
     FetchPostsSavedByUser {
       user_id: self.user_id,
+      pagination: self.pagination,
       db_client: self.db_client,
       user_details: WithUserDetails(user_details),
     }
@@ -249,6 +345,12 @@ impl<'a, D> FetchPostsSavedByUser<D, WithUserDetails<'a>> {
   }
 }
 
+impl<D, U> FetchPostsSavedByUser<D, U> {
+  pub fn add_pagination(self, pagination: Pagination) -> FetchPostsSavedByUser<D, U> {
+    FetchPostsSavedByUser { pagination, ..self }
+  }
+}
+
 impl<'a, U> FetchPostsSavedByUser<WithDBClient<'a>, U> {
   fn get_db_client(&self) -> &'a Client {
     self.db_client.0
@@ -256,7 +358,94 @@ impl<'a, U> FetchPostsSavedByUser<WithDBClient<'a>, U> {
 }
 
 impl<'a> FetchPostsSavedByUser<WithDBClient<'a>, NoUserDetails> {
-  pub async fn fetch_posts(&self) -> Result<Vec<FetchPostsResponse>, (StatusCode, Value)> {
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
+     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, 0::BIGINT saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score FROM posts p 
+     INNER JOIN posts_hashtags_relationship r ON p.id = r.post_id 
+     INNER JOIN hashtags t ON t.id = r.hashtag_id
+     INNER JOIN users u ON u.id = p.user_id
+     LEFT JOIN post_comments c ON p.id = c.post_id
+     LEFT JOIN saved_posts s ON s.post_id = p.id
+     WHERE s.user_id = $1
+     GROUP BY p.id, u.id";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(self.user_id)];
+
+    let stmt =
+      Sort::Latest.add_pagination_statement(stmt, Clause::Having, &self.pagination, &mut params);
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
+  }
+}
+
+impl<'a> FetchPostsSavedByUser<WithDBClient<'a>, WithUserDetails<'a>> {
+  pub async fn fetch_posts(&self) -> Result<Page<FetchPostsResponse>, (StatusCode, Value)> {
+    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name,
+      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, 0::BIGINT saves,
+      COALESCE((SELECT SUM(v.value) FROM post_votes v WHERE v.post_id = p.id), 0)::BIGINT score,
+      COALESCE((SELECT v.value FROM post_votes v WHERE v.post_id = p.id AND v.user_id = $2), 0::SMALLINT) my_vote FROM posts p
+      INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id
+      INNER JOIN hashtags t ON t.id = r.hashtag_id
+      INNER JOIN users u ON u.id = p.user_id
+      LEFT JOIN saved_posts s ON s.post_id = p.id AND s.user_id = $2
+      LEFT JOIN saved_posts ss ON ss.post_id = p.id
+      LEFT JOIN post_comments c ON p.id = c.post_id
+      WHERE ss.user_id = $1
+      GROUP BY p.id, u.id, s.post_id";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> =
+      vec![Box::new(self.user_id), Box::new(self.get_user_details().id)];
+
+    let stmt =
+      Sort::Latest.add_pagination_statement(stmt, Clause::Having, &self.pagination, &mut params);
+
+    fetch_page(
+      self.get_db_client(),
+      &stmt,
+      &params,
+      &self.pagination,
+      FetchPostsResponse::from_row,
+    )
+    .await
+  }
+}
+
+#[derive(Deserialize)]
+pub struct FetchHashtagsFollowedByUser<D> {
+  user_id: i32,
+  #[serde(skip_deserializing)]
+  db_client: D,
+}
+
+#[derive(Serialize)]
+pub struct FollowedHashtag {
+  name: String,
+  color: String,
+  followed_at: NaiveDateTime,
+}
+
+impl<'a> FetchHashtagsFollowedByUser<NoDBClient> {
+  pub fn add_db_client(
+    self,
+    db_client: &'a Client,
+  ) -> FetchHashtagsFollowedByUser<WithDBClient<'a>> {
+    FetchHashtagsFollowedByUser {
+      user_id: self.user_id,
+      db_client: WithDBClient(db_client),
+    }
+  }
+}
+
+impl<'a> FetchHashtagsFollowedByUser<WithDBClient<'a>> {
+  pub async fn fetch(&self) -> Result<Vec<FollowedHashtag>, (StatusCode, Value)> {
     self
       .get_db_client()
       .query(&self.get_select_statement().await?, &[&self.user_id])
@@ -267,22 +456,15 @@ impl<'a> FetchPostsSavedByUser<WithDBClient<'a>, NoUserDetails> {
           json!({"message": e.to_string()}),
         )
       })?
-      .into_iter()
-      .map(|r| FetchPostsResponse::from_row(&r))
-      .collect::<Result<Vec<_>, _>>()
+      .iter()
+      .map(FollowedHashtag::from_row)
+      .collect()
   }
 
   async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name, 
-     p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, 0::BIGINT saves FROM posts p 
-     INNER JOIN posts_hashtags_relationship r ON p.id = r.post_id 
-     INNER JOIN hashtags t ON t.id = r.hashtag_id
-     INNER JOIN users u ON u.id = p.user_id
-     LEFT JOIN post_comments c ON p.id = c.post_id
-     LEFT JOIN saved_posts s ON s.post_id = p.id
-     WHERE s.user_id = $1
-     GROUP BY p.id, u.id
-     ORDER BY created_at DESC";
+    let stmt = "SELECT h.name, h.color::TEXT color, f.created_at followed_at FROM hashtag_follows f
+      INNER JOIN hashtags h ON h.id = f.hashtag_id
+      WHERE f.user_id = $1 ORDER BY f.created_at DESC";
 
     self.get_db_client().prepare(stmt).await.map_err(|e| {
       (
@@ -291,15 +473,74 @@ impl<'a> FetchPostsSavedByUser<WithDBClient<'a>, NoUserDetails> {
       )
     })
   }
+
+  fn get_db_client(&self) -> &'a Client {
+    self.db_client.0
+  }
 }
 
-impl<'a> FetchPostsSavedByUser<WithDBClient<'a>, WithUserDetails<'a>> {
-  pub async fn fetch_posts(&self) -> Result<Vec<FetchPostsResponse>, (StatusCode, Value)> {
+impl FollowedHashtag {
+  pub fn from_row(row: &Row) -> Result<FollowedHashtag, (StatusCode, Value)> {
+    let name = row.try_get::<&str, String>("name");
+    let color = row.try_get::<&str, String>("color");
+    let followed_at = row.try_get::<&str, NaiveDateTime>("followed_at");
+
+    match (name, color, followed_at) {
+      (Ok(name), Ok(color), Ok(followed_at)) => Ok(FollowedHashtag {
+        name,
+        color,
+        followed_at,
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+pub struct FollowUser<'a> {
+  pub user_details: UserAuthDetails,
+  pub db_client: &'a Client,
+  pub user_id: i32,
+}
+
+impl<'a> FollowUser<'a> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    if self.user_id == self.user_details.id {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"message": "Cannot follow yourself"}),
+      ));
+    }
+
+    let rows = self
+      .db_client
+      .query(&self.get_user_statement().await?, &[&self.user_id])
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })?;
+
+    if rows.is_empty() {
+      return Err((
+        StatusCode::NOT_FOUND,
+        json!({ "message": format!("No user found with id {}", self.user_id) }),
+      ));
+    }
+
     self
-      .get_db_client()
+      .db_client
       .query(
-        &self.get_select_statement().await?,
-        &[&self.user_id, &self.get_user_details().id],
+        &self.get_insert_statement().await?,
+        &[
+          &self.user_details.id,
+          &self.user_id,
+          &Utc::now().naive_utc(),
+        ],
       )
       .await
       .map_err(|e| {
@@ -307,30 +548,194 @@ impl<'a> FetchPostsSavedByUser<WithDBClient<'a>, WithUserDetails<'a>> {
           StatusCode::INTERNAL_SERVER_ERROR,
           json!({"message": e.to_string()}),
         )
-      })?
-      .into_iter()
-      .map(|r| FetchPostsResponse::from_row(&r))
-      .collect::<Result<Vec<_>, _>>()
+      })
+      .map(|_| ())
   }
 
-  async fn get_select_statement(&self) -> Result<Statement, (StatusCode, Value)> {
-    let stmt = "SELECT p.id, p.title, p.body, u.id author_id, u.username author_name,
-      (s.post_id IS NOT NULL) saved, p.created_at, ARRAY_AGG(DISTINCT t.name ||':'|| t.color::TEXT) hashtags, COUNT(DISTINCT c.*) comments, 0::BIGINT saves FROM posts p
-      INNER JOIN  posts_hashtags_relationship r ON p.id = r.post_id
-      INNER JOIN hashtags t ON t.id = r.hashtag_id
-      INNER JOIN users u ON u.id = p.user_id
-      LEFT JOIN saved_posts s ON s.post_id = p.id AND s.user_id = $2
-      LEFT JOIN saved_posts ss ON ss.post_id = p.id
-      LEFT JOIN post_comments c ON p.id = c.post_id
-      WHERE ss.user_id = $1
-      GROUP BY p.id, u.id, s.post_id
-      ORDER BY created_at DESC";
+  pub async fn exec_reverse(&self) -> Result<(), (StatusCode, Value)> {
+    self
+      .db_client
+      .query(
+        &self.get_delete_statement().await?,
+        &[&self.user_details.id, &self.user_id],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
 
-    self.get_db_client().prepare(stmt).await.map_err(|e| {
+  async fn get_user_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "SELECT id FROM users WHERE id = $1";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_insert_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt =
+      "INSERT INTO user_follows (follower_id, following_id, created_at) VALUES ($1, $2, $3)
+      ON CONFLICT (follower_id, following_id) DO NOTHING";
+
+    self.prepare(stmt).await
+  }
+
+  async fn get_delete_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "DELETE FROM user_follows WHERE follower_id = $1 AND following_id = $2";
+
+    self.prepare(stmt).await
+  }
+
+  async fn prepare(&self, stmt: &str) -> Result<Statement, (StatusCode, Value)> {
+    self.db_client.prepare(stmt).await.map_err(|e| {
       (
         StatusCode::INTERNAL_SERVER_ERROR,
-        json!({"message": e.to_string()}),
+        json!({ "message": e.to_string() }),
       )
     })
   }
 }
+
+#[derive(Serialize)]
+pub struct FollowListUser {
+  id: i32,
+  username: String,
+  followed_at: NaiveDateTime,
+}
+
+impl FollowListUser {
+  pub fn from_row(row: &Row) -> Result<FollowListUser, (StatusCode, Value)> {
+    let id = row.try_get::<&str, i32>("id");
+    let username = row.try_get::<&str, String>("username");
+    let followed_at = row.try_get::<&str, NaiveDateTime>("followed_at");
+
+    match (id, username, followed_at) {
+      (Ok(id), Ok(username), Ok(followed_at)) => Ok(FollowListUser {
+        id,
+        username,
+        followed_at,
+      }),
+      _ => Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message":"Error converting postgres types" }),
+      )),
+    }
+  }
+}
+
+#[derive(Deserialize)]
+pub struct FetchFollowersOfUser<D> {
+  user_id: i32,
+  #[serde(skip_deserializing)]
+  pagination: Pagination,
+  #[serde(skip_deserializing)]
+  db_client: D,
+}
+
+impl<'a> FetchFollowersOfUser<NoDBClient> {
+  pub fn add_db_client(self, db_client: &'a Client) -> FetchFollowersOfUser<WithDBClient<'a>> {
+    FetchFollowersOfUser {
+      user_id: self.user_id,
+      pagination: self.pagination,
+      db_client: WithDBClient(db_client),
+    }
+  }
+}
+
+impl<D> FetchFollowersOfUser<D> {
+  pub fn add_pagination(self, pagination: Pagination) -> FetchFollowersOfUser<D> {
+    FetchFollowersOfUser { pagination, ..self }
+  }
+}
+
+impl<'a> FetchFollowersOfUser<WithDBClient<'a>> {
+  pub async fn fetch(&self) -> Result<Page<FollowListUser>, (StatusCode, Value)> {
+    let stmt = "SELECT u.id, u.username, f.created_at followed_at FROM user_follows f
+      INNER JOIN users u ON u.id = f.follower_id
+      WHERE f.following_id = $1";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(self.user_id)];
+
+    let stmt = self.pagination.add_statement(
+      stmt,
+      Clause::And,
+      &SortKey::Column {
+        column: "f.created_at",
+        id: "u.id",
+        name: "followed_at",
+      },
+      false,
+      Vec::new(),
+      &mut params,
+    );
+
+    fetch_page(
+      self.db_client.0,
+      &stmt,
+      &params,
+      &self.pagination,
+      FollowListUser::from_row,
+    )
+    .await
+  }
+}
+
+#[derive(Deserialize)]
+pub struct FetchUsersFollowedByUser<D> {
+  user_id: i32,
+  #[serde(skip_deserializing)]
+  pagination: Pagination,
+  #[serde(skip_deserializing)]
+  db_client: D,
+}
+
+impl<'a> FetchUsersFollowedByUser<NoDBClient> {
+  pub fn add_db_client(self, db_client: &'a Client) -> FetchUsersFollowedByUser<WithDBClient<'a>> {
+    FetchUsersFollowedByUser {
+      user_id: self.user_id,
+      pagination: self.pagination,
+      db_client: WithDBClient(db_client),
+    }
+  }
+}
+
+impl<D> FetchUsersFollowedByUser<D> {
+  pub fn add_pagination(self, pagination: Pagination) -> FetchUsersFollowedByUser<D> {
+    FetchUsersFollowedByUser { pagination, ..self }
+  }
+}
+
+impl<'a> FetchUsersFollowedByUser<WithDBClient<'a>> {
+  pub async fn fetch(&self) -> Result<Page<FollowListUser>, (StatusCode, Value)> {
+    let stmt = "SELECT u.id, u.username, f.created_at followed_at FROM user_follows f
+      INNER JOIN users u ON u.id = f.following_id
+      WHERE f.follower_id = $1";
+
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(self.user_id)];
+
+    let stmt = self.pagination.add_statement(
+      stmt,
+      Clause::And,
+      &SortKey::Column {
+        column: "f.created_at",
+        id: "u.id",
+        name: "followed_at",
+      },
+      false,
+      Vec::new(),
+      &mut params,
+    );
+
+    fetch_page(
+      self.db_client.0,
+      &stmt,
+      &params,
+      &self.pagination,
+      FollowListUser::from_row,
+    )
+    .await
+  }
+}
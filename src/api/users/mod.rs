@@ -5,5 +5,6 @@ mod models;
 use actix_web::web::{self, ServiceConfig};
 
 pub fn view(cfg: &mut ServiceConfig) {
+  cfg.route("/me", web::patch().to(controllers::update_profile));
   cfg.service(web::scope("{user_id}").configure(id::view));
 }
@@ -0,0 +1,189 @@
+use std::marker::PhantomData;
+
+use actix_web::http::StatusCode;
+use deadpool_postgres::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::Statement;
+
+use crate::api::{
+  handler_utils::{
+    NoDBClient, NoUserDetails, NotValidated, Validated, WithDBClient, WithUserDetails,
+  },
+  UserAuthDetails,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct UpdateProfile<D, U, V> {
+  display_name: Option<String>,
+
+  bio: Option<String>,
+
+  website: Option<String>,
+
+  location: Option<String>,
+
+  avatar_url: Option<String>,
+
+  #[serde(skip_deserializing)]
+  db_client: D,
+
+  #[serde(skip_deserializing)]
+  user_details: U,
+
+  #[serde(skip_deserializing)]
+  validated: PhantomData<V>,
+}
+
+impl UpdateProfile<NoDBClient, NoUserDetails, NotValidated> {
+  pub fn validate(
+    self,
+  ) -> Result<UpdateProfile<NoDBClient, NoUserDetails, Validated>, (StatusCode, Value)> {
+    if self.display_name.is_none()
+      && self.bio.is_none()
+      && self.website.is_none()
+      && self.location.is_none()
+      && self.avatar_url.is_none()
+    {
+      return Err((
+        StatusCode::BAD_REQUEST,
+        json!({"message": "Nothing to update"}),
+      ));
+    }
+
+    Ok(UpdateProfile {
+      display_name: self
+        .display_name
+        .as_deref()
+        .map(|v| validate_text("display_name", "Display name", v, 50))
+        .transpose()?,
+      bio: self
+        .bio
+        .as_deref()
+        .map(|v| validate_text("bio", "Bio", v, 500))
+        .transpose()?,
+      website: self
+        .website
+        .as_deref()
+        .map(|v| validate_url("website", "Website", v, 200))
+        .transpose()?,
+      location: self
+        .location
+        .as_deref()
+        .map(|v| validate_text("location", "Location", v, 100))
+        .transpose()?,
+      avatar_url: self
+        .avatar_url
+        .as_deref()
+        .map(|v| validate_url("avatar_url", "Avatar URL", v, 500))
+        .transpose()?,
+      db_client: self.db_client,
+      user_details: self.user_details,
+      validated: PhantomData,
+    })
+  }
+}
+
+impl<'a, V> UpdateProfile<NoDBClient, NoUserDetails, V> {
+  pub fn add_details(
+    self,
+    db_client: &'a Client,
+    user_details: &'a UserAuthDetails,
+  ) -> UpdateProfile<WithDBClient<'a>, WithUserDetails<'a>, V> {
+    UpdateProfile {
+      display_name: self.display_name,
+      bio: self.bio,
+      website: self.website,
+      location: self.location,
+      avatar_url: self.avatar_url,
+      db_client: WithDBClient(db_client),
+      user_details: WithUserDetails(user_details),
+      validated: PhantomData,
+    }
+  }
+}
+
+impl<'a> UpdateProfile<WithDBClient<'a>, WithUserDetails<'a>, Validated> {
+  pub async fn exec(&self) -> Result<(), (StatusCode, Value)> {
+    self
+      .get_db_client()
+      .execute(
+        &self.get_update_statement().await?,
+        &[
+          &self.user_details.0.id,
+          &self.display_name,
+          &self.bio,
+          &self.website,
+          &self.location,
+          &self.avatar_url,
+        ],
+      )
+      .await
+      .map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": e.to_string()}),
+        )
+      })
+      .map(|_| ())
+  }
+
+  fn get_db_client(&self) -> &'a Client {
+    self.db_client.0
+  }
+
+  async fn get_update_statement(&self) -> Result<Statement, (StatusCode, Value)> {
+    let stmt = "UPDATE users SET
+      display_name = CASE WHEN $2::VARCHAR IS NULL THEN display_name ELSE NULLIF($2, '') END,
+      bio = CASE WHEN $3::VARCHAR IS NULL THEN bio ELSE NULLIF($3, '') END,
+      website = CASE WHEN $4::VARCHAR IS NULL THEN website ELSE NULLIF($4, '') END,
+      location = CASE WHEN $5::VARCHAR IS NULL THEN location ELSE NULLIF($5, '') END,
+      avatar_url = CASE WHEN $6::VARCHAR IS NULL THEN avatar_url ELSE NULLIF($6, '') END
+      WHERE id = $1";
+
+    self.get_db_client().prepare(stmt).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": e.to_string()}),
+      )
+    })
+  }
+}
+
+fn validate_text(
+  name: &str,
+  label: &str,
+  value: &str,
+  max: usize,
+) -> Result<String, (StatusCode, Value)> {
+  let value = value.trim();
+
+  if value.chars().count() > max {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": name, "message": format!("{} should not have more than {} characters", label, max)}),
+    ));
+  }
+
+  Ok(value.to_owned())
+}
+
+fn validate_url(
+  name: &str,
+  label: &str,
+  value: &str,
+  max: usize,
+) -> Result<String, (StatusCode, Value)> {
+  let value = validate_text(name, label, value, max)?;
+
+  let has_scheme = value.starts_with("http://") || value.starts_with("https://");
+
+  if !value.is_empty() && (!has_scheme || value.contains(char::is_whitespace)) {
+    return Err((
+      StatusCode::BAD_REQUEST,
+      json!({"name": name, "message": format!("{} should be a valid http or https URL", label)}),
+    ));
+  }
+
+  Ok(value)
+}
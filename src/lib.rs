@@ -5,7 +5,11 @@ use actix_web::{
 use serde_json::json;
 
 mod api;
+pub mod mailer;
 pub mod middleware;
+pub mod oidc;
+pub mod password;
+pub mod throttle;
 
 pub fn app(cfg: &mut ServiceConfig) {
   cfg
@@ -13,6 +17,8 @@ pub fn app(cfg: &mut ServiceConfig) {
     .service(web::scope("/posts").configure(api::post))
     .service(web::scope("/users").configure(api::user))
     .service(web::scope("/hashtags").configure(api::hashtags))
+    .service(web::scope("/search").configure(api::search))
+    .service(web::scope("/feed").configure(api::feed))
     .default_service(web::to(|| async {
       HttpResponse::NotFound().json(json!({
         "success": false,
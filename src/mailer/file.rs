@@ -0,0 +1,45 @@
+use std::{fs, path::PathBuf};
+
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+
+use super::{Mail, Mailer};
+
+pub struct FileMailer {
+  dir: PathBuf,
+}
+
+impl FileMailer {
+  pub fn new(dir: &str) -> FileMailer {
+    FileMailer { dir: dir.into() }
+  }
+}
+
+impl Mailer for FileMailer {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), String>> {
+    Box::pin(async move {
+      fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+
+      let recipient: String = mail
+        .to
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+      let path = self.dir.join(format!(
+        "{}-{}.eml",
+        Utc::now().timestamp_micros(),
+        recipient
+      ));
+
+      fs::write(
+        path,
+        format!(
+          "To: {}\nSubject: {}\n\n{}\n",
+          mail.to, mail.subject, mail.body
+        ),
+      )
+      .map_err(|e| e.to_string())
+    })
+  }
+}
@@ -0,0 +1,26 @@
+use std::sync::Mutex;
+
+use futures_util::future::BoxFuture;
+
+use super::{Mail, Mailer};
+
+#[derive(Default)]
+pub struct MemoryMailer {
+  sent: Mutex<Vec<Mail>>,
+}
+
+impl MemoryMailer {
+  pub fn sent(&self) -> Vec<Mail> {
+    self.sent.lock().map(|m| m.clone()).unwrap_or_default()
+  }
+}
+
+impl Mailer for MemoryMailer {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), String>> {
+    Box::pin(async move {
+      self.sent.lock().map_err(|e| e.to_string())?.push(mail);
+
+      Ok(())
+    })
+  }
+}
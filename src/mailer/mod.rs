@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+mod file;
+mod memory;
+mod smtp;
+
+pub use file::FileMailer;
+pub use memory::MemoryMailer;
+pub use smtp::SmtpMailer;
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+  pub to: String,
+  pub subject: String,
+  pub body: String,
+}
+
+pub trait Mailer: Send + Sync {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), String>>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct MailConfig {
+  pub backend: Option<String>,
+  pub from: Option<String>,
+  pub dir: Option<String>,
+  pub smtp_host: Option<String>,
+  pub smtp_port: Option<u16>,
+  pub smtp_username: Option<String>,
+  pub smtp_password: Option<String>,
+  pub smtp_tls: Option<bool>,
+}
+
+impl MailConfig {
+  pub fn build(&self) -> Result<Arc<dyn Mailer>, String> {
+    let from = self
+      .from
+      .clone()
+      .unwrap_or("Forum <no-reply@localhost>".to_owned());
+
+    match self.backend.as_deref().unwrap_or("file") {
+      "smtp" => Ok(Arc::new(SmtpMailer::new(
+        self
+          .smtp_host
+          .as_deref()
+          .ok_or("MAIL.SMTP_HOST is required")?,
+        self.smtp_port,
+        self.smtp_username.clone().zip(self.smtp_password.clone()),
+        self.smtp_tls.unwrap_or(true),
+        &from,
+      )?)),
+      "file" => Ok(Arc::new(FileMailer::new(
+        self.dir.as_deref().unwrap_or("mail"),
+      ))),
+      "memory" => Ok(Arc::new(MemoryMailer::default())),
+      backend => Err(format!("Unknown mail backend {backend}")),
+    }
+  }
+}
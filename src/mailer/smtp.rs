@@ -0,0 +1,69 @@
+use futures_util::future::BoxFuture;
+use lettre::{
+  message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+  AsyncTransport, Message, Tokio1Executor,
+};
+
+use super::{Mail, Mailer};
+
+pub struct SmtpMailer {
+  transport: AsyncSmtpTransport<Tokio1Executor>,
+  from: Mailbox,
+}
+
+impl SmtpMailer {
+  pub fn new(
+    host: &str,
+    port: Option<u16>,
+    credentials: Option<(String, String)>,
+    tls: bool,
+    from: &str,
+  ) -> Result<SmtpMailer, String> {
+    let mut builder = match tls {
+      true => {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host).map_err(|e| e.to_string())?
+      }
+      false => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    };
+
+    if let Some(port) = port {
+      builder = builder.port(port);
+    }
+
+    if let Some((username, password)) = credentials {
+      builder = builder.credentials(Credentials::new(username, password));
+    }
+
+    Ok(SmtpMailer {
+      transport: builder.build(),
+      from: from
+        .parse()
+        .map_err(|e| format!("Invalid sender address {e}"))?,
+    })
+  }
+}
+
+impl Mailer for SmtpMailer {
+  fn send(&self, mail: Mail) -> BoxFuture<'_, Result<(), String>> {
+    Box::pin(async move {
+      let message = Message::builder()
+        .from(self.from.clone())
+        .to(
+          mail
+            .to
+            .parse()
+            .map_err(|e| format!("Invalid recipient address {e}"))?,
+        )
+        .subject(mail.subject)
+        .body(mail.body)
+        .map_err(|e| e.to_string())?;
+
+      self
+        .transport
+        .send(message)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    })
+  }
+}
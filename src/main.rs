@@ -1,4 +1,4 @@
-use std::env;
+use std::{collections::HashMap, env, time::Duration};
 
 use actix_web::{
   error,
@@ -11,7 +11,14 @@ use actix_cors::Cors;
 use deadpool_postgres::Runtime;
 use serde::{Deserialize, Serialize};
 
-use forum_api::{app, middleware::auth::Authenticate};
+use forum_api::{
+  app,
+  mailer::MailConfig,
+  middleware::auth::Authenticate,
+  oidc::{OidcProviderConfig, OidcProviders},
+  password::PasswordConfig,
+  throttle::ThrottleConfig,
+};
 use serde_json::json;
 use tokio_postgres::NoTls;
 
@@ -35,6 +42,55 @@ async fn main() -> std::io::Result<()> {
 
   let pool = pool_res.unwrap();
 
+  let mailer_res = config.mail.clone().unwrap_or_default().build();
+
+  if let Err(e) = mailer_res {
+    eprintln!("Mailer creation error\n\n {e}");
+    return Ok(());
+  }
+
+  let mailer = mailer_res.unwrap();
+
+  let oidc_res = OidcProviders::new(config.oidc.clone().unwrap_or_default());
+
+  if let Err(e) = oidc_res {
+    eprintln!("OIDC provider configuration error\n\n {e}");
+    return Ok(());
+  }
+
+  let oidc_providers = web::Data::new(oidc_res.unwrap());
+
+  let passwords_res = config.password.clone().unwrap_or_default().build();
+
+  if let Err(e) = passwords_res {
+    eprintln!("Password hasher creation error\n\n {e}");
+    return Ok(());
+  }
+
+  let passwords = web::Data::new(passwords_res.unwrap());
+
+  let throttle_res = config.throttle.clone().unwrap_or_default().build(&pool);
+
+  if let Err(e) = throttle_res {
+    eprintln!("Throttle creation error\n\n {e}");
+    return Ok(());
+  }
+
+  let throttle = web::Data::new(throttle_res.unwrap());
+  let pruned_throttle = throttle.clone();
+
+  actix_web::rt::spawn(async move {
+    let mut interval = actix_web::rt::time::interval(Duration::from_secs(10 * 60));
+
+    loop {
+      interval.tick().await;
+
+      if let Err(e) = pruned_throttle.prune().await {
+        eprintln!("Could not prune auth attempts: {e}");
+      }
+    }
+  });
+
   let server = HttpServer::new(move || {
     let json_config = web::JsonConfig::default()
       .limit(4096)
@@ -64,6 +120,10 @@ async fn main() -> std::io::Result<()> {
       .app_data(json_config)
       .app_data(query_config)
       .app_data(web::Data::new(pool.clone()))
+      .app_data(web::Data::from(mailer.clone()))
+      .app_data(oidc_providers.clone())
+      .app_data(passwords.clone())
+      .app_data(throttle.clone())
       .wrap(
         Cors::default()
           .allowed_origin_fn(|origin, _| {
@@ -107,4 +167,8 @@ struct Config {
   pub threads: Option<usize>,
   pub server_port: u16,
   pub pg: deadpool_postgres::Config,
+  pub mail: Option<MailConfig>,
+  pub oidc: Option<HashMap<String, OidcProviderConfig>>,
+  pub password: Option<PasswordConfig>,
+  pub throttle: Option<ThrottleConfig>,
 }
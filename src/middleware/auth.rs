@@ -1,22 +1,26 @@
-use std::future::{ready, Ready};
+use std::{
+  future::{ready, Ready},
+  rc::Rc,
+};
 
 use actix_web::{
   dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
   http::header,
-  Error, HttpMessage,
+  web, Error, HttpMessage,
 };
+use deadpool_postgres::Pool;
 use futures_util::future::LocalBoxFuture;
 
 use crate::api::UserAuthDetails;
 
 pub struct Authenticate;
 pub struct AuthenticateMiddleware<S> {
-  service: S,
+  service: Rc<S>,
 }
 
 impl<S, B> Transform<S, ServiceRequest> for Authenticate
 where
-  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
   S::Future: 'static,
   B: 'static,
 {
@@ -27,13 +31,15 @@ where
   type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
   fn new_transform(&self, service: S) -> Self::Future {
-    ready(Ok(AuthenticateMiddleware { service }))
+    ready(Ok(AuthenticateMiddleware {
+      service: Rc::new(service),
+    }))
   }
 }
 
 impl<S, B> Service<ServiceRequest> for AuthenticateMiddleware<S>
 where
-  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
   S::Future: 'static,
   B: 'static,
 {
@@ -44,25 +50,34 @@ where
   forward_ready!(service);
 
   fn call(&self, req: ServiceRequest) -> Self::Future {
-    let user_details = req
+    let token = req
       .headers()
       .get(header::AUTHORIZATION)
       .ok_or(())
       .and_then(|h| h.to_str().map_err(|_| ()))
       .ok()
       .and_then(|s| s.split_whitespace().nth(1))
-      .ok_or(())
-      .and_then(|t| UserAuthDetails::from_jwt(t).map_err(|_| ()))
-      .ok();
-
-    if let Some(u) = user_details {
-      req.extensions_mut().insert(u);
-    }
+      .map(str::to_owned);
 
-    let fut = self.service.call(req);
+    let db_pool = req.app_data::<web::Data<Pool>>().cloned();
+    let service = self.service.clone();
 
     Box::pin(async move {
-      let res = fut.await?;
+      if let (Some(token), Some(db_pool)) = (token, db_pool) {
+        if let Ok(db_client) = db_pool.get().await {
+          let user_details = match UserAuthDetails::from_jwt(&token) {
+            Ok(u) if u.verify_session(&db_client).await => Some(u),
+            Ok(_) => None,
+            Err(_) => UserAuthDetails::from_personal_access_token(&token, &db_client).await,
+          };
+
+          if let Some(u) = user_details {
+            req.extensions_mut().insert(u);
+          }
+        }
+      }
+
+      let res = service.call(req).await?;
       Ok(res)
     })
   }
@@ -0,0 +1,159 @@
+use std::{
+  collections::HashMap,
+  sync::RwLock,
+  time::{Duration, Instant},
+};
+
+use openidconnect::{
+  core::{CoreClient, CoreProviderMetadata},
+  reqwest, ClientId, ClientSecret, EndpointMaybeSet, EndpointNotSet, EndpointSet, IssuerUrl,
+  RedirectUrl, Scope,
+};
+use serde::{Deserialize, Serialize};
+
+const METADATA_TTL: Duration = Duration::from_secs(60 * 60);
+
+pub type OidcClient = CoreClient<
+  EndpointSet,
+  EndpointNotSet,
+  EndpointNotSet,
+  EndpointNotSet,
+  EndpointMaybeSet,
+  EndpointMaybeSet,
+>;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OidcProviderConfig {
+  pub issuer_url: String,
+  pub client_id: String,
+  pub client_secret: Option<String>,
+  pub redirect_url: String,
+  pub scopes: Option<String>,
+  pub display_name: Option<String>,
+}
+
+pub struct OidcProvider {
+  pub name: String,
+  pub display_name: String,
+  issuer_url: IssuerUrl,
+  client_id: ClientId,
+  client_secret: Option<ClientSecret>,
+  redirect_url: RedirectUrl,
+  scopes: Vec<Scope>,
+  metadata: RwLock<Option<(Instant, CoreProviderMetadata)>>,
+}
+
+pub struct OidcProviders {
+  http_client: reqwest::Client,
+  providers: HashMap<String, OidcProvider>,
+}
+
+impl OidcProviders {
+  pub fn new(configs: HashMap<String, OidcProviderConfig>) -> Result<OidcProviders, String> {
+    let http_client = reqwest::ClientBuilder::new()
+      .redirect(reqwest::redirect::Policy::none())
+      .timeout(Duration::from_secs(10))
+      .build()
+      .map_err(|e| e.to_string())?;
+
+    let providers = configs
+      .into_iter()
+      .map(|(name, config)| {
+        let provider = OidcProvider::new(&name, config)?;
+        Ok((name, provider))
+      })
+      .collect::<Result<HashMap<_, _>, String>>()?;
+
+    Ok(OidcProviders {
+      http_client,
+      providers,
+    })
+  }
+
+  pub fn get(&self, name: &str) -> Option<&OidcProvider> {
+    self.providers.get(name)
+  }
+
+  pub fn list(&self) -> Vec<&OidcProvider> {
+    let mut providers: Vec<_> = self.providers.values().collect();
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+    providers
+  }
+
+  pub fn http_client(&self) -> &reqwest::Client {
+    &self.http_client
+  }
+}
+
+impl OidcProvider {
+  fn new(name: &str, config: OidcProviderConfig) -> Result<OidcProvider, String> {
+    let issuer_url = IssuerUrl::new(config.issuer_url)
+      .map_err(|e| format!("OIDC.{}.ISSUER_URL is not valid: {e}", name.to_uppercase()))?;
+
+    let redirect_url = RedirectUrl::new(config.redirect_url).map_err(|e| {
+      format!(
+        "OIDC.{}.REDIRECT_URL is not valid: {e}",
+        name.to_uppercase()
+      )
+    })?;
+
+    let scopes = config
+      .scopes
+      .as_deref()
+      .unwrap_or("email profile")
+      .split_whitespace()
+      .filter(|s| *s != "openid")
+      .map(|s| Scope::new(s.to_owned()))
+      .collect();
+
+    Ok(OidcProvider {
+      name: name.to_owned(),
+      display_name: config.display_name.unwrap_or(name.to_owned()),
+      issuer_url,
+      client_id: ClientId::new(config.client_id),
+      client_secret: config.client_secret.map(ClientSecret::new),
+      redirect_url,
+      scopes,
+      metadata: RwLock::new(None),
+    })
+  }
+
+  pub fn scopes(&self) -> &[Scope] {
+    &self.scopes
+  }
+
+  pub async fn client(&self, http_client: &reqwest::Client) -> Result<OidcClient, String> {
+    let metadata = self.metadata(http_client).await?;
+
+    Ok(
+      CoreClient::from_provider_metadata(
+        metadata,
+        self.client_id.clone(),
+        self.client_secret.clone(),
+      )
+      .set_redirect_uri(self.redirect_url.clone()),
+    )
+  }
+
+  async fn metadata(&self, http_client: &reqwest::Client) -> Result<CoreProviderMetadata, String> {
+    let cached = self
+      .metadata
+      .read()
+      .map_err(|e| e.to_string())?
+      .as_ref()
+      .filter(|(fetched_at, _)| fetched_at.elapsed() < METADATA_TTL)
+      .map(|(_, metadata)| metadata.clone());
+
+    if let Some(metadata) = cached {
+      return Ok(metadata);
+    }
+
+    let metadata = CoreProviderMetadata::discover_async(self.issuer_url.clone(), http_client)
+      .await
+      .map_err(|e| format!("Could not discover provider {}: {e}", self.name))?;
+
+    *self.metadata.write().map_err(|e| e.to_string())? = Some((Instant::now(), metadata.clone()));
+
+    Ok(metadata)
+  }
+}
@@ -0,0 +1,327 @@
+use std::{collections::HashSet, fs};
+
+use actix_web::web;
+use argon2::{
+  password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+  Algorithm, Argon2, Params, Version,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const MAX_PASSWORD_LENGTH: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct PasswordConfig {
+  pub algorithm: Option<String>,
+  pub bcrypt_cost: Option<u32>,
+  pub argon2_memory_kib: Option<u32>,
+  pub argon2_iterations: Option<u32>,
+  pub argon2_parallelism: Option<u32>,
+  pub min_length: Option<usize>,
+  pub common_passwords_file: Option<String>,
+}
+
+#[derive(Clone)]
+enum Hasher {
+  Argon2id(Params),
+  Bcrypt(u32),
+}
+
+pub struct Passwords {
+  hasher: Hasher,
+  min_length: usize,
+  common_passwords: HashSet<String>,
+  dummy_hash: String,
+}
+
+impl PasswordConfig {
+  pub fn build(&self) -> Result<Passwords, String> {
+    let hasher = match self.algorithm.as_deref().unwrap_or("argon2id") {
+      "argon2id" => Hasher::Argon2id(
+        Params::new(
+          self.argon2_memory_kib.unwrap_or(19 * 1024),
+          self.argon2_iterations.unwrap_or(2),
+          self.argon2_parallelism.unwrap_or(1),
+          None,
+        )
+        .map_err(|e| format!("Invalid PASSWORD.ARGON2_* settings: {e}"))?,
+      ),
+      "bcrypt" => {
+        let cost = self.bcrypt_cost.unwrap_or(12);
+
+        if !(4..=31).contains(&cost) {
+          return Err("PASSWORD.BCRYPT_COST should be between 4 and 31".to_owned());
+        }
+
+        Hasher::Bcrypt(cost)
+      }
+      algorithm => return Err(format!("Unknown password algorithm {algorithm}")),
+    };
+
+    let common_passwords = match self.common_passwords_file.as_deref() {
+      Some(file) => fs::read_to_string(file)
+        .map_err(|e| format!("Could not read PASSWORD.COMMON_PASSWORDS_FILE {file}: {e}"))?
+        .lines()
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+        .collect(),
+      None => HashSet::new(),
+    };
+
+    let dummy_hash = hasher.hash(&generate_password())?;
+
+    Ok(Passwords {
+      hasher,
+      min_length: self.min_length.unwrap_or(4).clamp(1, MAX_PASSWORD_LENGTH),
+      common_passwords,
+      dummy_hash,
+    })
+  }
+}
+
+impl Hasher {
+  fn hash(&self, password: &str) -> Result<String, String> {
+    match self {
+      Hasher::Argon2id(params) => {
+        let salt = SaltString::encode_b64(&rand::thread_rng().gen::<[u8; 16]>())
+          .map_err(|e| e.to_string())?;
+
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone())
+          .hash_password(password.as_bytes(), &salt)
+          .map(|h| h.to_string())
+          .map_err(|e| e.to_string())
+      }
+      Hasher::Bcrypt(cost) => bcrypt::hash(password, *cost).map_err(|e| e.to_string()),
+    }
+  }
+
+  fn needs_rehash(&self, hash: &str) -> bool {
+    match self {
+      Hasher::Argon2id(params) => {
+        let Ok(hash) = PasswordHash::new(hash) else {
+          return true;
+        };
+
+        let Ok(current) = Params::try_from(&hash) else {
+          return true;
+        };
+
+        hash.algorithm != Algorithm::Argon2id.ident()
+          || hash.version != Some(Version::V0x13.into())
+          || current.m_cost() < params.m_cost()
+          || current.t_cost() < params.t_cost()
+          || current.p_cost() < params.p_cost()
+      }
+      Hasher::Bcrypt(cost) => bcrypt_cost(hash).is_none_or(|c| c < *cost),
+    }
+  }
+}
+
+impl Passwords {
+  pub async fn hash(&self, password: &str) -> Result<String, String> {
+    let hasher = self.hasher.clone();
+    let password = password.to_owned();
+
+    web::block(move || hasher.hash(&password))
+      .await
+      .map_err(|e| e.to_string())?
+  }
+
+  pub async fn verify(&self, password: &str, hash: &str) -> Result<bool, String> {
+    let password = password.to_owned();
+    let hash = hash.to_owned();
+
+    web::block(move || verify(&password, &hash))
+      .await
+      .map_err(|e| e.to_string())?
+  }
+
+  pub async fn verify_dummy(&self, password: &str) {
+    let _ = self.verify(password, &self.dummy_hash).await;
+  }
+
+  pub fn needs_rehash(&self, hash: &str) -> bool {
+    self.hasher.needs_rehash(hash)
+  }
+
+  pub fn validate(&self, password: &str) -> Result<(), Value> {
+    let length = password.chars().count();
+
+    if length < self.min_length || length > MAX_PASSWORD_LENGTH {
+      return Err(json!({
+        "name": "password",
+        "message": format!(
+          "Password should be at least {} but not more than {MAX_PASSWORD_LENGTH} characters",
+          self.min_length
+        )
+      }));
+    }
+
+    if self
+      .common_passwords
+      .contains(&password.trim().to_lowercase())
+    {
+      return Err(json!({
+        "name": "password",
+        "message": "Password is too common. Choose a different one"
+      }));
+    }
+
+    Ok(())
+  }
+}
+
+fn verify(password: &str, hash: &str) -> Result<bool, String> {
+  if hash.starts_with("$argon2") {
+    let hash = PasswordHash::new(hash).map_err(|e| e.to_string())?;
+
+    return Ok(
+      Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok(),
+    );
+  }
+
+  if bcrypt_cost(hash).is_some() {
+    return bcrypt::verify(password, hash).map_err(|e| e.to_string());
+  }
+
+  Err("Unknown password hash format".to_owned())
+}
+
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+  let mut parts = hash.split('$');
+
+  match (parts.next(), parts.next(), parts.next()) {
+    (Some(""), Some("2a" | "2b" | "2x" | "2y"), Some(cost)) => cost.parse().ok(),
+    _ => None,
+  }
+}
+
+fn generate_password() -> String {
+  rand::thread_rng()
+    .sample_iter(rand::distributions::Alphanumeric)
+    .take(32)
+    .map(char::from)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::env;
+
+  use super::*;
+
+  fn argon2id(m_cost: u32, t_cost: u32) -> Hasher {
+    Hasher::Argon2id(Params::new(m_cost, t_cost, 1, None).unwrap())
+  }
+
+  #[test]
+  fn verify_picks_the_algorithm_from_the_prefix() {
+    let hash = argon2id(1024, 1).hash("hunter22").unwrap();
+
+    assert!(hash.starts_with("$argon2id$"));
+    assert_eq!(verify("hunter22", &hash), Ok(true));
+    assert_eq!(verify("hunter23", &hash), Ok(false));
+
+    let hash = Hasher::Bcrypt(4).hash("hunter22").unwrap();
+
+    for prefix in ["$2a$", "$2b$", "$2y$"] {
+      let hash = hash.replacen("$2b$", prefix, 1);
+
+      assert_eq!(verify("hunter22", &hash), Ok(true), "{prefix}");
+      assert_eq!(verify("hunter23", &hash), Ok(false), "{prefix}");
+    }
+
+    assert!(verify("hunter22", "hunter22").is_err());
+    assert!(verify("hunter22", "$1$salt$hash").is_err());
+  }
+
+  #[test]
+  fn needs_rehash_when_the_algorithm_changes() {
+    let argon2_hash = argon2id(1024, 1).hash("hunter22").unwrap();
+    let bcrypt_hash = Hasher::Bcrypt(4).hash("hunter22").unwrap();
+
+    assert!(argon2id(1024, 1).needs_rehash(&bcrypt_hash));
+    assert!(Hasher::Bcrypt(4).needs_rehash(&argon2_hash));
+    assert!(argon2id(1024, 1).needs_rehash("garbage"));
+  }
+
+  #[test]
+  fn needs_rehash_when_the_cost_goes_up() {
+    let argon2_hash = argon2id(1024, 1).hash("hunter22").unwrap();
+
+    assert!(!argon2id(1024, 1).needs_rehash(&argon2_hash));
+    assert!(!argon2id(512, 1).needs_rehash(&argon2_hash));
+    assert!(argon2id(2048, 1).needs_rehash(&argon2_hash));
+    assert!(argon2id(1024, 2).needs_rehash(&argon2_hash));
+
+    let bcrypt_hash = Hasher::Bcrypt(5).hash("hunter22").unwrap();
+
+    assert!(!Hasher::Bcrypt(4).needs_rehash(&bcrypt_hash));
+    assert!(!Hasher::Bcrypt(5).needs_rehash(&bcrypt_hash));
+    assert!(Hasher::Bcrypt(6).needs_rehash(&bcrypt_hash));
+  }
+
+  #[test]
+  fn bcrypt_cost_is_read_from_the_hash() {
+    assert_eq!(bcrypt_cost("$2b$12$abcdefghijklmnopqrstuv"), Some(12));
+    assert_eq!(bcrypt_cost("$2y$04$abcdefghijklmnopqrstuv"), Some(4));
+    assert_eq!(bcrypt_cost("$2a$xx$abcdefghijklmnopqrstuv"), None);
+    assert_eq!(bcrypt_cost("$argon2id$v=19$m=1024,t=1,p=1$salt$hash"), None);
+    assert_eq!(bcrypt_cost("2b$12$abcdefghijklmnopqrstuv"), None);
+  }
+
+  #[test]
+  fn validate_checks_length() {
+    let passwords = PasswordConfig {
+      algorithm: Some("bcrypt".to_owned()),
+      bcrypt_cost: Some(4),
+      ..Default::default()
+    }
+    .build()
+    .unwrap();
+
+    assert!(passwords.validate("abc").is_err());
+    assert!(passwords.validate("abcd").is_ok());
+    assert!(passwords.validate(&"a".repeat(50)).is_ok());
+    assert!(passwords.validate(&"a".repeat(51)).is_err());
+    assert!(passwords.validate("ääää").is_ok());
+
+    let passwords = PasswordConfig {
+      algorithm: Some("bcrypt".to_owned()),
+      bcrypt_cost: Some(4),
+      min_length: Some(8),
+      ..Default::default()
+    }
+    .build()
+    .unwrap();
+
+    assert!(passwords.validate("abcdefg").is_err());
+    assert!(passwords.validate("abcdefgh").is_ok());
+  }
+
+  #[test]
+  fn validate_rejects_common_passwords() {
+    let file = env::temp_dir().join(format!("common-passwords-{}.txt", std::process::id()));
+    fs::write(&file, "password\n  Letmein123 \n\n").unwrap();
+
+    let passwords = PasswordConfig {
+      algorithm: Some("bcrypt".to_owned()),
+      bcrypt_cost: Some(4),
+      common_passwords_file: Some(file.to_string_lossy().into_owned()),
+      ..Default::default()
+    }
+    .build();
+
+    fs::remove_file(&file).unwrap();
+
+    let passwords = passwords.unwrap();
+
+    assert!(passwords.validate("password").is_err());
+    assert!(passwords.validate("PASSWORD").is_err());
+    assert!(passwords.validate("letmein123").is_err());
+    assert!(passwords.validate("correct horse").is_ok());
+  }
+}
@@ -0,0 +1,80 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+
+use super::{window_start, AttemptStore, Attempts};
+
+#[derive(Default)]
+pub struct MemoryAttemptStore {
+  attempts: Mutex<HashMap<String, Attempts>>,
+}
+
+impl AttemptStore for MemoryAttemptStore {
+  fn hit(&self, key: &str) -> BoxFuture<'_, Result<Attempts, String>> {
+    let key = key.to_owned();
+
+    Box::pin(async move {
+      let mut attempts = self.attempts.lock().map_err(|e| e.to_string())?;
+      let now = Utc::now().naive_utc();
+
+      let entry = attempts.entry(key).or_insert(Attempts {
+        count: 0,
+        last_attempt_at: now,
+        previous_attempt_at: now,
+      });
+
+      if entry.last_attempt_at <= window_start() {
+        entry.count = 0;
+      }
+
+      entry.count += 1;
+      entry.previous_attempt_at = entry.last_attempt_at;
+      entry.last_attempt_at = now;
+
+      Ok(entry.clone())
+    })
+  }
+
+  fn release(&self, key: &str) -> BoxFuture<'_, Result<(), String>> {
+    let key = key.to_owned();
+
+    Box::pin(async move {
+      let mut attempts = self.attempts.lock().map_err(|e| e.to_string())?;
+
+      if let Some(entry) = attempts.get_mut(&key) {
+        entry.count = (entry.count - 1).max(0);
+      }
+
+      Ok(())
+    })
+  }
+
+  fn clear(&self, key: &str) -> BoxFuture<'_, Result<(), String>> {
+    let key = key.to_owned();
+
+    Box::pin(async move {
+      self
+        .attempts
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&key);
+
+      Ok(())
+    })
+  }
+
+  fn prune(&self) -> BoxFuture<'_, Result<(), String>> {
+    Box::pin(async move {
+      let window_start = window_start();
+
+      self
+        .attempts
+        .lock()
+        .map_err(|e| e.to_string())?
+        .retain(|_, a| a.last_attempt_at > window_start);
+
+      Ok(())
+    })
+  }
+}
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use chrono::{Duration, NaiveDateTime, Utc};
+use deadpool_postgres::Pool;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+mod memory;
+mod postgres;
+
+pub use memory::MemoryAttemptStore;
+pub use postgres::PostgresAttemptStore;
+
+const WINDOW_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct Attempts {
+  pub count: i32,
+  pub last_attempt_at: NaiveDateTime,
+  pub previous_attempt_at: NaiveDateTime,
+}
+
+pub trait AttemptStore: Send + Sync {
+  fn hit(&self, key: &str) -> BoxFuture<'_, Result<Attempts, String>>;
+  fn release(&self, key: &str) -> BoxFuture<'_, Result<(), String>>;
+  fn clear(&self, key: &str) -> BoxFuture<'_, Result<(), String>>;
+  fn prune(&self) -> BoxFuture<'_, Result<(), String>>;
+}
+
+#[derive(Clone, Copy)]
+pub struct Limit {
+  pub name: &'static str,
+  pub free_attempts: i32,
+  pub base_delay_seconds: i64,
+  pub lockout_after: i32,
+  pub lockout_seconds: i64,
+}
+
+pub const ACCOUNT_LOGIN: Limit = Limit {
+  name: "login:account",
+  free_attempts: 3,
+  base_delay_seconds: 1,
+  lockout_after: 10,
+  lockout_seconds: 15 * 60,
+};
+
+pub const IP_LOGIN: Limit = Limit {
+  name: "login:ip",
+  free_attempts: 10,
+  base_delay_seconds: 1,
+  lockout_after: 50,
+  lockout_seconds: 30 * 60,
+};
+
+pub const IP_SIGN_UP: Limit = Limit {
+  name: "sign-up:ip",
+  free_attempts: 10,
+  base_delay_seconds: 30,
+  lockout_after: 20,
+  lockout_seconds: 60 * 60,
+};
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ThrottleConfig {
+  pub backend: Option<String>,
+  pub trust_proxy: Option<bool>,
+}
+
+pub struct Throttle {
+  store: Arc<dyn AttemptStore>,
+  trust_proxy: bool,
+}
+
+impl ThrottleConfig {
+  pub fn build(&self, pool: &Pool) -> Result<Throttle, String> {
+    let store: Arc<dyn AttemptStore> = match self.backend.as_deref().unwrap_or("memory") {
+      "memory" => Arc::new(MemoryAttemptStore::default()),
+      "postgres" => Arc::new(PostgresAttemptStore::new(pool.clone())),
+      backend => return Err(format!("Unknown throttle backend {backend}")),
+    };
+
+    Ok(Throttle {
+      store,
+      trust_proxy: self.trust_proxy.unwrap_or(false),
+    })
+  }
+}
+
+impl Limit {
+  pub fn key(&self, value: &str) -> String {
+    let value: String = value.trim().to_lowercase().chars().take(100).collect();
+    format!("{}:{value}", self.name)
+  }
+
+  fn delay(&self, count: i32) -> Option<Duration> {
+    if count >= self.lockout_after {
+      return Some(Duration::seconds(self.lockout_seconds));
+    }
+
+    if count < self.free_attempts {
+      return None;
+    }
+
+    let exponent = (count - self.free_attempts).min(20) as u32;
+    let seconds = self.base_delay_seconds.saturating_mul(2_i64.pow(exponent));
+
+    Some(Duration::seconds(seconds.min(self.lockout_seconds)))
+  }
+}
+
+impl Throttle {
+  pub fn client_ip<'b>(&self, ip: Option<&'b str>, peer_ip: Option<&'b str>) -> &'b str {
+    let ip = if self.trust_proxy { ip } else { peer_ip };
+
+    ip.unwrap_or("unknown")
+  }
+
+  pub async fn hit(&self, keys: &[(Limit, String)]) -> Result<(), (StatusCode, Value)> {
+    let mut retry_after = 0;
+
+    for (limit, key) in keys {
+      let attempts = self.store.hit(key).await.map_err(|e| {
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          json!({"message": format!("Throttle store error {e}")}),
+        )
+      })?;
+
+      let Some(delay) = limit.delay(attempts.count - 1) else {
+        continue;
+      };
+
+      let milliseconds =
+        (attempts.previous_attempt_at + delay - attempts.last_attempt_at).num_milliseconds();
+      retry_after = retry_after.max((milliseconds + 999) / 1000);
+    }
+
+    if retry_after > 0 {
+      return Err((
+        StatusCode::TOO_MANY_REQUESTS,
+        json!({
+          "name": "throttle",
+          "message": format!("Too many attempts. Try again in {retry_after} seconds"),
+          "retry_after": retry_after
+        }),
+      ));
+    }
+
+    Ok(())
+  }
+
+  pub async fn release(&self, key: &str) -> Result<(), (StatusCode, Value)> {
+    self.store.release(key).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": format!("Throttle store error {e}")}),
+      )
+    })
+  }
+
+  pub async fn clear(&self, key: &str) -> Result<(), (StatusCode, Value)> {
+    self.store.clear(key).await.map_err(|e| {
+      (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        json!({"message": format!("Throttle store error {e}")}),
+      )
+    })
+  }
+
+  pub async fn prune(&self) -> Result<(), String> {
+    self.store.prune().await
+  }
+}
+
+fn window_start() -> NaiveDateTime {
+  Utc::now().naive_utc() - Duration::minutes(WINDOW_MINUTES)
+}
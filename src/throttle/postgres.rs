@@ -0,0 +1,112 @@
+use chrono::{NaiveDateTime, Utc};
+use deadpool_postgres::Pool;
+use futures_util::future::BoxFuture;
+
+use super::{window_start, AttemptStore, Attempts};
+
+pub struct PostgresAttemptStore {
+  pool: Pool,
+}
+
+impl PostgresAttemptStore {
+  pub fn new(pool: Pool) -> PostgresAttemptStore {
+    PostgresAttemptStore { pool }
+  }
+}
+
+impl AttemptStore for PostgresAttemptStore {
+  fn hit(&self, key: &str) -> BoxFuture<'_, Result<Attempts, String>> {
+    let key = key.to_owned();
+
+    Box::pin(async move {
+      let db_client = self.pool.get().await.map_err(|e| e.to_string())?;
+
+      let stmt = "INSERT INTO auth_attempts (key, count, last_attempt_at, previous_attempt_at)
+        VALUES ($1, 1, $2, $2)
+        ON CONFLICT (key) DO UPDATE SET
+          count = CASE WHEN auth_attempts.last_attempt_at > $3 THEN auth_attempts.count + 1 ELSE 1 END,
+          previous_attempt_at = auth_attempts.last_attempt_at,
+          last_attempt_at = $2
+        RETURNING count, last_attempt_at, previous_attempt_at";
+
+      let stmt = db_client.prepare(stmt).await.map_err(|e| e.to_string())?;
+
+      let rows = db_client
+        .query(&stmt, &[&key, &Utc::now().naive_utc(), &window_start()])
+        .await
+        .map_err(|e| e.to_string())?;
+
+      let row = rows.first().ok_or("Error recording attempt".to_owned())?;
+
+      Ok(Attempts {
+        count: row
+          .try_get::<&str, i32>("count")
+          .map_err(|e| e.to_string())?,
+        last_attempt_at: row
+          .try_get::<&str, NaiveDateTime>("last_attempt_at")
+          .map_err(|e| e.to_string())?,
+        previous_attempt_at: row
+          .try_get::<&str, NaiveDateTime>("previous_attempt_at")
+          .map_err(|e| e.to_string())?,
+      })
+    })
+  }
+
+  fn release(&self, key: &str) -> BoxFuture<'_, Result<(), String>> {
+    let key = key.to_owned();
+
+    Box::pin(async move {
+      let db_client = self.pool.get().await.map_err(|e| e.to_string())?;
+
+      let stmt = db_client
+        .prepare("UPDATE auth_attempts SET count = GREATEST(count - 1, 0) WHERE key = $1")
+        .await
+        .map_err(|e| e.to_string())?;
+
+      db_client
+        .execute(&stmt, &[&key])
+        .await
+        .map_err(|e| e.to_string())?;
+
+      Ok(())
+    })
+  }
+
+  fn clear(&self, key: &str) -> BoxFuture<'_, Result<(), String>> {
+    let key = key.to_owned();
+
+    Box::pin(async move {
+      let db_client = self.pool.get().await.map_err(|e| e.to_string())?;
+
+      let stmt = db_client
+        .prepare("DELETE FROM auth_attempts WHERE key = $1")
+        .await
+        .map_err(|e| e.to_string())?;
+
+      db_client
+        .execute(&stmt, &[&key])
+        .await
+        .map_err(|e| e.to_string())?;
+
+      Ok(())
+    })
+  }
+
+  fn prune(&self) -> BoxFuture<'_, Result<(), String>> {
+    Box::pin(async move {
+      let db_client = self.pool.get().await.map_err(|e| e.to_string())?;
+
+      let stmt = db_client
+        .prepare("DELETE FROM auth_attempts WHERE last_attempt_at <= $1")
+        .await
+        .map_err(|e| e.to_string())?;
+
+      db_client
+        .execute(&stmt, &[&window_start()])
+        .await
+        .map_err(|e| e.to_string())?;
+
+      Ok(())
+    })
+  }
+}
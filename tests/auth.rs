@@ -0,0 +1,567 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_http::Request;
+use actix_web::{
+  dev::{Service, ServiceResponse},
+  http::StatusCode,
+  test, Error,
+};
+use forum_api::{mailer::MemoryMailer, password::PasswordConfig, throttle::ThrottleConfig};
+use serde_json::{json, Value};
+
+use common::{bearer, init_app, init_app_with, send, sign_up, TestDb};
+
+async fn post(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  uri: &str,
+  body: Value,
+) -> (StatusCode, Value) {
+  send(app, test::TestRequest::post().uri(uri).set_json(body)).await
+}
+
+fn last_token(mailer: &MemoryMailer, to: &str, subject: &str) -> String {
+  let mail = mailer
+    .sent()
+    .into_iter()
+    .rev()
+    .find(|m| m.to == to && m.subject == subject)
+    .expect("No mail sent");
+
+  mail
+    .body
+    .split("token=")
+    .nth(1)
+    .and_then(|t| t.split_whitespace().next())
+    .unwrap()
+    .to_owned()
+}
+
+async fn expire_email_tokens(db: &TestDb) {
+  db.client()
+    .await
+    .execute(
+      "UPDATE email_tokens SET expires_at = created_at - INTERVAL '1 second'",
+      &[],
+    )
+    .await
+    .unwrap();
+}
+
+async fn create_access_token(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  access_token: &str,
+  scopes: &[&str],
+) -> String {
+  let (status, body) = send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/tokens")
+      .insert_header(bearer(access_token))
+      .set_json(json!({ "name": "script", "scopes": scopes })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  body["data"]["token"].as_str().unwrap().to_owned()
+}
+
+async fn password_hash(db: &TestDb, username: &str) -> String {
+  db.client()
+    .await
+    .query_one(
+      "SELECT password_hash FROM users WHERE username = $1",
+      &[&username],
+    )
+    .await
+    .unwrap()
+    .get(0)
+}
+
+async fn is_signed_in(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  token: &str,
+) -> bool {
+  let (_, body) = send(
+    app,
+    test::TestRequest::get()
+      .uri("/auth")
+      .insert_header(bearer(token)),
+  )
+  .await;
+
+  body["success"] == true
+}
+
+#[actix_web::test]
+async fn refresh_rotates_once() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/sign-up")
+      .set_json(json!({
+        "username": "rotator",
+        "password": "password123",
+        "confirm_password": "password123"
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let first = body["data"]["refresh_token"].as_str().unwrap().to_owned();
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/refresh")
+      .set_json(json!({ "refresh_token": first })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let second = body["data"]["refresh_token"].as_str().unwrap().to_owned();
+
+  let tokens: i64 = db
+    .client()
+    .await
+    .query_one(
+      "SELECT COUNT(*) FROM refresh_tokens WHERE used_at IS NULL",
+      &[],
+    )
+    .await
+    .unwrap()
+    .get(0);
+
+  assert_eq!(tokens, 1);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/refresh")
+      .set_json(json!({ "refresh_token": first })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/refresh")
+      .set_json(json!({ "refresh_token": second })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+}
+
+#[actix_web::test]
+async fn verify_email_token_works_once() {
+  let db = TestDb::new().await;
+  let mailer = Arc::new(MemoryMailer::default());
+  let app = init_app(&db, mailer.clone(), Default::default()).await;
+
+  for (username, email) in [
+    ("verified", "verified@example.com"),
+    ("expired", "expired@example.com"),
+  ] {
+    let (status, body) = post(
+      &app,
+      "/auth/sign-up",
+      json!({
+        "username": username,
+        "password": "password123",
+        "confirm_password": "password123",
+        "email": email
+      }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+  }
+
+  let token = last_token(&mailer, "verified@example.com", "Verify your email address");
+
+  let (status, body) = post(&app, "/auth/verify-email", json!({ "token": token })).await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let verified: bool = db
+    .client()
+    .await
+    .query_one(
+      "SELECT email_verified_at IS NOT NULL FROM users WHERE username = 'verified'",
+      &[],
+    )
+    .await
+    .unwrap()
+    .get(0);
+
+  assert!(verified);
+
+  let (status, body) = post(&app, "/auth/verify-email", json!({ "token": token })).await;
+
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+  let token = last_token(&mailer, "expired@example.com", "Verify your email address");
+
+  expire_email_tokens(&db).await;
+
+  let (status, body) = post(&app, "/auth/verify-email", json!({ "token": token })).await;
+
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[actix_web::test]
+async fn reset_password_token_works_once() {
+  let db = TestDb::new().await;
+  let mailer = Arc::new(MemoryMailer::default());
+  let app = init_app(&db, mailer.clone(), Default::default()).await;
+
+  let (status, body) = post(
+    &app,
+    "/auth/sign-up",
+    json!({
+      "username": "forgetful",
+      "password": "password123",
+      "confirm_password": "password123",
+      "email": "forgetful@example.com"
+    }),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let personal_token = create_access_token(
+    &app,
+    body["data"]["access_token"].as_str().unwrap(),
+    &["read"],
+  )
+  .await;
+
+  assert!(is_signed_in(&app, &personal_token).await);
+
+  let token = last_token(
+    &mailer,
+    "forgetful@example.com",
+    "Verify your email address",
+  );
+
+  let (status, body) = post(&app, "/auth/verify-email", json!({ "token": token })).await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = post(
+    &app,
+    "/auth/forgot-password",
+    json!({ "email": "forgetful@example.com" }),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let token = last_token(&mailer, "forgetful@example.com", "Reset your password");
+
+  let reset = |token: String, password: &str| {
+    json!({
+      "token": token,
+      "password": password,
+      "confirm_password": password
+    })
+  };
+
+  let (status, body) = post(
+    &app,
+    "/auth/reset-password",
+    reset(token.clone(), "newpassword1"),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  assert!(!is_signed_in(&app, &personal_token).await);
+
+  let (status, body) = post(
+    &app,
+    "/auth/sign-in",
+    json!({ "username": "forgetful", "password": "newpassword1" }),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = post(&app, "/auth/reset-password", reset(token, "newpassword2")).await;
+
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+  let (status, body) = post(
+    &app,
+    "/auth/forgot-password",
+    json!({ "email": "forgetful@example.com" }),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let token = last_token(&mailer, "forgetful@example.com", "Reset your password");
+
+  expire_email_tokens(&db).await;
+
+  let (status, body) = post(&app, "/auth/reset-password", reset(token, "newpassword3")).await;
+
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+  let (status, body) = post(
+    &app,
+    "/auth/sign-in",
+    json!({ "username": "forgetful", "password": "newpassword1" }),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[actix_web::test]
+async fn change_password_revokes_personal_access_tokens() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "changer").await;
+  let personal_token = create_access_token(&app, &access_token, &["read"]).await;
+
+  assert!(is_signed_in(&app, &personal_token).await);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/password")
+      .insert_header(bearer(&access_token))
+      .set_json(json!({
+        "current_password": "password123",
+        "password": "newpassword1",
+        "confirm_password": "newpassword1"
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(!is_signed_in(&app, &personal_token).await);
+}
+
+#[actix_web::test]
+async fn sign_out_all_revokes_personal_access_tokens() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "leaver").await;
+  let personal_token = create_access_token(&app, &access_token, &["read"]).await;
+
+  assert!(is_signed_in(&app, &personal_token).await);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/sign-out-all")
+      .insert_header(bearer(&access_token)),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(!is_signed_in(&app, &personal_token).await);
+}
+
+#[actix_web::test]
+async fn unknown_token_scopes_are_rejected() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "scripter").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/tokens")
+      .insert_header(bearer(&access_token))
+      .set_json(json!({ "name": "admin", "scopes": ["admin"] })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[actix_web::test]
+async fn moderate_scope_is_needed_to_edit_and_delete_posts() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "moderator").await;
+  let post_token = create_access_token(&app, &access_token, &["post"]).await;
+  let moderate_token = create_access_token(&app, &access_token, &["moderate"]).await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&post_token))
+      .set_json(json!({
+        "title": "Posted by a bot",
+        "body": "Edited and deleted by another bot",
+        "hashtags": ["bots"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let uri = format!("/posts/{}", body["data"]["id"]);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&moderate_token))
+      .set_json(
+        json!({ "title": "Not mine", "body": "Moderators cannot post", "hashtags": ["bots"] }),
+      ),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+  let edit = |token: &str| {
+    test::TestRequest::patch()
+      .uri(&uri)
+      .insert_header(bearer(token))
+      .set_json(json!({ "title": "Edited by a moderator" }))
+  };
+
+  let (status, body) = send(&app, edit(&post_token)).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+  assert_eq!(body["error"]["name"], "scope");
+
+  let (status, body) = send(&app, edit(&moderate_token)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let delete = |token: &str| {
+    test::TestRequest::delete()
+      .uri(&uri)
+      .insert_header(bearer(token))
+  };
+
+  let (status, body) = send(&app, delete(&post_token)).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+  let (status, body) = send(&app, delete(&moderate_token)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[actix_web::test]
+async fn access_tokens_cannot_manage_the_account() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "bot_owner").await;
+  let personal_token = create_access_token(
+    &app,
+    &access_token,
+    &["read", "post", "comment", "moderate"],
+  )
+  .await;
+
+  for uri in ["/auth/tokens", "/auth/sessions", "/auth/2fa"] {
+    let (status, body) = send(
+      &app,
+      test::TestRequest::get()
+        .uri(uri)
+        .insert_header(bearer(&personal_token)),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::FORBIDDEN, "{uri} {body}");
+    assert_eq!(body["error"]["name"], "scope", "{uri}");
+
+    let (status, body) = send(
+      &app,
+      test::TestRequest::get()
+        .uri(uri)
+        .insert_header(bearer(&access_token)),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{uri} {body}");
+  }
+
+  for (uri, body) in [
+    (
+      "/auth/tokens",
+      json!({ "name": "escalated", "scopes": ["read"] }),
+    ),
+    ("/auth/2fa/enroll", json!({})),
+    ("/auth/email", json!({ "email": "bot@example.com" })),
+    (
+      "/auth/password",
+      json!({
+        "current_password": "password123",
+        "password": "newpassword1",
+        "confirm_password": "newpassword1"
+      }),
+    ),
+    ("/auth/sign-out-all", json!({})),
+  ] {
+    let (status, body) = send(
+      &app,
+      test::TestRequest::post()
+        .uri(uri)
+        .insert_header(bearer(&personal_token))
+        .set_json(body),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::FORBIDDEN, "{uri} {body}");
+    assert_eq!(body["error"]["name"], "scope", "{uri}");
+  }
+}
+
+#[actix_web::test]
+async fn sign_in_rehashes_bcrypt_passwords_with_argon2id() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  sign_up(&app, "henry").await;
+
+  let bcrypt_hash = password_hash(&db, "henry").await;
+  assert!(bcrypt_hash.starts_with("$2b$04$"), "{bcrypt_hash}");
+
+  let app = init_app_with(
+    &db,
+    Arc::new(MemoryMailer::default()),
+    Default::default(),
+    PasswordConfig {
+      algorithm: Some("argon2id".to_owned()),
+      argon2_memory_kib: Some(1024),
+      argon2_iterations: Some(1),
+      ..Default::default()
+    },
+    ThrottleConfig::default(),
+  )
+  .await;
+
+  let sign_in = json!({ "username": "henry", "password": "password123" });
+
+  let (status, body) = post(&app, "/auth/sign-in", sign_in.clone()).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let argon2_hash = password_hash(&db, "henry").await;
+  assert!(argon2_hash.starts_with("$argon2id$"), "{argon2_hash}");
+
+  let (status, body) = post(&app, "/auth/sign-in", sign_in).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(password_hash(&db, "henry").await, argon2_hash);
+}
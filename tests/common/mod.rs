@@ -0,0 +1,261 @@
+#![allow(dead_code)]
+
+use std::{
+  collections::HashMap,
+  net::SocketAddr,
+  process,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+};
+
+use actix_http::Request;
+use actix_web::{
+  dev::{Service, ServiceResponse},
+  http::StatusCode,
+  test, web, App, Error,
+};
+use chrono::Utc;
+use deadpool_postgres::{Client, Pool, Runtime};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio_postgres::NoTls;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use forum_api::{
+  app,
+  mailer::MemoryMailer,
+  middleware::auth::Authenticate,
+  oidc::{OidcProviderConfig, OidcProviders},
+  password::PasswordConfig,
+  throttle::ThrottleConfig,
+};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Deserialize)]
+struct Config {
+  pg: deadpool_postgres::Config,
+}
+
+pub struct TestDb {
+  pub pool: Pool,
+  config: deadpool_postgres::Config,
+  schema: String,
+}
+
+impl TestDb {
+  pub async fn new() -> TestDb {
+    TestDb::from_sql(include_str!("../../schema.sql")).await
+  }
+
+  pub async fn from_sql(sql: &str) -> TestDb {
+    dotenvy::dotenv().ok();
+
+    let config: Config = config::Config::builder()
+      .add_source(config::Environment::default())
+      .build()
+      .unwrap()
+      .try_deserialize()
+      .expect("Tests need the PG.* settings from .env");
+
+    let schema = format!(
+      "test_{}_{}",
+      process::id(),
+      COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+
+    let setup_pool = config.pg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
+
+    let client = setup_pool
+      .get()
+      .await
+      .expect("Could not connect to Postgres");
+
+    client
+      .batch_execute(&format!(
+        "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema};"
+      ))
+      .await
+      .unwrap();
+
+    client
+      .batch_execute(&sql.replace("public.", &format!("{schema}.")))
+      .await
+      .unwrap();
+
+    let mut pg = config.pg.clone();
+    pg.options = Some(format!("-c search_path={schema}"));
+
+    TestDb {
+      pool: pg.create_pool(Some(Runtime::Tokio1), NoTls).unwrap(),
+      config: config.pg,
+      schema,
+    }
+  }
+
+  pub async fn client(&self) -> Client {
+    self.pool.get().await.unwrap()
+  }
+}
+
+impl Drop for TestDb {
+  fn drop(&mut self) {
+    let config = self.config.clone();
+    let schema = self.schema.clone();
+
+    std::thread::spawn(move || {
+      tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async move {
+          let pool = config.create_pool(Some(Runtime::Tokio1), NoTls).unwrap();
+
+          if let Ok(client) = pool.get().await {
+            let _ = client
+              .batch_execute(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+              .await;
+          }
+        })
+    })
+    .join()
+    .ok();
+  }
+}
+
+pub async fn init_app(
+  db: &TestDb,
+  mailer: Arc<MemoryMailer>,
+  oidc: HashMap<String, OidcProviderConfig>,
+) -> impl Service<Request, Response = ServiceResponse, Error = Error> {
+  init_app_with(
+    db,
+    mailer,
+    oidc,
+    PasswordConfig {
+      algorithm: Some("bcrypt".to_owned()),
+      bcrypt_cost: Some(4),
+      ..Default::default()
+    },
+    ThrottleConfig::default(),
+  )
+  .await
+}
+
+pub async fn init_app_with(
+  db: &TestDb,
+  mailer: Arc<MemoryMailer>,
+  oidc: HashMap<String, OidcProviderConfig>,
+  passwords: PasswordConfig,
+  throttle: ThrottleConfig,
+) -> impl Service<Request, Response = ServiceResponse, Error = Error> {
+  let passwords = passwords.build().unwrap();
+
+  let throttle = throttle.build(&db.pool).unwrap();
+
+  test::init_service(
+    App::new()
+      .app_data(web::Data::new(db.pool.clone()))
+      .app_data(web::Data::from(
+        mailer as Arc<dyn forum_api::mailer::Mailer>,
+      ))
+      .app_data(web::Data::new(OidcProviders::new(oidc).unwrap()))
+      .app_data(web::Data::new(passwords))
+      .app_data(web::Data::new(throttle))
+      .wrap(Authenticate)
+      .configure(app),
+  )
+  .await
+}
+
+pub fn peer_addr() -> SocketAddr {
+  let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+  SocketAddr::from(([10, (n >> 16) as u8, (n >> 8) as u8, n as u8], 40000))
+}
+
+pub async fn send(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  req: test::TestRequest,
+) -> (StatusCode, Value) {
+  let res = test::call_service(app, req.peer_addr(peer_addr()).to_request()).await;
+  let status = res.status();
+  let body = test::read_body(res).await;
+
+  (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+pub async fn sign_up(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  username: &str,
+) -> String {
+  let (status, body) = send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/sign-up")
+      .set_json(json!({
+        "username": username,
+        "password": "password123",
+        "confirm_password": "password123"
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  body["data"]["access_token"].as_str().unwrap().to_owned()
+}
+
+pub fn bearer(token: &str) -> (&'static str, String) {
+  ("Authorization", format!("Bearer {token}"))
+}
+
+pub fn totp_step() -> i64 {
+  Utc::now().timestamp() / 30
+}
+
+pub fn totp_code(secret: &str, step: i64) -> String {
+  let secret = Secret::Encoded(secret.to_owned()).to_bytes().unwrap();
+
+  TOTP::new(Algorithm::SHA1, 6, 0, 30, secret, None, "test".to_owned())
+    .unwrap()
+    .generate(step as u64 * 30)
+}
+
+pub async fn enable_two_factor(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  access_token: &str,
+) -> (String, Vec<String>) {
+  let (status, body) = send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/2fa/enroll")
+      .insert_header(bearer(access_token)),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let secret = body["data"]["secret"].as_str().unwrap().to_owned();
+
+  let (status, body) = send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/2fa/confirm")
+      .insert_header(bearer(access_token))
+      .set_json(json!({ "code": totp_code(&secret, totp_step() - 1) })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let recovery_codes = body["data"]["recovery_codes"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|c| c.as_str().unwrap().to_owned())
+    .collect();
+
+  (secret, recovery_codes)
+}
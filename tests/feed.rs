@@ -0,0 +1,144 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_http::Request;
+use actix_web::{
+  dev::{Service, ServiceResponse},
+  http::StatusCode,
+  test, Error,
+};
+use serde_json::{json, Value};
+
+use common::{bearer, init_app, send, sign_up, TestDb};
+
+async fn post(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  access_token: &str,
+  uri: &str,
+  body: Value,
+) -> Value {
+  let (status, body) = send(
+    app,
+    test::TestRequest::post()
+      .uri(uri)
+      .insert_header(bearer(access_token))
+      .set_json(body),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  body
+}
+
+async fn create_post(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  access_token: &str,
+  title: &str,
+  hashtags: &[&str],
+) -> i64 {
+  let body = post(
+    app,
+    access_token,
+    "/posts",
+    json!({ "title": title, "body": "Feed post", "hashtags": hashtags }),
+  )
+  .await;
+
+  body["data"]["id"].as_i64().unwrap()
+}
+
+async fn feed(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  access_token: &str,
+  uri: &str,
+) -> Vec<Value> {
+  let (status, body) = send(
+    app,
+    test::TestRequest::get()
+      .uri(uri)
+      .insert_header(bearer(access_token)),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  body["data"].as_array().unwrap().clone()
+}
+
+fn titles(posts: &[Value]) -> Vec<&str> {
+  let mut titles: Vec<&str> = posts.iter().map(|p| p["title"].as_str().unwrap()).collect();
+  titles.sort();
+  titles
+}
+
+#[actix_web::test]
+async fn feed_has_followed_hashtags_and_users() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  let reader = sign_up(&app, "reader").await;
+  let alice = sign_up(&app, "alice").await;
+  let bob = sign_up(&app, "bob").await;
+  let carol = sign_up(&app, "carol").await;
+
+  create_post(&app, &alice, "By alice", &["misc"]).await;
+  create_post(&app, &alice, "By alice about rust", &["rust"]).await;
+  let tagged = create_post(&app, &bob, "By bob about rust", &["rust", "async"]).await;
+  create_post(&app, &carol, "By carol", &["cooking"]).await;
+
+  post(&app, &reader, "/hashtags/rust/follow", json!({})).await;
+
+  let alice_id: i32 = db
+    .client()
+    .await
+    .query_one("SELECT id FROM users WHERE username = 'alice'", &[])
+    .await
+    .unwrap()
+    .get(0);
+
+  post(
+    &app,
+    &reader,
+    &format!("/users/{alice_id}/follow"),
+    json!({}),
+  )
+  .await;
+
+  for (token, comment) in [(&alice, "One"), (&carol, "Two"), (&carol, "Three")] {
+    post(
+      &app,
+      token,
+      &format!("/posts/{tagged}/comments"),
+      json!({ "body": comment }),
+    )
+    .await;
+  }
+
+  for token in [&reader, &carol] {
+    post(&app, token, &format!("/posts/{tagged}/save"), json!({})).await;
+  }
+
+  let posts = feed(&app, &reader, "/feed").await;
+
+  assert_eq!(
+    titles(&posts),
+    ["By alice", "By alice about rust", "By bob about rust"]
+  );
+
+  let tagged = posts
+    .iter()
+    .find(|p| p["title"] == "By bob about rust")
+    .unwrap();
+
+  assert_eq!(tagged["comments"], 3);
+  assert_eq!(tagged["saves"], 2);
+  assert_eq!(tagged["saved"], true);
+
+  let posts = feed(&app, &reader, "/feed?include_users=false").await;
+
+  assert_eq!(titles(&posts), ["By alice about rust", "By bob about rust"]);
+
+  assert!(feed(&app, &carol, "/feed").await.is_empty());
+}
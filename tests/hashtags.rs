@@ -0,0 +1,90 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+
+use common::{bearer, init_app, send, sign_up, TestDb};
+
+#[actix_web::test]
+async fn hashtag_posts_of_missing_hashtag_is_not_found() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+  let token = sign_up(&app, "tagger").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&token))
+      .set_json(json!({
+        "title": "Tagged",
+        "body": "A tagged post",
+        "hashtags": ["present"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get().uri("/hashtags/present/posts"),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get().uri("/hashtags/missing/posts"),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  assert_eq!(body["success"], false);
+}
+
+#[actix_web::test]
+async fn suggestions_need_a_prefix() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+  let token = sign_up(&app, "tagger").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&token))
+      .set_json(json!({
+        "title": "Tagged",
+        "body": "A tagged post",
+        "hashtags": ["rust", "python"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  for prefix in ["", "%25", "_", "%20%25"] {
+    let (status, body) = send(
+      &app,
+      test::TestRequest::get().uri(&format!("/hashtags/suggest?prefix={prefix}")),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{prefix} {body}");
+  }
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get().uri("/hashtags/suggest?prefix=ru"),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"].as_array().unwrap().len(), 1);
+  assert_eq!(body["data"][0]["name"], "rust");
+}
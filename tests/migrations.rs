@@ -0,0 +1,79 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::TestDb;
+
+fn migrations() -> String {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+
+  let mut files: Vec<_> = fs::read_dir(dir)
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.extension().is_some_and(|e| e == "sql"))
+    .collect();
+
+  files.sort();
+
+  files
+    .iter()
+    .map(|path| fs::read_to_string(path).unwrap())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+async fn describe(db: &TestDb) -> Vec<String> {
+  let stmt = "SELECT 'column ' || table_name || '.' || column_name || ' ' || udt_name || ' '
+        || COALESCE(character_maximum_length::TEXT, '') || ' ' || is_nullable || ' '
+        || COALESCE(column_default, '') || ' ' || COALESCE(generation_expression, '')
+      FROM information_schema.columns WHERE table_schema = current_schema()
+    UNION ALL
+    SELECT 'constraint ' || conrelid::regclass::TEXT || ' ' || conname || ' ' || pg_get_constraintdef(oid)
+      FROM pg_constraint WHERE connamespace = current_schema()::regnamespace
+    UNION ALL
+    SELECT 'index ' || indexname || ' ' || indexdef
+      FROM pg_indexes WHERE schemaname = current_schema()
+    UNION ALL
+    SELECT 'sequence ' || sequence_name
+      FROM information_schema.sequences WHERE sequence_schema = current_schema()
+    UNION ALL
+    SELECT 'enum ' || t.typname || ' ' || string_agg(e.enumlabel, ',' ORDER BY e.enumsortorder)
+      FROM pg_type t INNER JOIN pg_enum e ON e.enumtypid = t.oid
+      WHERE t.typnamespace = current_schema()::regnamespace GROUP BY t.typname";
+
+  let client = db.client().await;
+
+  let schema: String = client
+    .query_one("SELECT current_schema()", &[])
+    .await
+    .unwrap()
+    .get(0);
+
+  let mut rows: Vec<String> = client
+    .query(stmt, &[])
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| row.get::<_, String>(0).replace(&schema, "public"))
+    .collect();
+
+  rows.sort();
+  rows
+}
+
+#[actix_web::test]
+async fn migrations_rebuild_schema() {
+  let schema = TestDb::new().await;
+  let migrated = TestDb::from_sql(&migrations()).await;
+
+  let expected = describe(&schema).await;
+  let actual = describe(&migrated).await;
+
+  let missing: Vec<_> = expected.iter().filter(|r| !actual.contains(r)).collect();
+  let extra: Vec<_> = actual.iter().filter(|r| !expected.contains(r)).collect();
+
+  assert!(
+    missing.is_empty() && extra.is_empty(),
+    "missing from migrations: {missing:#?}\nnot in schema.sql: {extra:#?}"
+  );
+}
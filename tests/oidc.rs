@@ -0,0 +1,370 @@
+mod common;
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use actix_http::Request;
+use actix_web::{
+  dev::{Service, ServiceResponse},
+  http::StatusCode,
+  test, web, App, Error, HttpResponse, HttpServer,
+};
+use chrono::{Duration, Utc};
+use forum_api::{mailer::MemoryMailer, oidc::OidcProviderConfig};
+use openidconnect::{
+  core::{
+    CoreIdToken, CoreIdTokenClaims, CoreIdTokenFields, CoreJsonWebKeySet, CoreJwsSigningAlgorithm,
+    CoreProviderMetadata, CoreResponseType, CoreRsaPrivateSigningKey, CoreSubjectIdentifierType,
+    CoreTokenResponse, CoreTokenType,
+  },
+  url::Url,
+  AccessToken, Audience, AuthUrl, EmptyAdditionalClaims, EmptyAdditionalProviderMetadata,
+  EmptyExtraTokenFields, EndUserEmail, EndUserUsername, IssuerUrl, JsonWebKeyId, JsonWebKeySetUrl,
+  Nonce, PkceCodeChallenge, PkceCodeVerifier, PrivateSigningKey, ResponseTypes, StandardClaims,
+  SubjectIdentifier, TokenUrl,
+};
+use serde_json::{json, Value};
+
+use common::{bearer, init_app, send, sign_up, TestDb};
+
+const CLIENT_ID: &str = "forum";
+
+#[derive(Clone, Default)]
+struct Identity {
+  subject: &'static str,
+  email: Option<&'static str>,
+  username: Option<&'static str>,
+  nonce: Option<&'static str>,
+}
+
+struct Grant {
+  code_challenge: String,
+  nonce: String,
+  identity: Identity,
+}
+
+struct MockProvider {
+  issuer: String,
+  key: CoreRsaPrivateSigningKey,
+  grants: Mutex<HashMap<String, Grant>>,
+}
+
+impl MockProvider {
+  async fn start() -> Arc<MockProvider> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let provider = Arc::new(MockProvider {
+      issuer: format!("http://127.0.0.1:{port}"),
+      key: CoreRsaPrivateSigningKey::from_pem(
+        include_str!("fixtures/oidc_signing_key.pem"),
+        Some(JsonWebKeyId::new("test".to_owned())),
+      )
+      .unwrap(),
+      grants: Mutex::new(HashMap::new()),
+    });
+
+    let data = web::Data::from(provider.clone());
+
+    let server = HttpServer::new(move || {
+      App::new()
+        .app_data(data.clone())
+        .route(
+          "/.well-known/openid-configuration",
+          web::get().to(discovery),
+        )
+        .route("/jwks", web::get().to(jwks))
+        .route("/token", web::post().to(token))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+
+    actix_web::rt::spawn(server);
+
+    provider
+  }
+
+  fn config(&self) -> HashMap<String, OidcProviderConfig> {
+    HashMap::from([(
+      "mock".to_owned(),
+      OidcProviderConfig {
+        issuer_url: self.issuer.clone(),
+        client_id: CLIENT_ID.to_owned(),
+        client_secret: Some("secret".to_owned()),
+        redirect_url: "http://localhost/auth/callback".to_owned(),
+        scopes: None,
+        display_name: Some("Mock".to_owned()),
+      },
+    )])
+  }
+
+  fn authorize(&self, authorization_url: &str, identity: Identity) -> (String, String) {
+    let url = Url::parse(authorization_url).unwrap();
+    let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+    assert!(authorization_url.starts_with(&format!("{}/authorize?", self.issuer)));
+    assert_eq!(query["client_id"], CLIENT_ID);
+    assert_eq!(query["code_challenge_method"], "S256");
+
+    let code = format!("code-{}", self.grants.lock().unwrap().len());
+
+    self.grants.lock().unwrap().insert(
+      code.clone(),
+      Grant {
+        code_challenge: query["code_challenge"].clone(),
+        nonce: query["nonce"].clone(),
+        identity,
+      },
+    );
+
+    (code, query["state"].clone())
+  }
+}
+
+async fn discovery(provider: web::Data<MockProvider>) -> HttpResponse {
+  let metadata = CoreProviderMetadata::new(
+    IssuerUrl::new(provider.issuer.clone()).unwrap(),
+    AuthUrl::new(format!("{}/authorize", provider.issuer)).unwrap(),
+    JsonWebKeySetUrl::new(format!("{}/jwks", provider.issuer)).unwrap(),
+    vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+    vec![CoreSubjectIdentifierType::Public],
+    vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+    EmptyAdditionalProviderMetadata {},
+  )
+  .set_token_endpoint(Some(
+    TokenUrl::new(format!("{}/token", provider.issuer)).unwrap(),
+  ));
+
+  HttpResponse::Ok().json(metadata)
+}
+
+async fn jwks(provider: web::Data<MockProvider>) -> HttpResponse {
+  HttpResponse::Ok().json(CoreJsonWebKeySet::new(vec![provider
+    .key
+    .as_verification_key()]))
+}
+
+async fn token(
+  provider: web::Data<MockProvider>,
+  form: web::Form<HashMap<String, String>>,
+) -> HttpResponse {
+  let grant = form
+    .get("code")
+    .and_then(|code| provider.grants.lock().unwrap().remove(code));
+
+  let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+
+  let Some(grant) = grant.filter(|g| {
+    PkceCodeChallenge::from_code_verifier_sha256(&PkceCodeVerifier::new(verifier)).as_str()
+      == g.code_challenge
+  }) else {
+    return HttpResponse::BadRequest().json(json!({"error": "invalid_grant"}));
+  };
+
+  let identity = grant.identity;
+  let access_token = AccessToken::new(format!("access-{}", identity.subject));
+  let now = Utc::now();
+
+  let claims = CoreIdTokenClaims::new(
+    IssuerUrl::new(provider.issuer.clone()).unwrap(),
+    vec![Audience::new(CLIENT_ID.to_owned())],
+    now + Duration::minutes(5),
+    now,
+    StandardClaims::new(SubjectIdentifier::new(identity.subject.to_owned()))
+      .set_email(identity.email.map(|e| EndUserEmail::new(e.to_owned())))
+      .set_email_verified(identity.email.map(|_| true))
+      .set_preferred_username(
+        identity
+          .username
+          .map(|u| EndUserUsername::new(u.to_owned())),
+      ),
+    EmptyAdditionalClaims {},
+  )
+  .set_nonce(Some(Nonce::new(
+    identity.nonce.map(str::to_owned).unwrap_or(grant.nonce),
+  )));
+
+  let id_token = CoreIdToken::new(
+    claims,
+    &provider.key,
+    CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+    Some(&access_token),
+    None,
+  )
+  .unwrap();
+
+  HttpResponse::Ok().json(CoreTokenResponse::new(
+    access_token,
+    CoreTokenType::Bearer,
+    CoreIdTokenFields::new(Some(id_token), EmptyExtraTokenFields {}),
+  ))
+}
+
+async fn start(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  uri: &str,
+  token: Option<&str>,
+) -> String {
+  let mut req = test::TestRequest::post().uri(uri);
+
+  if let Some(token) = token {
+    req = req.insert_header(bearer(token));
+  }
+
+  let (status, body) = send(app, req).await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  body["data"]["authorization_url"]
+    .as_str()
+    .unwrap()
+    .to_owned()
+}
+
+async fn callback(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  code: &str,
+  state: &str,
+) -> (StatusCode, Value) {
+  send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/oidc/mock/callback")
+      .set_json(json!({ "code": code, "state": state })),
+  )
+  .await
+}
+
+#[actix_web::test]
+async fn sign_in_creates_user_and_state_is_single_use() {
+  let db = TestDb::new().await;
+  let provider = MockProvider::start().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), provider.config()).await;
+
+  let identity = Identity {
+    subject: "alice-sub",
+    email: Some("alice@example.com"),
+    username: Some("alice"),
+    ..Default::default()
+  };
+
+  let url = start(&app, "/auth/oidc/mock", None).await;
+  let (code, state) = provider.authorize(&url, identity.clone());
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["username"], "alice");
+  assert!(body["data"]["access_token"].is_string());
+
+  let user_id = body["data"]["id"].clone();
+
+  let (code, _) = provider.authorize(&url, identity.clone());
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(body["error"]["name"], "state");
+
+  let url = start(&app, "/auth/oidc/mock", None).await;
+  let (code, state) = provider.authorize(&url, identity);
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["id"], user_id);
+}
+
+#[actix_web::test]
+async fn wrong_pkce_verifier_is_rejected() {
+  let db = TestDb::new().await;
+  let provider = MockProvider::start().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), provider.config()).await;
+
+  let url = start(&app, "/auth/oidc/mock", None).await;
+  let (code, state) = provider.authorize(
+    &url,
+    Identity {
+      subject: "bob-sub",
+      ..Default::default()
+    },
+  );
+
+  db.client()
+    .await
+    .execute(
+      "UPDATE oidc_states SET pkce_verifier = $1",
+      &[&"x".repeat(43)],
+    )
+    .await
+    .unwrap();
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+}
+
+#[actix_web::test]
+async fn nonce_mismatch_is_rejected() {
+  let db = TestDb::new().await;
+  let provider = MockProvider::start().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), provider.config()).await;
+
+  let url = start(&app, "/auth/oidc/mock", None).await;
+  let (code, state) = provider.authorize(
+    &url,
+    Identity {
+      subject: "carol-sub",
+      nonce: Some("another-nonce"),
+      ..Default::default()
+    },
+  );
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+  let users: i64 = db
+    .client()
+    .await
+    .query_one("SELECT COUNT(*) FROM user_identities", &[])
+    .await
+    .unwrap()
+    .get(0);
+
+  assert_eq!(users, 0);
+}
+
+#[actix_web::test]
+async fn link_identity_to_signed_in_user() {
+  let db = TestDb::new().await;
+  let provider = MockProvider::start().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), provider.config()).await;
+
+  let dave = sign_up(&app, "dave").await;
+  let erin = sign_up(&app, "erin").await;
+
+  let identity = Identity {
+    subject: "dave-sub",
+    username: Some("dave_elsewhere"),
+    ..Default::default()
+  };
+
+  let url = start(&app, "/auth/oidc/mock/link", Some(&dave)).await;
+  let (code, state) = provider.authorize(&url, identity.clone());
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["linked"], true);
+
+  let url = start(&app, "/auth/oidc/mock", None).await;
+  let (code, state) = provider.authorize(&url, identity.clone());
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["username"], "dave");
+
+  let url = start(&app, "/auth/oidc/mock/link", Some(&erin)).await;
+  let (code, state) = provider.authorize(&url, identity);
+
+  let (status, body) = callback(&app, &code, &state).await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+}
@@ -0,0 +1,383 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+
+use common::{bearer, init_app, send, sign_up, TestDb};
+
+async fn fail_hashtag_relationships(db: &TestDb) {
+  db.client()
+    .await
+    .batch_execute(
+      "CREATE FUNCTION fail_insert() RETURNS trigger LANGUAGE plpgsql
+        AS $$ BEGIN RAISE EXCEPTION 'injected failure'; END $$;
+      CREATE TRIGGER fail_insert BEFORE INSERT ON posts_hashtags_relationship
+        FOR EACH ROW EXECUTE FUNCTION fail_insert();",
+    )
+    .await
+    .unwrap();
+}
+
+async fn count(db: &TestDb, stmt: &str) -> i64 {
+  db.client().await.query_one(stmt, &[]).await.unwrap().get(0)
+}
+
+#[actix_web::test]
+async fn create_post_rolls_back_when_hashtags_fail() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+  let token = sign_up(&app, "rollback").await;
+
+  fail_hashtag_relationships(&db).await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&token))
+      .set_json(json!({
+        "title": "Rolled back",
+        "body": "This post should not be saved",
+        "hashtags": ["rollbacktag"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{body}");
+
+  assert_eq!(count(&db, "SELECT COUNT(*) FROM posts").await, 0);
+  assert_eq!(
+    count(
+      &db,
+      "SELECT COUNT(*) FROM hashtags WHERE name = 'rollbacktag'"
+    )
+    .await,
+    0
+  );
+}
+
+#[actix_web::test]
+async fn update_post_rolls_back_when_hashtags_fail() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+  let token = sign_up(&app, "rollback").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&token))
+      .set_json(json!({
+        "title": "Original title",
+        "body": "Original body",
+        "hashtags": ["original"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let id = body["data"]["id"].as_i64().unwrap();
+
+  fail_hashtag_relationships(&db).await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::patch()
+      .uri(&format!("/posts/{id}"))
+      .insert_header(bearer(&token))
+      .set_json(json!({
+        "title": "Updated title",
+        "hashtags": ["rollbacktag"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{body}");
+
+  assert_eq!(
+    count(
+      &db,
+      "SELECT COUNT(*) FROM posts WHERE title = 'Original title'"
+    )
+    .await,
+    1
+  );
+  assert_eq!(
+    count(
+      &db,
+      "SELECT COUNT(*) FROM hashtags WHERE name = 'rollbacktag'"
+    )
+    .await,
+    0
+  );
+  assert_eq!(
+    count(&db, "SELECT COUNT(*) FROM posts_hashtags_relationship").await,
+    1
+  );
+}
+
+const RANKED_AT: &str = "2024-06-01T12:00:00";
+
+async fn seed_ranked_posts(db: &TestDb) {
+  db.client()
+    .await
+    .batch_execute(
+      "INSERT INTO users (username, password_hash, created_at)
+        SELECT 'voter' || i, '', '2024-05-01 00:00:00' FROM generate_series(1, 10) i;
+
+      INSERT INTO posts (title, body, user_id, created_at) VALUES
+        ('A', 'Two hours old, score 3', 1, '2024-06-01 10:00:00'),
+        ('B', 'Twenty hours old, score 5', 1, '2024-05-31 16:00:00'),
+        ('C', 'Thirty minutes old, score 2', 1, '2024-06-01 11:30:00'),
+        ('D', 'Three days old, score 10', 1, '2024-05-29 12:00:00');
+
+      INSERT INTO post_votes (user_id, post_id, value, created_at)
+        SELECT u.id, p.id, 1, '2024-06-01 11:45:00' FROM posts p
+        JOIN users u ON u.id <= CASE p.title WHEN 'A' THEN 3 WHEN 'B' THEN 5 WHEN 'C' THEN 2 ELSE 10 END;
+
+      INSERT INTO hashtags (name, color, created_at) VALUES ('ranked', 'red', '2024-05-01 00:00:00');
+
+      INSERT INTO posts_hashtags_relationship (post_id, hashtag_id)
+        SELECT p.id, t.id FROM posts p, hashtags t;",
+    )
+    .await
+    .unwrap();
+}
+
+async fn fetch_titles(
+  app: &impl actix_web::dev::Service<
+    actix_http::Request,
+    Response = actix_web::dev::ServiceResponse,
+    Error = actix_web::Error,
+  >,
+  uri: &str,
+  access_token: Option<&str>,
+) -> (Vec<String>, Option<String>) {
+  let mut req = test::TestRequest::get().uri(uri);
+
+  if let Some(access_token) = access_token {
+    req = req.insert_header(bearer(access_token));
+  }
+
+  let (status, body) = send(app, req).await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  (
+    body["data"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|p| p["title"].as_str().unwrap().to_owned())
+      .collect(),
+    body["next_cursor"].as_str().map(str::to_owned),
+  )
+}
+
+#[actix_web::test]
+async fn hot_and_rising_order() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  seed_ranked_posts(&db).await;
+
+  let reader = sign_up(&app, "reader").await;
+
+  for access_token in [None, Some(reader.as_str())] {
+    assert_eq!(
+      fetch_titles(
+        &app,
+        &format!("/posts?sort=hot&now={RANKED_AT}"),
+        access_token
+      )
+      .await
+      .0,
+      ["A", "C", "B", "D"]
+    );
+    assert_eq!(
+      fetch_titles(
+        &app,
+        &format!("/posts?sort=rising&now={RANKED_AT}"),
+        access_token
+      )
+      .await
+      .0,
+      ["C", "A", "B"]
+    );
+    assert_eq!(
+      fetch_titles(&app, "/posts?sort=highest", access_token)
+        .await
+        .0,
+      ["D", "B", "A", "C"]
+    );
+  }
+}
+
+#[actix_web::test]
+async fn hot_and_rising_cursor_pages() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  seed_ranked_posts(&db).await;
+
+  let reader = sign_up(&app, "reader").await;
+
+  for access_token in [None, Some(reader.as_str())] {
+    for (sort, expected) in [
+      ("hot", vec!["A", "C", "B", "D"]),
+      ("rising", vec!["C", "A", "B"]),
+      ("latest", vec!["C", "A", "B", "D"]),
+    ] {
+      let mut titles = Vec::new();
+      let mut uri = format!("/posts?sort={sort}&limit=2&now={RANKED_AT}");
+
+      loop {
+        let (page, next_cursor) = fetch_titles(&app, &uri, access_token).await;
+        titles.extend(page);
+
+        match next_cursor {
+          Some(c) => uri = format!("/posts?sort={sort}&limit=2&cursor={c}"),
+          None => break,
+        }
+      }
+
+      assert_eq!(titles, expected, "{sort}");
+    }
+  }
+}
+
+#[actix_web::test]
+async fn latest_cursor_pages_through_microseconds() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  db.client()
+    .await
+    .batch_execute(
+      "INSERT INTO users (username, password_hash, created_at)
+        VALUES ('author', '', '2024-01-01 00:00:00');
+
+      INSERT INTO posts (title, body, user_id, created_at) VALUES
+        ('first', 'Newest', 1, '2024-01-01 00:00:00.000003'),
+        ('second', 'Middle', 1, '2024-01-01 00:00:00.000002'),
+        ('third', 'Oldest', 1, '2024-01-01 00:00:00.000001');
+
+      INSERT INTO hashtags (name, color, created_at) VALUES ('micro', 'red', '2024-01-01 00:00:00');
+
+      INSERT INTO posts_hashtags_relationship (post_id, hashtag_id)
+        SELECT p.id, t.id FROM posts p, hashtags t;",
+    )
+    .await
+    .unwrap();
+
+  for (sort, expected) in [
+    ("latest", ["first", "second", "third"]),
+    ("oldest", ["third", "second", "first"]),
+  ] {
+    let mut titles = Vec::new();
+    let mut uri = format!("/posts?sort={sort}&limit=1");
+
+    loop {
+      let (page, next_cursor) = fetch_titles(&app, &uri, None).await;
+      titles.extend(page);
+
+      match next_cursor {
+        Some(c) => uri = format!("/posts?sort={sort}&limit=1&cursor={c}"),
+        None => break,
+      }
+    }
+
+    assert_eq!(titles, expected, "{sort}");
+  }
+
+  let mut titles = Vec::new();
+  let mut uri = "/users/1/posts?limit=1".to_owned();
+
+  loop {
+    let (page, next_cursor) = fetch_titles(&app, &uri, None).await;
+    titles.extend(page);
+
+    match next_cursor {
+      Some(c) => uri = format!("/users/1/posts?limit=1&cursor={c}"),
+      None => break,
+    }
+  }
+
+  assert_eq!(titles, ["first", "second", "third"]);
+}
+
+#[actix_web::test]
+async fn comment_sorts_apply_to_roots_and_replies() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  db.client()
+    .await
+    .batch_execute(
+      "INSERT INTO users (username, password_hash, created_at)
+        SELECT 'user' || i, '', '2024-01-01 00:00:00' FROM generate_series(0, 10) i;
+
+      INSERT INTO posts (title, body, user_id, created_at)
+        VALUES ('Thread', 'Body', 1, '2024-01-01 00:00:00');
+
+      INSERT INTO post_comments (body, post_id, user_id, comment_id, created_at) VALUES
+        ('R1', 1, 1, NULL, '2024-01-01 00:01:00'),
+        ('R2', 1, 1, NULL, '2024-01-01 00:02:00'),
+        ('R3', 1, 1, NULL, '2024-01-01 00:03:00'),
+        ('a', 1, 1, 1, '2024-01-01 00:04:00'),
+        ('b', 1, 1, 1, '2024-01-01 00:05:00'),
+        ('c', 1, 1, 4, '2024-01-01 00:06:00');
+
+      INSERT INTO comment_votes (user_id, comment_id, value, created_at)
+        SELECT u.id, v.comment_id, CASE WHEN u.id - 1 <= v.ups THEN 1 ELSE -1 END, '2024-01-01 00:10:00'
+        FROM (VALUES (1, 6, 4), (2, 3, 0), (3, 1, 1), (4, 3, 0), (5, 2, 2)) v(comment_id, ups, downs)
+        INNER JOIN users u ON u.id > 1 AND u.id - 1 <= v.ups + v.downs;",
+    )
+    .await
+    .unwrap();
+
+  let comments = |sort: &str| {
+    let uri = format!("/posts/1/comments?sort={sort}&max_depth=1");
+    let app = &app;
+
+    async move {
+      let (status, body) = send(app, test::TestRequest::get().uri(&uri)).await;
+      assert_eq!(status, StatusCode::OK, "{body}");
+      body["data"].as_array().unwrap().clone()
+    }
+  };
+
+  let bodies = |comments: &[serde_json::Value]| -> Vec<String> {
+    comments
+      .iter()
+      .map(|c| c["body"].as_str().unwrap().to_owned())
+      .collect()
+  };
+
+  for (sort, roots, replies) in [
+    ("top", ["R2", "R1", "R3"], ["a", "b"]),
+    ("best", ["R2", "R1", "R3"], ["a", "b"]),
+    ("controversial", ["R1", "R3", "R2"], ["b", "a"]),
+    ("highest", ["R1", "R3", "R2"], ["a", "b"]),
+  ] {
+    let data = comments(sort).await;
+    assert_eq!(bodies(&data), roots, "{sort}");
+
+    let r1 = data.iter().find(|c| c["body"] == "R1").unwrap();
+    assert_eq!(r1["reply_count"], 3, "{sort}");
+    assert_eq!(bodies(r1["replies"].as_array().unwrap()), replies, "{sort}");
+
+    let a = r1["replies"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|c| c["body"] == "a")
+      .unwrap();
+    assert_eq!(a["reply_count"], 1, "{sort}");
+    assert_eq!(a["replies"], json!([]), "{sort}");
+    assert_eq!(a["load_more"]["hidden_replies"], 1, "{sort}");
+  }
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test};
+use serde_json::json;
+
+use common::{bearer, init_app, send, sign_up, TestDb};
+
+#[actix_web::test]
+async fn search_snippets_escape_html() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+  let token = sign_up(&app, "searcher").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/posts")
+      .insert_header(bearer(&token))
+      .set_json(json!({
+        "title": "Markup <b>post</b>",
+        "body": "<script>alert(\"x\")</script> walrus & 'friends' <img src=x onerror=alert(1)>",
+        "hashtags": ["markup"]
+      })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let id = body["data"]["id"].as_i64().unwrap();
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri(&format!("/posts/{id}/comments"))
+      .insert_header(bearer(&token))
+      .set_json(json!({ "body": "<iframe src=evil> narwhal \"quoted\"" })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  for (q, expected) in [
+    (
+      "walrus",
+      "&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; <mark>walrus</mark> &amp; &#39;friends&#39;",
+    ),
+    (
+      "narwhal",
+      "iframe src=evil&gt; <mark>narwhal</mark> &quot;quoted",
+    ),
+  ] {
+    let (status, body) = send(
+      &app,
+      test::TestRequest::get().uri(&format!("/search?q={q}")),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let snippet = body["data"][0]["snippet"].as_str().unwrap();
+
+    assert!(snippet.contains(expected), "{snippet}");
+    assert_eq!(
+      snippet.replace("<mark>", "").replace("</mark>", "").find(['<', '>', '"', '\'']),
+      None,
+      "{snippet}"
+    );
+  }
+}
+
+#[actix_web::test]
+async fn search_filters_by_author_and_hashtags() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::default(), Default::default()).await;
+
+  let alice = sign_up(&app, "alice").await;
+  let bob = sign_up(&app, "bob").await;
+
+  for (token, title, hashtags) in [
+    (&alice, "Walrus at sea", vec!["sea", "cold"]),
+    (&alice, "Walrus diving", vec!["sea"]),
+    (&alice, "Walrus on land", vec!["land"]),
+    (&bob, "Walrus in the cold", vec!["cold"]),
+  ] {
+    let (status, body) = send(
+      &app,
+      test::TestRequest::post()
+        .uri("/posts")
+        .insert_header(bearer(token))
+        .set_json(json!({ "title": title, "body": "A walrus story", "hashtags": hashtags })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+  }
+
+  for (query, expected) in [
+    (
+      "",
+      vec![
+        "Walrus at sea",
+        "Walrus diving",
+        "Walrus in the cold",
+        "Walrus on land",
+      ],
+    ),
+    (
+      "&author=ALICE",
+      vec!["Walrus at sea", "Walrus diving", "Walrus on land"],
+    ),
+    (
+      "&author=%20",
+      vec![
+        "Walrus at sea",
+        "Walrus diving",
+        "Walrus in the cold",
+        "Walrus on land",
+      ],
+    ),
+    ("&hashtag=sea", vec!["Walrus at sea", "Walrus diving"]),
+    ("&all_hashtags=sea,cold", vec!["Walrus at sea"]),
+    (
+      "&any_hashtags=land,cold",
+      vec!["Walrus at sea", "Walrus in the cold", "Walrus on land"],
+    ),
+    (
+      "&exclude_hashtags=sea",
+      vec!["Walrus in the cold", "Walrus on land"],
+    ),
+    ("&author=bob&any_hashtags=sea,land", vec![]),
+  ] {
+    let (status, body) = send(
+      &app,
+      test::TestRequest::get().uri(&format!("/search?q=walrus{query}")),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let mut titles: Vec<&str> = body["data"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|p| p["title"].as_str().unwrap())
+      .collect();
+    titles.sort();
+
+    assert_eq!(titles, expected, "{query}");
+  }
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get()
+      .uri("/search?q=walrus&hashtag=sea&exclude_hashtags=sea")
+      .insert_header(bearer(&bob)),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(body["error"]["name"], "hashtags");
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get()
+      .uri("/search?q=walrus&author=alice&exclude_hashtags=land")
+      .insert_header(bearer(&bob)),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, test};
+use forum_api::{
+  mailer::MemoryMailer,
+  throttle::{self, ThrottleConfig},
+};
+use futures_util::future::join_all;
+use serde_json::json;
+
+use common::{enable_two_factor, init_app, send, sign_up, totp_code, totp_step, TestDb};
+
+async fn concurrent_hits_are_counted(backend: &str) {
+  let db = TestDb::new().await;
+
+  let throttle = ThrottleConfig {
+    backend: Some(backend.to_owned()),
+    ..Default::default()
+  }
+  .build(&db.pool)
+  .unwrap();
+
+  let keys = [(
+    throttle::ACCOUNT_LOGIN,
+    throttle::ACCOUNT_LOGIN.key("target"),
+  )];
+
+  let results = join_all((0..10).map(|_| throttle.hit(&keys))).await;
+
+  assert_eq!(
+    results.iter().filter(|r| r.is_ok()).count(),
+    throttle::ACCOUNT_LOGIN.free_attempts as usize,
+    "{backend}"
+  );
+
+  throttle.clear(&keys[0].1).await.unwrap();
+
+  assert!(throttle.hit(&keys).await.is_ok(), "{backend}");
+}
+
+#[actix_web::test]
+async fn memory_store_counts_concurrent_hits() {
+  concurrent_hits_are_counted("memory").await;
+}
+
+#[actix_web::test]
+async fn postgres_store_counts_concurrent_hits() {
+  concurrent_hits_are_counted("postgres").await;
+}
+
+#[actix_web::test]
+async fn released_hits_are_not_counted() {
+  let db = TestDb::new().await;
+  let throttle = ThrottleConfig::default().build(&db.pool).unwrap();
+  let keys = [(
+    throttle::ACCOUNT_LOGIN,
+    throttle::ACCOUNT_LOGIN.key("alice"),
+  )];
+
+  for _ in 0..throttle::ACCOUNT_LOGIN.free_attempts {
+    throttle.hit(&keys).await.unwrap();
+    throttle.release(&keys[0].1).await.unwrap();
+  }
+
+  for _ in 0..throttle::ACCOUNT_LOGIN.free_attempts {
+    assert!(throttle.hit(&keys).await.is_ok());
+  }
+
+  assert!(throttle.hit(&keys).await.is_err());
+}
+
+#[actix_web::test]
+async fn concurrent_wrong_passwords_are_throttled() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  sign_up(&app, "victim").await;
+
+  let responses = join_all((0..10).map(|_| {
+    send(
+      &app,
+      test::TestRequest::post()
+        .uri("/auth/sign-in")
+        .set_json(json!({ "username": "victim", "password": "wrongpassword" })),
+    )
+  }))
+  .await;
+
+  let rejected = responses
+    .iter()
+    .filter(|(status, _)| *status == StatusCode::BAD_REQUEST)
+    .count();
+
+  let throttled = responses
+    .iter()
+    .filter(|(status, _)| *status == StatusCode::TOO_MANY_REQUESTS)
+    .count();
+
+  assert_eq!(rejected, throttle::ACCOUNT_LOGIN.free_attempts as usize);
+  assert_eq!(rejected + throttled, 10);
+}
+
+#[actix_web::test]
+async fn sign_in_clears_account_attempts() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  sign_up(&app, "forgetful").await;
+
+  let sign_in = |password: &str| {
+    test::TestRequest::post()
+      .uri("/auth/sign-in")
+      .set_json(json!({ "username": "forgetful", "password": password }))
+  };
+
+  for _ in 1..throttle::ACCOUNT_LOGIN.free_attempts {
+    let (status, body) = send(&app, sign_in("wrongpassword")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  }
+
+  let (status, body) = send(&app, sign_in("password123")).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  for _ in 0..throttle::ACCOUNT_LOGIN.free_attempts {
+    let (status, body) = send(&app, sign_in("wrongpassword")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  }
+
+  let (status, body) = send(&app, sign_in("password123")).await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+}
+
+#[actix_web::test]
+async fn two_factor_sign_in_clears_attempts_only_after_the_code() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "careful").await;
+  let (secret, _) = enable_two_factor(&app, &access_token).await;
+
+  let sign_in = |password: &str| {
+    test::TestRequest::post()
+      .uri("/auth/sign-in")
+      .set_json(json!({ "username": "careful", "password": password }))
+  };
+
+  let (status, body) = send(&app, sign_in("password123")).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["two_factor_required"], true);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/sign-in/2fa")
+      .set_json(json!({
+        "challenge_token": body["data"]["challenge"]["challenge_token"],
+        "code": totp_code(&secret, totp_step())
+      })),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  for _ in 0..throttle::ACCOUNT_LOGIN.free_attempts {
+    let (status, body) = send(&app, sign_in("password123")).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["data"]["two_factor_required"], true);
+  }
+
+  let (status, body) = send(&app, sign_in("password123")).await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+}
+
+#[actix_web::test]
+async fn wrong_two_factor_codes_are_throttled() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "guessed").await;
+  enable_two_factor(&app, &access_token).await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/sign-in")
+      .set_json(json!({ "username": "guessed", "password": "password123" })),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let challenge_token = body["data"]["challenge"]["challenge_token"].clone();
+
+  let verify = || {
+    test::TestRequest::post()
+      .uri("/auth/sign-in/2fa")
+      .set_json(json!({ "challenge_token": challenge_token, "code": "000000" }))
+  };
+
+  for _ in 1..throttle::ACCOUNT_LOGIN.free_attempts {
+    let (status, body) = send(&app, verify()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  }
+
+  let (status, body) = send(&app, verify()).await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+}
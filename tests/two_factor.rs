@@ -0,0 +1,313 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_http::Request;
+use actix_web::{
+  dev::{Service, ServiceResponse},
+  http::StatusCode,
+  test, Error,
+};
+use forum_api::{mailer::MemoryMailer, password::PasswordConfig, throttle::ThrottleConfig};
+use serde_json::{json, Value};
+
+use common::{
+  bearer, enable_two_factor, init_app, init_app_with, send, sign_up, totp_code, totp_step, TestDb,
+};
+
+async fn challenge(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  username: &str,
+) -> Value {
+  let (status, body) = send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/sign-in")
+      .set_json(json!({ "username": username, "password": "password123" })),
+  )
+  .await;
+
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["two_factor_required"], true);
+
+  body["data"]["challenge"]["challenge_token"].clone()
+}
+
+async fn verify(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  challenge_token: &Value,
+  second_factor: Value,
+) -> (StatusCode, Value) {
+  let mut body = second_factor;
+  body["challenge_token"] = challenge_token.clone();
+
+  send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/sign-in/2fa")
+      .set_json(body),
+  )
+  .await
+}
+
+async fn confirm(
+  app: &impl Service<Request, Response = ServiceResponse, Error = Error>,
+  access_token: &str,
+  code: &str,
+) -> (StatusCode, Value) {
+  send(
+    app,
+    test::TestRequest::post()
+      .uri("/auth/2fa/confirm")
+      .insert_header(bearer(access_token))
+      .set_json(json!({ "code": code })),
+  )
+  .await
+}
+
+#[actix_web::test]
+async fn enrolment_needs_a_valid_code() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "alice").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/2fa/enroll")
+      .insert_header(bearer(&access_token)),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let secret = body["data"]["secret"].as_str().unwrap().to_owned();
+  let step = totp_step();
+
+  let (status, body) = confirm(&app, &access_token, "000000").await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(body["error"]["name"], "code");
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/sign-in")
+      .set_json(json!({ "username": "alice", "password": "password123" })),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(body["data"]["access_token"].is_string());
+
+  let (status, body) = confirm(&app, &access_token, &totp_code(&secret, step)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["recovery_codes"].as_array().unwrap().len(), 10);
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get()
+      .uri("/auth/2fa")
+      .insert_header(bearer(&access_token)),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["enabled"], true);
+  assert_eq!(body["data"]["recovery_codes_left"], 10);
+
+  challenge(&app, "alice").await;
+}
+
+#[actix_web::test]
+async fn codes_are_accepted_one_step_either_side() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "bob").await;
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::post()
+      .uri("/auth/2fa/enroll")
+      .insert_header(bearer(&access_token)),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let secret = body["data"]["secret"].as_str().unwrap().to_owned();
+  let step = totp_step();
+
+  for step in [step - 2, step + 2] {
+    let (status, body) = confirm(&app, &access_token, &totp_code(&secret, step)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  }
+
+  let (status, body) = confirm(&app, &access_token, &totp_code(&secret, step + 1)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let carol = sign_up(&app, "carol").await;
+  let (secret, _) = enable_two_factor(&app, &carol).await;
+
+  let challenge_token = challenge(&app, "carol").await;
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "code": totp_code(&secret, totp_step()) }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[actix_web::test]
+async fn used_steps_are_rejected() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "dave").await;
+  let (secret, _) = enable_two_factor(&app, &access_token).await;
+  let step = totp_step();
+
+  let challenge_token = challenge(&app, "dave").await;
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "code": totp_code(&secret, step) }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(body["data"]["access_token"].is_string());
+
+  let challenge_token = challenge(&app, "dave").await;
+
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "code": totp_code(&secret, step) }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(body["error"]["name"], "code");
+
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "code": totp_code(&secret, step + 1) }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[actix_web::test]
+async fn recovery_codes_work_once() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "erin").await;
+  let (_, recovery_codes) = enable_two_factor(&app, &access_token).await;
+
+  let challenge_token = challenge(&app, "erin").await;
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "recovery_code": recovery_codes[0].to_uppercase() }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let challenge_token = challenge(&app, "erin").await;
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "recovery_code": recovery_codes[0] }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+  let (status, body) = send(
+    &app,
+    test::TestRequest::get()
+      .uri("/auth/2fa")
+      .insert_header(bearer(&access_token)),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"]["recovery_codes_left"], 9);
+}
+
+#[actix_web::test]
+async fn challenge_dies_after_too_many_wrong_codes() {
+  let db = TestDb::new().await;
+
+  let throttle = ThrottleConfig {
+    backend: Some("postgres".to_owned()),
+    ..Default::default()
+  };
+
+  let passwords = PasswordConfig {
+    algorithm: Some("bcrypt".to_owned()),
+    bcrypt_cost: Some(4),
+    ..Default::default()
+  };
+
+  let app = init_app_with(
+    &db,
+    Arc::new(MemoryMailer::default()),
+    Default::default(),
+    passwords,
+    throttle,
+  )
+  .await;
+
+  let access_token = sign_up(&app, "frank").await;
+  let (secret, _) = enable_two_factor(&app, &access_token).await;
+
+  let challenge_token = challenge(&app, "frank").await;
+
+  for _ in 0..5 {
+    // Only the challenge's own limit is under test here, not the sign-in throttle.
+    db.client()
+      .await
+      .execute("DELETE FROM auth_attempts", &[])
+      .await
+      .unwrap();
+
+    let (status, body) = verify(&app, &challenge_token, json!({ "code": "000000" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  }
+
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "code": totp_code(&secret, totp_step()) }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+  assert_eq!(body["error"]["name"], "re-auth");
+}
+
+#[actix_web::test]
+async fn expired_challenges_are_rejected() {
+  let db = TestDb::new().await;
+  let app = init_app(&db, Arc::new(MemoryMailer::default()), Default::default()).await;
+
+  let access_token = sign_up(&app, "grace").await;
+  let (secret, _) = enable_two_factor(&app, &access_token).await;
+
+  let challenge_token = challenge(&app, "grace").await;
+
+  db.client()
+    .await
+    .execute(
+      "UPDATE login_challenges SET expires_at = created_at - INTERVAL '1 second'",
+      &[],
+    )
+    .await
+    .unwrap();
+
+  let (status, body) = verify(
+    &app,
+    &challenge_token,
+    json!({ "code": totp_code(&secret, totp_step()) }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+  assert_eq!(body["error"]["name"], "re-auth");
+}